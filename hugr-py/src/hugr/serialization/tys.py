@@ -65,6 +65,7 @@ class TypeTypeParam(ConfiguredBaseModel):
 class BoundedNatParam(ConfiguredBaseModel):
     tp: Literal["BoundedNat"] = "BoundedNat"
     bound: int | None
+    min: int = 0
 
 
 class OpaqueParam(ConfiguredBaseModel):
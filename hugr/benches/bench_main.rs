@@ -6,6 +6,7 @@ mod benchmarks;
 use criterion::criterion_main;
 
 criterion_main! {
+    benchmarks::extension::benches,
     benchmarks::hugr::benches,
     benchmarks::types::benches,
 }
@@ -0,0 +1,138 @@
+// Required for black_box uses
+#![allow(clippy::unit_arg)]
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use hugr::extension::prelude::USIZE_T;
+use hugr::extension::simple_op::MakeExtensionOp;
+use hugr::extension::{ExtensionId, ExtensionRegistry, PRELUDE};
+use hugr::ops::custom::ExtensionOp;
+use hugr::std_extensions::arithmetic::int_ops::IntOpDef;
+use hugr::std_extensions::logic::{self, ConcreteLogicOp, NotOp, LOGIC_REG};
+use hugr::types::{FunctionType, Type, TypeArg, TypeBound};
+use hugr::Extension;
+
+use strum::IntoEnumIterator;
+
+use criterion::{black_box, criterion_group, AxisScale, BatchSize, Criterion, PlotConfiguration};
+
+fn bench_signature(c: &mut Criterion) {
+    let mut group = c.benchmark_group("extension");
+    group.plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    let and_def = logic::EXTENSION.get_op("And").unwrap();
+    let args = [TypeArg::BoundedNat { n: 8 }];
+    group.bench_function("nary_logic_signature", |b| {
+        b.iter(|| black_box(and_def.compute_signature(&args, &LOGIC_REG)))
+    });
+
+    group.finish();
+}
+
+fn bench_load_ops(c: &mut Criterion) {
+    let mut group = c.benchmark_group("extension");
+
+    let arity = |n| [TypeArg::BoundedNat { n }];
+    let ops: Vec<ExtensionOp> = (0..1000)
+        .map(|i| {
+            let ext = &logic::EXTENSION;
+            match i % 3 {
+                0 => ext.instantiate_extension_op("And", arity(2), &LOGIC_REG),
+                1 => ext.instantiate_extension_op("Or", arity(3), &LOGIC_REG),
+                _ => ext.instantiate_extension_op("Not", [], &LOGIC_REG),
+            }
+            .unwrap()
+        })
+        .collect();
+    group.bench_function("logic_ops_from_extension_op", |b| {
+        b.iter(|| {
+            for op in &ops {
+                black_box(
+                    ConcreteLogicOp::from_extension_op(op).is_ok()
+                        || NotOp::from_extension_op(op).is_ok(),
+                );
+            }
+        })
+    });
+
+    // Compares the derived `FromStr` used by `try_from_name` with a lookup in
+    // a map from names to variants, for every variant of a large op enum.
+    let names: Vec<&'static str> = IntOpDef::iter().map(Into::into).collect();
+    group.bench_function("int_op_from_name", |b| {
+        b.iter(|| {
+            for name in &names {
+                black_box(IntOpDef::from_str(black_box(name)).ok());
+            }
+        })
+    });
+    let map: HashMap<&'static str, IntOpDef> = IntOpDef::iter().map(|op| (op.into(), op)).collect();
+    group.bench_function("int_op_from_name_map", |b| {
+        b.iter(|| {
+            for name in &names {
+                black_box(map.get(black_box(name)).copied());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+/// An extension with `n` type-scheme operations over a type it defines.
+fn many_ops_extension(n: usize) -> Extension {
+    let mut ext = Extension::new(ExtensionId::new_unchecked("many_ops"));
+    ext.add_type("T".into(), vec![], "".into(), TypeBound::Eq.into())
+        .unwrap();
+    let t = Type::new_extension(ext.get_type("T").unwrap().instantiate([]).unwrap());
+    for i in 0..n {
+        let sig = FunctionType::new(vec![t.clone(), USIZE_T], vec![t.clone()]);
+        ext.add_op(format!("op{i}").into(), "".into(), sig).unwrap();
+    }
+    ext
+}
+
+fn bench_registry_rebuild(c: &mut Criterion) {
+    let mut group = c.benchmark_group("extension");
+
+    group.bench_function("registry_new_100_ops", |b| {
+        b.iter_batched(
+            || many_ops_extension(100),
+            |ext| black_box(ExtensionRegistry::try_new([PRELUDE.to_owned(), ext])),
+            BatchSize::SmallInput,
+        )
+    });
+    let ext = many_ops_extension(100);
+    ExtensionRegistry::try_new([PRELUDE.to_owned(), ext.clone()]).unwrap();
+    group.bench_function("registry_rebuild_100_ops", |b| {
+        b.iter_batched(
+            || ext.clone(),
+            |ext| black_box(ExtensionRegistry::try_new([PRELUDE.to_owned(), ext])),
+            BatchSize::SmallInput,
+        )
+    });
+    let (_, fingerprint) =
+        ExtensionRegistry::try_new_fingerprinted([PRELUDE.to_owned(), many_ops_extension(100)])
+            .unwrap();
+    group.bench_function("registry_with_fingerprint_100_ops", |b| {
+        b.iter_batched(
+            || many_ops_extension(100),
+            |ext| {
+                black_box(ExtensionRegistry::try_new_with_fingerprint(
+                    [PRELUDE.to_owned(), ext],
+                    &fingerprint,
+                ))
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default();
+    targets =
+        bench_signature,
+        bench_load_ops,
+        bench_registry_rebuild,
+}
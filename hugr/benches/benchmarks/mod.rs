@@ -1,2 +1,3 @@
+pub mod extension;
 pub mod hugr;
 pub mod types;
@@ -1,5 +1,6 @@
 //! Algorithms using the Hugr.
 
 pub mod const_fold;
+pub mod dead_funcs;
 mod half_node;
 pub mod nest_cfgs;
@@ -47,7 +47,9 @@ pub(crate) fn sorted_consts(consts: &[(IncomingPort, Value)]) -> Vec<&Value> {
 pub fn fold_leaf_op(op: &OpType, consts: &[(IncomingPort, Value)]) -> ConstFoldResult {
     match op {
         OpType::Noop { .. } => out_row([consts.first()?.1.clone()]),
-        OpType::MakeTuple { .. } => {
+        OpType::MakeTuple(t) => {
+            // Only fold once every element of the tuple is known.
+            (consts.len() == t.tys.len()).then_some(())?;
             out_row([Value::tuple(sorted_consts(consts).into_iter().cloned())])
         }
         OpType::UnpackTuple { .. } => {
@@ -212,8 +214,8 @@ mod test {
 
     use super::*;
     use crate::extension::prelude::{sum_with_error, BOOL_T};
-    use crate::extension::PRELUDE;
-    use crate::ops::UnpackTuple;
+    use crate::extension::{PRELUDE, PRELUDE_REGISTRY};
+    use crate::ops::{MakeTuple, UnpackTuple};
     use crate::std_extensions::arithmetic;
     use crate::std_extensions::arithmetic::conversions::ConvertOpDef;
     use crate::std_extensions::arithmetic::float_ops::FloatOps;
@@ -296,6 +298,51 @@ mod test {
         assert_fully_folded(&h, &expected);
     }
 
+    #[test]
+    fn test_make_tuple() {
+        let op: OpType = MakeTuple::new(type_row![BOOL_T, BOOL_T]).into();
+        let consts = [
+            (1.into(), Value::false_val()),
+            (0.into(), Value::true_val()),
+        ];
+        let out = fold_leaf_op(&op, &consts).unwrap();
+        assert_eq!(
+            &out[..],
+            &[(
+                0.into(),
+                Value::tuple([Value::true_val(), Value::false_val()])
+            )]
+        );
+
+        // A tuple with an unknown element cannot be folded.
+        assert_eq!(fold_leaf_op(&op, &consts[..1]), None);
+    }
+
+    #[test]
+    fn test_pack_unpack() -> Result<(), Box<dyn std::error::Error>> {
+        let tys = type_row![BOOL_T, BOOL_T];
+        let mut build = DFGBuilder::new(FunctionType::new(type_row![], tys.clone())).unwrap();
+
+        let a = build.add_load_const(Value::true_val());
+        let b = build.add_load_const(Value::false_val());
+        let tup = build.add_dataflow_op(MakeTuple::new(tys.clone()), [a, b])?;
+        let unpack = build.add_dataflow_op(UnpackTuple::new(tys), tup.outputs())?;
+
+        let mut h = build.finish_hugr_with_outputs(unpack.outputs(), &PRELUDE_REGISTRY)?;
+        constant_fold_pass(&mut h, &PRELUDE_REGISTRY);
+
+        // The pack/unpack pair folds away, leaving the two constants.
+        let output = h.get_io(h.root()).unwrap()[1];
+        let outputs = h
+            .in_value_types(output)
+            .map(|(p, _)| get_const(&h, output, p).unwrap().0)
+            .collect_vec();
+        assert_eq!(outputs, [Value::true_val(), Value::false_val()]);
+        // Input, Output and a Const and LoadConstant for each value.
+        assert_eq!(h.children(h.root()).count(), 6);
+        Ok(())
+    }
+
     #[rstest]
     #[case(NaryLogic::And, [true, true, true], true)]
     #[case(NaryLogic::And, [true, false, true], false)]
@@ -311,8 +358,7 @@ mod test {
         let ins = ins.map(|b| build.add_load_const(Value::from_bool(b)));
         let logic_op = build.add_dataflow_op(op.with_n_inputs(ins.len() as u64), ins)?;
 
-        let reg =
-            ExtensionRegistry::try_new([PRELUDE.to_owned(), logic::EXTENSION.to_owned()]).unwrap();
+        let reg = ExtensionRegistry::with_prelude([logic::EXTENSION.to_owned()]).unwrap();
         let mut h = build.finish_hugr_with_outputs(logic_op.outputs(), &reg)?;
         constant_fold_pass(&mut h, &reg);
 
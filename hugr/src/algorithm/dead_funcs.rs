@@ -0,0 +1,108 @@
+//! Removal of unreachable function definitions.
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::hugr::HugrMut;
+use crate::Node;
+
+/// Removes the module-level function definitions that cannot be reached from
+/// `keep`.
+///
+/// A function is reachable if it is in `keep`, or if it is the source of a
+/// static edge (e.g. from a [`crate::ops::Call`] or a
+/// [`crate::ops::LoadFunction`]) into the body of a reachable function. Any
+/// static edge counts, so functions that are only loaded as values are kept.
+/// Nodes in `keep` that are not functions are also explored, so that their
+/// references are preserved.
+///
+/// Only the [exported functions][crate::HugrView::exported_functions] of a module are
+/// candidates for removal; declarations are left in place. Returns the set of
+/// removed function nodes.
+pub fn remove_dead_functions(hugr: &mut impl HugrMut, keep: &[Node]) -> HashSet<Node> {
+    let funcs: HashSet<Node> = hugr.exported_functions().collect();
+
+    let mut live: HashSet<Node> = keep.iter().copied().collect();
+    let mut queue: VecDeque<Node> = keep.iter().copied().collect();
+    while let Some(node) = queue.pop_front() {
+        if let Some(src) = hugr.static_source(node) {
+            if funcs.contains(&src) && live.insert(src) {
+                queue.push_back(src);
+            }
+        }
+        queue.extend(hugr.children(node));
+    }
+
+    let dead: HashSet<Node> = funcs.difference(&live).copied().collect();
+    for &func in &dead {
+        remove_subtree(hugr, func);
+    }
+    dead
+}
+
+/// Removes a node and all of its descendants.
+fn remove_subtree(hugr: &mut impl HugrMut, node: Node) {
+    let mut stack = vec![node];
+    let mut to_remove = Vec::new();
+    while let Some(n) = stack.pop() {
+        stack.extend(hugr.children(n));
+        to_remove.push(n);
+    }
+    for n in to_remove {
+        hugr.remove_node(n);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use itertools::Itertools;
+
+    use super::remove_dead_functions;
+    use crate::builder::{Container, Dataflow, DataflowSubContainer, HugrBuilder, ModuleBuilder};
+    use crate::extension::prelude::BOOL_T;
+    use crate::extension::PRELUDE_REGISTRY;
+    use crate::ops::handle::{FuncID, NodeHandle};
+    use crate::types::FunctionType;
+    use crate::{type_row, HugrView};
+
+    #[test]
+    fn remove_unreachable() {
+        let mut module = ModuleBuilder::new();
+        let sig = FunctionType::new_endo(type_row![BOOL_T]);
+        // Defines a function calling each of `callees` in turn, and loading
+        // `loaded` as a value.
+        let mut define = |name, callees: &[FuncID<true>], loaded: Option<FuncID<true>>| {
+            let mut f = module.define_function(name, sig.clone().into()).unwrap();
+            let mut b = f.input_wires().exactly_one().unwrap();
+            for callee in callees {
+                b = f
+                    .call(callee, &[], [b], &PRELUDE_REGISTRY)
+                    .unwrap()
+                    .out_wire(0);
+            }
+            if let Some(loaded) = loaded {
+                f.load_func(&loaded, &[], &PRELUDE_REGISTRY).unwrap();
+            }
+            FuncID::from(f.finish_with_outputs([b]).unwrap().node())
+        };
+        let loaded = define("loaded", &[], None);
+        let c = define("c", &[], Some(loaded));
+        let b = define("b", &[c], None);
+        let a = define("a", &[b], None);
+        let e = define("e", &[], None);
+        let d = define("d", &[e, b], None);
+        let mut h = module.finish_prelude_hugr().unwrap();
+
+        let removed = remove_dead_functions(&mut h, &[a.node()]);
+        assert_eq!(removed, HashSet::from([d.node(), e.node()]));
+        assert_eq!(
+            h.exported_functions().collect_vec(),
+            [loaded.node(), c.node(), b.node(), a.node()]
+        );
+        h.update_validate(&PRELUDE_REGISTRY).unwrap();
+
+        // Nothing else is dead.
+        assert!(remove_dead_functions(&mut h, &[a.node()]).is_empty());
+    }
+}
@@ -648,10 +648,10 @@ pub(crate) mod test {
         );
         transform_cfg_to_nested(&mut IdentityCfgMap::new(rc));
         h.update_validate(&PRELUDE_REGISTRY).unwrap();
-        assert_eq!(1, depth(&h, entry));
-        assert_eq!(1, depth(&h, exit));
+        assert_eq!(1, h.depth(entry));
+        assert_eq!(1, h.depth(exit));
         for n in [split, left, right, merge, head, tail] {
-            assert_eq!(3, depth(&h, n));
+            assert_eq!(3, h.depth(n));
         }
         let first = [split, left, right, merge]
             .iter()
@@ -755,13 +755,13 @@ pub(crate) mod test {
         let m = SiblingMut::<CfgID>::try_new(&mut h, root).unwrap();
         transform_cfg_to_nested(&mut IdentityCfgMap::new(m));
         h.update_validate(&PRELUDE_REGISTRY).unwrap();
-        assert_eq!(1, depth(&h, entry));
-        assert_eq!(3, depth(&h, head));
+        assert_eq!(1, h.depth(entry));
+        assert_eq!(3, h.depth(head));
         for n in [split, left, right, merge] {
-            assert_eq!(5, depth(&h, n));
+            assert_eq!(5, h.depth(n));
         }
-        assert_eq!(3, depth(&h, tail));
-        assert_eq!(1, depth(&h, exit));
+        assert_eq!(3, h.depth(tail));
+        assert_eq!(1, h.depth(exit));
         Ok(())
     }
 
@@ -957,11 +957,4 @@ pub(crate) mod test {
 
         Ok((head, tail))
     }
-
-    pub fn depth(h: &Hugr, n: Node) -> u32 {
-        match h.get_parent(n) {
-            Some(p) => 1 + depth(h, p),
-            None => 0,
-        }
-    }
 }
@@ -89,11 +89,12 @@
 use thiserror::Error;
 
 use crate::extension::SignatureError;
+use crate::hugr::hugrmut::ConnectError;
 use crate::hugr::ValidationError;
 use crate::ops::handle::{BasicBlockID, CfgID, ConditionalID, DfgID, FuncID, TailLoopID};
-use crate::ops::{NamedOp, OpType};
+use crate::ops::{NamedOp, OpName, OpType};
 use crate::types::ConstTypeError;
-use crate::types::Type;
+use crate::types::{EdgeKind, Type};
 use crate::{Node, Port, Wire};
 
 pub mod handle;
@@ -105,7 +106,7 @@ pub use build_traits::{
 };
 
 mod dataflow;
-pub use dataflow::{DFGBuilder, DFGWrapper, FunctionBuilder};
+pub use dataflow::{DFGBuilder, DFGWrapper, FunctionBuilder, StrictBuilder};
 
 mod module;
 pub use module::ModuleBuilder;
@@ -119,6 +120,9 @@ pub use tail_loop::TailLoopBuilder;
 mod conditional;
 pub use conditional::{CaseBuilder, ConditionalBuilder};
 
+mod description;
+pub use description::DescriptionError;
+
 mod circuit;
 pub use circuit::{CircuitBuildError, CircuitBuilder};
 
@@ -141,6 +145,18 @@ pub enum BuildError {
     /// CFG can only have one entry.
     #[error("CFG entry node already built for CFG node: {0:?}.")]
     EntryBuiltError(Node),
+    /// A basic block must have at least one successor.
+    #[error("Tried to build a basic block with no branches in CFG node: {0:?}.")]
+    EmptyPredicate(Node),
+    /// A wire selecting a branch is not a sum with the expected number of
+    /// variants.
+    #[error("Branch predicate wire {wire:?} has type {typ}, which is not a sum of {n_variants} variants.")]
+    #[allow(missing_docs)]
+    InvalidPredicate {
+        wire: Wire,
+        typ: Type,
+        n_variants: usize,
+    },
     /// Node was expected to have a certain type but was found to not.
     #[error("Node with index {node:?} does not have type {op_desc:?} as expected.")]
     #[allow(missing_docs)]
@@ -150,10 +166,28 @@ pub enum BuildError {
         /// Description of expected node.
         op_desc: &'static str,
     },
+    /// An operation added with [`Dataflow::add_op1`] does not have exactly
+    /// one output.
+    #[error("Expected a single output from {}, found {n_outputs}.", .op.name())]
+    #[allow(missing_docs)]
+    ExpectedSingleOutput { op: OpType, n_outputs: usize },
+    /// An operation over a variable number of inputs, such as a logical
+    /// "and", was given none.
+    #[error("Tried to add a {op} operation with no inputs.")]
+    #[allow(missing_docs)]
+    NoInputs { op: OpName },
     /// Error building Conditional node
     #[error("Error building Conditional node: {0}.")]
     ConditionalError(#[from] conditional::ConditionalBuildError),
 
+    /// Error in the description of a HUGR, see [`crate::Hugr::from_description`].
+    #[error("Invalid HUGR description: {0}")]
+    DescriptionError(#[from] DescriptionError),
+
+    /// Two ports could not be connected.
+    #[error("Cannot connect ports: {0}")]
+    ConnectError(#[from] ConnectError),
+
     /// Wire not found in Hugr
     #[error("Wire not found in Hugr: {0:?}.")]
     WireNotFound(Wire),
@@ -215,6 +249,31 @@ pub enum BuilderWiringError {
         dst_offset: Port,
         typ: Type,
     },
+    /// The ports of an edge have different kinds, e.g. mismatched value types.
+    ///
+    /// Only reported by a [`StrictBuilder`].
+    #[error("Cannot connect {src} ({src_offset}) of kind {src_kind:?} with {dst} ({dst_offset}) of kind {dst_kind:?}.")]
+    #[allow(missing_docs)]
+    TypeMismatch {
+        src: Node,
+        src_offset: Port,
+        src_kind: EdgeKind,
+        dst: Node,
+        dst_offset: Port,
+        dst_kind: EdgeKind,
+    },
+    /// Tried to connect a port that the node does not have.
+    ///
+    /// Only reported by a [`StrictBuilder`].
+    #[error("Node {node} has no port {port}.")]
+    #[allow(missing_docs)]
+    InvalidPort { node: Node, port: Port },
+    /// A value input of a new node was left unconnected.
+    ///
+    /// Only reported by a [`StrictBuilder`].
+    #[error("Input {port} of node {node} is not connected.")]
+    #[allow(missing_docs)]
+    UnconnectedInput { node: Node, port: Port },
 }
 
 #[cfg(test)]
@@ -3,15 +3,16 @@ use crate::hugr::views::HugrView;
 use crate::hugr::{NodeMetadata, ValidationError};
 use crate::ops::{self, MakeTuple, OpTag, OpTrait, OpType, Tag};
 use crate::utils::collect_array;
-use crate::{IncomingPort, Node, OutgoingPort};
+use crate::{IncomingPort, Node, OutgoingPort, PortIndex};
 
+use std::collections::HashSet;
 use std::iter;
 
 use super::{
     handle::{BuildHandle, Outputs},
     CircuitBuilder,
 };
-use super::{BuilderWiringError, FunctionBuilder};
+use super::{BuilderWiringError, FunctionBuilder, StrictBuilder};
 
 use crate::{
     hugr::NodeType,
@@ -19,6 +20,7 @@ use crate::{
     types::EdgeKind,
 };
 
+use crate::extension::prelude::{copy_op, discard_op};
 use crate::extension::{ExtensionRegistry, ExtensionSet, PRELUDE_REGISTRY};
 use crate::types::{FunctionType, PolyFuncType, Type, TypeArg, TypeRow};
 
@@ -161,6 +163,27 @@ pub trait SubContainer: Container {
 pub trait Dataflow: Container {
     /// Return the number of inputs to the dataflow sibling graph.
     fn num_inputs(&self) -> usize;
+    /// Whether wires are checked as they are connected, see [`Dataflow::strict`].
+    ///
+    /// Returns `false` unless overridden.
+    fn is_strict(&self) -> bool {
+        false
+    }
+    /// Wrap the builder so that each connection is checked when it is made.
+    ///
+    /// By default builders are lazy: mismatched types and missing inputs are
+    /// only reported when the HUGR is validated in [`HugrBuilder::finish_hugr`].
+    /// A strict builder instead checks that every wire connects existing ports
+    /// of the same type, and that every value input of a new node is
+    /// connected, returning the error from the offending call without adding
+    /// the node. This is slower, and does not extend to nested builders, which
+    /// must be wrapped separately.
+    fn strict(self) -> StrictBuilder<Self>
+    where
+        Self: Sized,
+    {
+        StrictBuilder::new(self)
+    }
     /// Return indices of input and output nodes.
     fn io(&self) -> [Node; 2] {
         self.hugr()
@@ -196,6 +219,29 @@ pub trait Dataflow: Container {
         self.add_dataflow_node(NodeType::new_auto(op), input_wires)
     }
 
+    /// Add a dataflow op with a single output to the sibling graph, wiring up
+    /// the `input_wires` to the incoming ports of the resulting node, and
+    /// return its output wire.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BuildError::ExpectedSingleOutput`] error, without adding
+    /// the op, if it does not have exactly one value output, or a
+    /// [`BuildError::OperationWiring`] error if the `input_wires` cannot be
+    /// connected.
+    fn add_op1(
+        &mut self,
+        op: impl Into<OpType>,
+        input_wires: impl IntoIterator<Item = Wire>,
+    ) -> Result<Wire, BuildError> {
+        let op = op.into();
+        let n_outputs = op.value_output_count();
+        if n_outputs != 1 {
+            return Err(BuildError::ExpectedSingleOutput { op, n_outputs });
+        }
+        Ok(self.add_dataflow_op(op, input_wires)?.out_wire(0))
+    }
+
     /// Add a dataflow [`NodeType`] to the sibling graph, wiring up the `input_wires` to the
     /// incoming ports of the resulting node.
     ///
@@ -265,14 +311,25 @@ pub trait Dataflow: Container {
         &mut self,
         output_wires: impl IntoIterator<Item = Wire>,
     ) -> Result<(), BuildError> {
-        let [_, out] = self.io();
-        wire_up_inputs(output_wires.into_iter().collect_vec(), out, self).map_err(|error| {
-            BuildError::OutputWiring {
-                container_op: self.hugr().get_optype(self.container_node()).clone(),
-                container_node: self.container_node(),
-                error,
-            }
-        })
+        let output_wires = output_wires.into_iter().collect_vec();
+        self.prepare_outputs(&output_wires)?;
+        wire_up_outputs(self, output_wires)
+    }
+
+    /// Prepare the Output node for the `output_wires` passed to
+    /// [`Dataflow::set_outputs`], before they are connected.
+    ///
+    /// Does nothing unless overridden by a builder inferring its output types
+    /// from the wires, see [`DFGBuilder::new_inferred`]. Wrapping builders
+    /// must forward this to the builder they wrap.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the graph unchanged, if the outputs cannot be
+    /// set to `output_wires`.
+    fn prepare_outputs(&mut self, output_wires: &[Wire]) -> Result<(), BuildError> {
+        let _ = output_wires;
+        Ok(())
     }
 
     /// Return an array of the input wires.
@@ -475,6 +532,7 @@ pub trait Dataflow: Container {
         let sum_rows: Vec<_> = sum_rows.into_iter().collect();
         let n_cases = sum_rows.len();
         let n_out_wires = output_types.len();
+        check_predicate(self, sum_wire, n_cases)?;
 
         let conditional_id = self.add_dataflow_op(
             ops::Conditional {
@@ -511,6 +569,43 @@ pub trait Dataflow: Container {
         }
     }
 
+    /// The type of the value carried by `wire`, or `None` if `wire` is not
+    /// a Value output of a node in the HUGR.
+    ///
+    /// Like [`Dataflow::get_wire_type`], but useful to check types are
+    /// compatible before connecting wires.
+    fn wire_type(&self, wire: Wire) -> Option<Type> {
+        self.hugr().port_type(wire.node(), wire.source())
+    }
+
+    /// Add a prelude [copy] op duplicating the value on `wire`, returning
+    /// the two copies.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the type of `wire` is not
+    /// copyable, or if there is an error adding the node.
+    ///
+    /// [copy]: crate::extension::prelude::copy_op
+    fn add_copy(&mut self, wire: Wire) -> Result<[Wire; 2], BuildError> {
+        let op = copy_op(self.get_wire_type(wire)?)?;
+        Ok(self.add_dataflow_op(op, [wire])?.outputs_arr())
+    }
+
+    /// Add a prelude [discard] op consuming the value on `wire`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the type of `wire` is not
+    /// copyable, or if there is an error adding the node.
+    ///
+    /// [discard]: crate::extension::prelude::discard_op
+    fn add_discard(&mut self, wire: Wire) -> Result<(), BuildError> {
+        let op = discard_op(self.get_wire_type(wire)?)?;
+        self.add_dataflow_op(op, [wire])?;
+        Ok(())
+    }
+
     /// Add a [`MakeTuple`] node and wire in the `values` Wires,
     /// returning the Wire corresponding to the tuple.
     ///
@@ -648,17 +743,78 @@ fn add_node_with_wires<T: Dataflow + ?Sized>(
     let num_outputs = nodetype.op().value_output_count();
     let op_node = data_builder.add_child_node(nodetype.clone());
 
-    wire_up_inputs(inputs, op_node, data_builder).map_err(|error| BuildError::OperationWiring {
-        op: nodetype.into_op(),
-        error,
+    wire_up_inputs(inputs, op_node, data_builder).map_err(|error| {
+        if data_builder.is_strict() {
+            // No edge was added, so removing the node leaves the graph as it
+            // was before the call.
+            data_builder.hugr_mut().remove_node(op_node);
+        }
+        BuildError::OperationWiring {
+            op: nodetype.into_op(),
+            error,
+        }
     })?;
 
     Ok((op_node, num_outputs))
 }
 
+/// Connect `output_wires` to the output node of a dataflow builder, see
+/// [`Dataflow::set_outputs`].
+fn wire_up_outputs<T: Dataflow + ?Sized>(
+    data_builder: &mut T,
+    output_wires: Vec<Wire>,
+) -> Result<(), BuildError> {
+    let [_, out] = data_builder.io();
+    wire_up_inputs(output_wires, out, data_builder)
+        .map_err(|error| output_wiring_error(data_builder, error))
+}
+
+/// Check that `output_wires` can be connected to the output node of a
+/// dataflow builder, ignoring the types of its ports.
+///
+/// This allows [`Dataflow::prepare_outputs`] to reject the wires before
+/// changing the output node.
+pub(super) fn check_output_wires<T: Dataflow + ?Sized>(
+    data_builder: &T,
+    output_wires: &[Wire],
+) -> Result<(), BuildError> {
+    let [_, out] = data_builder.io();
+    let mut linear_sources = HashSet::new();
+    for (dst_port, wire) in output_wires.iter().enumerate() {
+        check_wire(
+            data_builder.hugr(),
+            false,
+            wire.node(),
+            wire.source(),
+            out,
+            dst_port.into(),
+            &mut linear_sources,
+        )
+        .map_err(|error| output_wiring_error(data_builder, error))?;
+    }
+    Ok(())
+}
+
+fn output_wiring_error<T: Dataflow + ?Sized>(
+    data_builder: &T,
+    error: BuilderWiringError,
+) -> BuildError {
+    BuildError::OutputWiring {
+        container_op: data_builder
+            .hugr()
+            .get_optype(data_builder.container_node())
+            .clone(),
+        container_node: data_builder.container_node(),
+        error,
+    }
+}
+
 /// Connect each of the `inputs` wires sequentially to the input ports of
 /// `op_node`.
 ///
+/// All the connections are checked before any is made, so the graph is
+/// unchanged if an error is returned.
+///
 /// # Errors
 ///
 /// Returns a [`BuilderWiringError`] if any of the connections produces an
@@ -668,91 +824,186 @@ fn wire_up_inputs<T: Dataflow + ?Sized>(
     op_node: Node,
     data_builder: &mut T,
 ) -> Result<(), BuilderWiringError> {
-    for (dst_port, wire) in inputs.into_iter().enumerate() {
-        wire_up(data_builder, wire.node(), wire.source(), op_node, dst_port)?;
+    let inputs = inputs.into_iter().collect_vec();
+    let strict = data_builder.is_strict();
+    let base = data_builder.hugr();
+    let mut linear_sources = HashSet::new();
+    let mut order_edges = Vec::with_capacity(inputs.len());
+    for (dst_port, wire) in inputs.iter().enumerate() {
+        order_edges.push(check_wire(
+            base,
+            strict,
+            wire.node(),
+            wire.source(),
+            op_node,
+            dst_port.into(),
+            &mut linear_sources,
+        )?);
+    }
+    if strict {
+        let op = base.get_optype(op_node);
+        if let Some(port) = base.node_inputs(op_node).find(|&p| {
+            matches!(op.port_kind(p), Some(EdgeKind::Value(_)))
+                && p.index() >= inputs.len()
+                && !base.is_linked(op_node, p)
+        }) {
+            return Err(BuilderWiringError::UnconnectedInput {
+                node: op_node,
+                port: port.into(),
+            });
+        }
+    }
+
+    let base = data_builder.hugr_mut();
+    for ((dst_port, wire), order_edge) in inputs.into_iter().enumerate().zip(order_edges) {
+        if let Some((src, src_sibling)) = order_edge {
+            // Add a state order constraint for the non-local edge.
+            base.add_other_edge(src, src_sibling);
+        }
+        base.connect(wire.node(), wire.source(), op_node, dst_port);
     }
     Ok(())
 }
 
-/// Add edge from src to dst.
+/// Check that `wire` carries a predicate with `n_variants` variants.
+///
+/// # Errors
+///
+/// Returns [`BuildError::InvalidPredicate`] if the wire's type is not a sum
+/// with `n_variants` variants.
+pub(super) fn check_predicate<T: Dataflow + ?Sized>(
+    data_builder: &T,
+    wire: Wire,
+    n_variants: usize,
+) -> Result<(), BuildError> {
+    let typ = data_builder.get_wire_type(wire)?;
+    if typ.is_predicate(n_variants) {
+        Ok(())
+    } else {
+        Err(BuildError::InvalidPredicate {
+            wire,
+            typ,
+            n_variants,
+        })
+    }
+}
+
+/// Check that an edge can be added from `src` to `dst`, checking the port
+/// kinds as well if `strict` is set.
+///
+/// `linear_sources` holds the sources of the other non-copyable values
+/// connected in the same call. Returns the state order edge to add along with
+/// a non-local edge, if any.
 ///
 /// # Errors
 ///
 /// Returns a [`BuilderWiringError`] if the edge is invalid.
-fn wire_up<T: Dataflow + ?Sized>(
-    data_builder: &mut T,
+fn check_wire(
+    base: &Hugr,
+    strict: bool,
     src: Node,
-    src_port: impl Into<OutgoingPort>,
+    src_port: OutgoingPort,
     dst: Node,
-    dst_port: impl Into<IncomingPort>,
-) -> Result<bool, BuilderWiringError> {
-    let src_port = src_port.into();
-    let dst_port = dst_port.into();
-    let base = data_builder.hugr_mut();
+    dst_port: IncomingPort,
+    linear_sources: &mut HashSet<(Node, OutgoingPort)>,
+) -> Result<Option<(Node, Node)>, BuilderWiringError> {
+    if strict {
+        check_wire_types(base, src, src_port, dst, dst_port)?;
+    }
 
     let src_parent = base.get_parent(src);
     let src_parent_parent = src_parent.and_then(|src| base.get_parent(src));
     let dst_parent = base.get_parent(dst);
     let local_source = src_parent == dst_parent;
-    if let EdgeKind::Value(typ) = base.get_optype(src).port_kind(src_port).unwrap() {
-        if !local_source {
-            // Non-local value sources require a state edge to an ancestor of dst
-            if !typ.copyable() {
-                return Err(BuilderWiringError::NonCopyableIntergraph {
-                    src,
-                    src_offset: src_port.into(),
-                    dst,
-                    dst_offset: dst_port.into(),
-                    typ,
-                });
-            }
-
-            let src_parent = src_parent.expect("Node has no parent");
-            let Some(src_sibling) = iter::successors(dst_parent, |&p| base.get_parent(p))
-                .tuple_windows()
-                .find_map(|(ancestor, ancestor_parent)| {
-                    (ancestor_parent == src_parent ||
-                        // Dom edge - in CFGs
-                        Some(ancestor_parent) == src_parent_parent)
-                        .then_some(ancestor)
-                })
-            else {
-                return Err(BuilderWiringError::NoRelationIntergraph {
-                    src,
-                    src_offset: src_port.into(),
-                    dst,
-                    dst_offset: dst_port.into(),
-                });
-            };
-
-            if !OpTag::BasicBlock.is_superset(base.get_optype(src).tag())
-                && !OpTag::BasicBlock.is_superset(base.get_optype(src_sibling).tag())
-            {
-                // Add a state order constraint unless one of the nodes is a CFG BasicBlock
-                base.add_other_edge(src, src_sibling);
-            }
-        } else if !typ.copyable() & base.linked_ports(src, src_port).next().is_some() {
-            // Don't copy linear edges.
-            return Err(BuilderWiringError::NoCopyLinear {
+    let EdgeKind::Value(typ) = base.get_optype(src).port_kind(src_port).unwrap() else {
+        return Ok(None);
+    };
+    if !local_source {
+        // Non-local value sources require a state edge to an ancestor of dst
+        if !typ.copyable() {
+            return Err(BuilderWiringError::NonCopyableIntergraph {
+                src,
+                src_offset: src_port.into(),
+                dst,
+                dst_offset: dst_port.into(),
                 typ,
+            });
+        }
+
+        let src_parent = src_parent.expect("Node has no parent");
+        let Some(src_sibling) = iter::successors(dst_parent, |&p| base.get_parent(p))
+            .tuple_windows()
+            .find_map(|(ancestor, ancestor_parent)| {
+                (ancestor_parent == src_parent ||
+                    // Dom edge - in CFGs
+                    Some(ancestor_parent) == src_parent_parent)
+                    .then_some(ancestor)
+            })
+        else {
+            return Err(BuilderWiringError::NoRelationIntergraph {
                 src,
                 src_offset: src_port.into(),
+                dst,
+                dst_offset: dst_port.into(),
             });
+        };
+
+        if !OpTag::BasicBlock.is_superset(base.get_optype(src).tag())
+            && !OpTag::BasicBlock.is_superset(base.get_optype(src_sibling).tag())
+        {
+            // Add a state order constraint unless one of the nodes is a CFG BasicBlock
+            return Ok(Some((src, src_sibling)));
         }
+    } else if !typ.copyable()
+        && (base.linked_ports(src, src_port).next().is_some()
+            || !linear_sources.insert((src, src_port)))
+    {
+        // Don't copy linear edges.
+        return Err(BuilderWiringError::NoCopyLinear {
+            typ,
+            src,
+            src_offset: src_port.into(),
+        });
     }
+    Ok(None)
+}
 
-    data_builder
-        .hugr_mut()
-        .connect(src, src_port, dst, dst_port);
-    Ok(local_source
-        && matches!(
-            data_builder
-                .hugr_mut()
-                .get_optype(dst)
-                .port_kind(dst_port)
-                .unwrap(),
-            EdgeKind::Value(_)
-        ))
+/// Check that both ends of an edge exist and have matching kinds.
+///
+/// # Errors
+///
+/// Returns a [`BuilderWiringError`] if a port does not exist or the port
+/// kinds differ.
+fn check_wire_types(
+    base: &Hugr,
+    src: Node,
+    src_port: OutgoingPort,
+    dst: Node,
+    dst_port: IncomingPort,
+) -> Result<(), BuilderWiringError> {
+    let Some(src_kind) = base.get_optype(src).port_kind(src_port) else {
+        return Err(BuilderWiringError::InvalidPort {
+            node: src,
+            port: src_port.into(),
+        });
+    };
+    let Some(dst_kind) = base.get_optype(dst).port_kind(dst_port) else {
+        return Err(BuilderWiringError::InvalidPort {
+            node: dst,
+            port: dst_port.into(),
+        });
+    };
+    if src_kind != dst_kind {
+        return Err(BuilderWiringError::TypeMismatch {
+            src,
+            src_offset: src_port.into(),
+            src_kind,
+            dst,
+            dst_offset: dst_port.into(),
+            dst_kind,
+        });
+    }
+    Ok(())
 }
 
 /// Trait implemented by builders of Dataflow Hugrs
@@ -1,11 +1,13 @@
 use super::{
-    build_traits::SubContainer,
+    build_traits::{check_predicate, SubContainer},
     dataflow::{DFGBuilder, DFGWrapper},
     handle::BuildHandle,
     BasicBlockID, BuildError, CfgID, Container, Dataflow, HugrBuilder, Wire,
 };
 
-use crate::ops::{self, handle::NodeHandle, DataflowBlock, DataflowParent, ExitBlock, OpType};
+use crate::ops::{
+    self, handle::NodeHandle, BasicBlock, DataflowBlock, DataflowParent, ExitBlock, OpType,
+};
 use crate::{
     extension::{ExtensionRegistry, ExtensionSet},
     types::FunctionType,
@@ -206,7 +208,8 @@ impl<B: AsMut<Hugr> + AsRef<Hugr>> CFGBuilder<B> {
     ///
     /// # Errors
     ///
-    /// This function will return an error if there is an error adding the node.
+    /// This function will return an error if there is an error adding the node,
+    /// or if `sum_rows` is empty.
     pub fn block_builder(
         &mut self,
         inputs: TypeRow,
@@ -214,18 +217,31 @@ impl<B: AsMut<Hugr> + AsRef<Hugr>> CFGBuilder<B> {
         extension_delta: ExtensionSet,
         other_outputs: TypeRow,
     ) -> Result<BlockBuilder<&mut Hugr>, BuildError> {
+        let sum_rows = self.branch_rows(sum_rows)?;
         self.any_block_builder(inputs, sum_rows, other_outputs, extension_delta, false)
     }
 
+    /// Collect the variants of a block's branching Sum, rejecting an empty
+    /// Sum since a block with no successors could never be left.
+    fn branch_rows(
+        &self,
+        sum_rows: impl IntoIterator<Item = TypeRow>,
+    ) -> Result<Vec<TypeRow>, BuildError> {
+        let sum_rows: Vec<_> = sum_rows.into_iter().collect();
+        if sum_rows.is_empty() {
+            return Err(BuildError::EmptyPredicate(self.cfg_node));
+        }
+        Ok(sum_rows)
+    }
+
     fn any_block_builder(
         &mut self,
         inputs: TypeRow,
-        sum_rows: impl IntoIterator<Item = TypeRow>,
+        sum_rows: Vec<TypeRow>,
         other_outputs: TypeRow,
         extension_delta: ExtensionSet,
         entry: bool,
     ) -> Result<BlockBuilder<&mut Hugr>, BuildError> {
-        let sum_rows: Vec<_> = sum_rows.into_iter().collect();
         let op = OpType::DataflowBlock(DataflowBlock {
             inputs: inputs.clone(),
             other_outputs: other_outputs.clone(),
@@ -270,13 +286,15 @@ impl<B: AsMut<Hugr> + AsRef<Hugr>> CFGBuilder<B> {
     ///
     /// # Errors
     ///
-    /// This function will return an error if an entry block has already been built.
+    /// This function will return an error if an entry block has already been built,
+    /// or if `sum_rows` is empty.
     pub fn entry_builder(
         &mut self,
         sum_rows: impl IntoIterator<Item = TypeRow>,
         other_outputs: TypeRow,
         extension_delta: ExtensionSet,
     ) -> Result<BlockBuilder<&mut Hugr>, BuildError> {
+        let sum_rows = self.branch_rows(sum_rows)?;
         let inputs = self
             .inputs
             .take()
@@ -304,11 +322,25 @@ impl<B: AsMut<Hugr> + AsRef<Hugr>> CFGBuilder<B> {
         self.exit_node.into()
     }
 
+    /// Returns the type row expected on every branch into the exit block,
+    /// i.e. the output row of the CFG.
+    ///
+    /// The last blocks of the CFG must produce this row (after the branch
+    /// predicate) to be able to branch to the [exit block](CFGBuilder::exit_block).
+    pub fn exit_signature(&self) -> &TypeRow {
+        self.hugr()
+            .get_optype(self.exit_node)
+            .as_exit_block()
+            .expect("CFG exit node is not an ExitBlock.")
+            .dataflow_input()
+    }
+
     /// Set the `branch` index `successor` block of `predecessor`.
     ///
     /// # Errors
     ///
-    /// This function will return an error if there is an error connecting the blocks.
+    /// This function will return an error if there is an error connecting the
+    /// blocks, e.g. if `predecessor` has no `branch` successor.
     pub fn branch(
         &mut self,
         predecessor: &BasicBlockID,
@@ -317,7 +349,7 @@ impl<B: AsMut<Hugr> + AsRef<Hugr>> CFGBuilder<B> {
     ) -> Result<(), BuildError> {
         let from = predecessor.node();
         let to = successor.node();
-        self.hugr_mut().connect(from, branch, to, 0);
+        self.hugr_mut().connect_checked(from, branch, to, 0)?;
         Ok(())
     }
 }
@@ -328,11 +360,22 @@ pub type BlockBuilder<B> = DFGWrapper<B, BasicBlockID>;
 impl<B: AsMut<Hugr> + AsRef<Hugr>> BlockBuilder<B> {
     /// Set the outputs of the block, with `branch_wire` carrying  the value of the
     /// branch controlling Sum value.  `outputs` are the remaining outputs.
+    ///
+    /// Returns [`BuildError::InvalidPredicate`] if `branch_wire` is not a sum
+    /// with one variant per successor of the block.
     pub fn set_outputs(
         &mut self,
         branch_wire: Wire,
         outputs: impl IntoIterator<Item = Wire>,
     ) -> Result<(), BuildError> {
+        let n_branches = self
+            .hugr()
+            .get_optype(self.container_node())
+            .as_dataflow_block()
+            .expect("Block builder container is not a DataflowBlock.")
+            .sum_rows
+            .len();
+        check_predicate(self, branch_wire, n_branches)?;
         Dataflow::set_outputs(self, [branch_wire].into_iter().chain(outputs))
     }
     fn create(base: B, block_n: Node) -> Result<Self, BuildError> {
@@ -402,10 +445,12 @@ impl BlockBuilder<Hugr> {
 pub(crate) mod test {
     use crate::builder::{DataflowSubContainer, ModuleBuilder};
 
+    use crate::hugr::hugrmut::ConnectError;
     use crate::hugr::validate::InterGraphEdgeError;
     use crate::hugr::ValidationError;
     use crate::{builder::test::NAT, type_row};
     use cool_asserts::assert_matches;
+    use itertools::Itertools;
 
     use super::*;
     #[test]
@@ -472,6 +517,19 @@ pub(crate) mod test {
         cfg_builder.branch(&entry, 1, &exit)?;
         Ok(())
     }
+    #[test]
+    fn basic_cfg_constants() -> Result<(), BuildError> {
+        let mut cfg_builder = CFGBuilder::new(FunctionType::new(type_row![NAT], type_row![NAT]))?;
+        build_basic_cfg(&mut cfg_builder)?;
+        let h = cfg_builder.finish_prelude_hugr()?;
+
+        let (node, value, ty) = h.constants().exactly_one().ok().unwrap();
+        assert!(h.get_optype(node).is_const());
+        assert_eq!(value, &ops::Value::unary_unit_sum());
+        assert_eq!(ty, value.const_type());
+        Ok(())
+    }
+
     #[test]
     fn test_dom_edge() -> Result<(), BuildError> {
         let mut cfg_builder = CFGBuilder::new(FunctionType::new(type_row![NAT], type_row![NAT]))?;
@@ -495,8 +553,103 @@ pub(crate) mod test {
         let exit = cfg_builder.exit_block();
         cfg_builder.branch(&entry, 0, &middle)?;
         cfg_builder.branch(&middle, 0, &exit)?;
+        // Neither `middle` nor the exit block has a successor at index 1.
+        for block in [middle, exit] {
+            assert_matches!(
+                cfg_builder.branch(&block, 1, &middle),
+                Err(BuildError::ConnectError(ConnectError::NoEdgeKind { node, .. }))
+                    => assert_eq!(node, block.node())
+            );
+        }
+        assert_matches!(cfg_builder.finish_prelude_hugr(), Ok(_));
+
+        Ok(())
+    }
+
+    #[test]
+    fn empty_predicate() -> Result<(), BuildError> {
+        let mut cfg_builder = CFGBuilder::new(FunctionType::new(type_row![NAT], type_row![NAT]))?;
+        let cfg_node = cfg_builder.container_node();
+        assert_matches!(
+            cfg_builder.entry_builder([], type_row![NAT], ExtensionSet::new()),
+            Err(BuildError::EmptyPredicate(n)) => assert_eq!(n, cfg_node)
+        );
+        assert_matches!(
+            cfg_builder.simple_block_builder(FunctionType::new_endo(type_row![NAT]), 0),
+            Err(BuildError::EmptyPredicate(_))
+        );
+
+        // The failed attempts leave no blocks behind, and the entry can still be built.
+        build_basic_cfg(&mut cfg_builder)?;
         assert_matches!(cfg_builder.finish_prelude_hugr(), Ok(_));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_predicate() -> Result<(), BuildError> {
+        let mut cfg_builder = CFGBuilder::new(FunctionType::new(type_row![NAT], type_row![NAT]))?;
+        let mut entry_b = cfg_builder.entry_builder(
+            vec![type_row![], type_row![]],
+            type_row![NAT],
+            ExtensionSet::new(),
+        )?;
+        let [inw] = entry_b.input_wires_arr();
+        // Neither a non-sum wire nor a sum with the wrong number of variants
+        // can select the branch.
+        assert_matches!(
+            entry_b.set_outputs(inw, [inw]),
+            Err(BuildError::InvalidPredicate { wire, n_variants: 2, .. }) => assert_eq!(wire, inw)
+        );
+        let unary = entry_b.add_load_value(ops::Value::unary_unit_sum());
+        assert_matches!(
+            entry_b.set_outputs(unary, [inw]),
+            Err(BuildError::InvalidPredicate { .. })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn exit_signature() -> Result<(), BuildError> {
+        use crate::hugr::validate::ValidationError;
+        use crate::ops::validate::EdgeValidationError;
+
+        let mut cfg_builder = CFGBuilder::new(FunctionType::new(type_row![NAT], type_row![NAT]))?;
+        assert_eq!(cfg_builder.exit_signature(), &type_row![NAT]);
 
+        // The entry drops the value, so its outputs do not match the exit's inputs.
+        let mut entry_b =
+            cfg_builder.entry_builder(vec![type_row![]], type_row![], ExtensionSet::new())?;
+        let unary = entry_b.add_load_value(ops::Value::unary_unit_sum());
+        let entry = entry_b.finish_with_outputs(unary, [])?;
+        let exit = cfg_builder.exit_block();
+        cfg_builder.branch(&entry, 0, &exit)?;
+        assert_matches!(
+            cfg_builder.finish_prelude_hugr(),
+            Err(ValidationError::InvalidEdges {
+                source: EdgeValidationError::CFGEdgeSignatureMismatch { .. },
+                ..
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn block_predicate_variants() -> Result<(), BuildError> {
+        let mut cfg_builder = CFGBuilder::new(FunctionType::new(type_row![NAT], type_row![NAT]))?;
+        let sum_variants = vec![type_row![NAT], type_row![]];
+        let mut entry_b =
+            cfg_builder.entry_builder(sum_variants.clone(), type_row![], ExtensionSet::new())?;
+        let [inw] = entry_b.input_wires_arr();
+        let sum = entry_b.make_sum(0, sum_variants.clone(), [inw])?;
+        let entry = entry_b.finish_with_outputs(sum, [])?;
+        let exit = cfg_builder.exit_block();
+
+        let h = cfg_builder.hugr();
+        assert_eq!(
+            h.block_predicate_variants(entry.node()),
+            Some(sum_variants.as_slice())
+        );
+        assert_eq!(h.block_predicate_variants(exit.node()), None);
         Ok(())
     }
 
@@ -1,6 +1,5 @@
 use crate::extension::ExtensionRegistry;
 use crate::hugr::views::HugrView;
-use crate::ops::dataflow::DataflowOpTrait;
 use crate::types::{FunctionType, TypeRow};
 
 use crate::ops;
@@ -44,6 +43,13 @@ pub enum ConditionalBuildError {
         conditional: Node,
         cases: HashSet<usize>,
     },
+    /// The cases require extensions not in the Conditional's extension delta.
+    #[error("Conditional node {conditional:?} declares extension delta {declared} but its cases require {cases}.")]
+    ExtensionDeltaMismatch {
+        conditional: Node,
+        declared: ExtensionSet,
+        cases: ExtensionSet,
+    },
 }
 
 /// Builder for a [`ops::Conditional`] node's children.
@@ -89,12 +95,38 @@ impl<H: AsMut<Hugr> + AsRef<Hugr>> SubContainer for ConditionalBuilder<H> {
             }
             .into());
         }
+        let declared = self
+            .hugr()
+            .get_optype(self.conditional_node)
+            .as_conditional()
+            .expect("Parent node does not have Conditional optype.")
+            .extension_delta
+            .clone();
+        let cases = ExtensionSet::union_over(self.case_nodes.iter().flatten().map(|&case| {
+            self.hugr()
+                .get_optype(case)
+                .as_case()
+                .unwrap()
+                .signature
+                .extension_reqs
+                .clone()
+        }));
+        if !cases.is_subset(&declared) {
+            return Err(ConditionalBuildError::ExtensionDeltaMismatch {
+                conditional: self.conditional_node,
+                declared,
+                cases,
+            }
+            .into());
+        }
         Ok((self.conditional_node, self.n_out_wires).into())
     }
 }
 impl<B: AsMut<Hugr> + AsRef<Hugr>> ConditionalBuilder<B> {
     /// Return a builder the Case node with index `case`.
     ///
+    /// The case has the same extension delta as the Conditional.
+    ///
     /// # Panics
     ///
     /// Panics if the parent node is not of type [`ops::Conditional`].
@@ -104,6 +136,35 @@ impl<B: AsMut<Hugr> + AsRef<Hugr>> ConditionalBuilder<B> {
     /// This function will return an error if the case has already been built,
     /// `case` is not a valid index or if there is an error adding nodes.
     pub fn case_builder(&mut self, case: usize) -> Result<CaseBuilder<&mut Hugr>, BuildError> {
+        let extension_delta = self
+            .hugr()
+            .get_optype(self.conditional_node)
+            .as_conditional()
+            .expect("Parent node does not have Conditional optype.")
+            .extension_delta
+            .clone();
+        self.case_builder_with_extension_delta(case, extension_delta)
+    }
+
+    /// Return a builder the Case node with index `case`, which only requires
+    /// the extensions in `extension_delta`.
+    ///
+    /// When the Conditional is finished, its own extension delta must include
+    /// the union of its cases' extension deltas.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parent node is not of type [`ops::Conditional`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the case has already been built,
+    /// `case` is not a valid index or if there is an error adding nodes.
+    pub fn case_builder_with_extension_delta(
+        &mut self,
+        case: usize,
+        extension_delta: ExtensionSet,
+    ) -> Result<CaseBuilder<&mut Hugr>, BuildError> {
         let conditional = self.conditional_node;
         let control_op = self.hugr().get_optype(self.conditional_node);
 
@@ -111,7 +172,6 @@ impl<B: AsMut<Hugr> + AsRef<Hugr>> ConditionalBuilder<B> {
             .clone()
             .try_into()
             .expect("Parent node does not have Conditional optype.");
-        let extension_delta = cond.signature().extension_reqs;
         let inputs = cond
             .case_input_row(case)
             .ok_or(ConditionalBuildError::NotCase { conditional, case })?;
@@ -272,6 +332,98 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn case_extension_deltas() -> Result<(), BuildError> {
+        use crate::builder::DataflowHugr;
+        use crate::extension::prelude::{BOOL_T, PRELUDE};
+        use crate::extension::ExtensionRegistry;
+        use crate::ops::handle::NodeHandle;
+        use crate::std_extensions::arithmetic::{float_ops, float_types::FLOAT64_TYPE};
+        use crate::std_extensions::{arithmetic::float_types, logic};
+
+        let delta = ExtensionSet::from_iter([logic::EXTENSION_ID, float_ops::EXTENSION_ID]);
+        let mut dfg = DFGBuilder::new(
+            FunctionType::new(type_row![BOOL_T, BOOL_T, FLOAT64_TYPE], type_row![BOOL_T])
+                .with_extension_delta(delta.clone()),
+        )?;
+        let [pred, b, f] = dfg.input_wires_arr();
+        let mut conditional_b = dfg.conditional_builder(
+            ([type_row![], type_row![]], pred),
+            [(BOOL_T, b), (FLOAT64_TYPE, f)],
+            type_row![BOOL_T],
+            delta.clone(),
+        )?;
+        let mut not_case = conditional_b
+            .case_builder_with_extension_delta(0, ExtensionSet::singleton(&logic::EXTENSION_ID))?;
+        let [b, _] = not_case.input_wires_arr();
+        let not = not_case.add_dataflow_op(logic::NotOp, [b])?;
+        not_case.finish_with_outputs(not.outputs())?;
+        let mut eq_case = conditional_b.case_builder_with_extension_delta(
+            1,
+            ExtensionSet::singleton(&float_ops::EXTENSION_ID),
+        )?;
+        let [_, f] = eq_case.input_wires_arr();
+        let eq = eq_case.add_dataflow_op(float_ops::FloatOps::feq, [f, f])?;
+        eq_case.finish_with_outputs(eq.outputs())?;
+        let conditional = conditional_b.finish_sub_container()?;
+
+        let reg = ExtensionRegistry::try_new([
+            PRELUDE.to_owned(),
+            logic::EXTENSION.to_owned(),
+            float_types::EXTENSION.to_owned(),
+            float_ops::EXTENSION.to_owned(),
+        ])
+        .unwrap();
+        let h = dfg.finish_hugr_with_outputs(conditional.outputs(), &reg)?;
+        let cond_op = h.get_optype(conditional.node()).as_conditional().unwrap();
+        assert_eq!(cond_op.extension_delta, delta);
+
+        // Declaring more extensions than the cases use is allowed...
+        let mut conditional_b =
+            ConditionalBuilder::new([type_row![]], type_row![], type_row![], delta)?;
+        let case = conditional_b
+            .case_builder_with_extension_delta(0, ExtensionSet::singleton(&logic::EXTENSION_ID))?;
+        case.finish_with_outputs([])?;
+        conditional_b.finish_sub_container()?;
+
+        // ...but declaring fewer is an error.
+        let mut conditional_b = ConditionalBuilder::new(
+            [type_row![]],
+            type_row![],
+            type_row![],
+            ExtensionSet::singleton(&float_ops::EXTENSION_ID),
+        )?;
+        let case = conditional_b
+            .case_builder_with_extension_delta(0, ExtensionSet::singleton(&logic::EXTENSION_ID))?;
+        case.finish_with_outputs([])?;
+        assert_matches!(
+            conditional_b.finish_sub_container().map(|_| ()),
+            Err(BuildError::ConditionalError(
+                ConditionalBuildError::ExtensionDeltaMismatch { .. }
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn non_sum_predicate() -> Result<(), BuildError> {
+        let mut dfg = DFGBuilder::new(FunctionType::new_endo(type_row![NAT]))?;
+        let [int] = dfg.input_wires_arr();
+        assert_matches!(
+            dfg.conditional_builder(
+                ([type_row![], type_row![]], int),
+                [],
+                type_row![],
+                ExtensionSet::new(),
+            ).map(|_| ()),
+            Err(BuildError::InvalidPredicate { wire, typ, n_variants: 2 }) => {
+                assert_eq!(wire, int);
+                assert_eq!(typ, NAT);
+            }
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_not_all_cases() -> Result<(), BuildError> {
         let mut builder = ConditionalBuilder::new(
@@ -1,18 +1,21 @@
-use super::build_traits::{HugrBuilder, SubContainer};
+use super::build_traits::{check_output_wires, HugrBuilder, SubContainer};
 use super::handle::BuildHandle;
 use super::{BuildError, Container, Dataflow, DfgID, FuncID};
 
 use std::marker::PhantomData;
 
 use crate::hugr::{HugrView, NodeType, ValidationError};
-use crate::ops;
+use crate::ops::{self, OpTrait, OpType};
 
-use crate::types::{FunctionType, PolyFuncType};
+use crate::types::{FunctionType, PolyFuncType, TypeRow};
 
 use crate::extension::{ExtensionRegistry, ExtensionSet};
-use crate::Node;
+use crate::hugr::hugrmut::sealed::HugrMutInternals;
 use crate::{hugr::HugrMut, Hugr};
 
+use crate::{Node, Wire};
+use itertools::Itertools;
+
 /// Builder for a [`ops::DFG`] node.
 #[derive(Debug, Clone, PartialEq)]
 pub struct DFGBuilder<T> {
@@ -20,6 +23,9 @@ pub struct DFGBuilder<T> {
     pub(crate) dfg_node: Node,
     pub(crate) num_in_wires: usize,
     pub(crate) num_out_wires: usize,
+    /// Whether the output row is computed from the wires passed to
+    /// [`Dataflow::set_outputs`], see [`DFGBuilder::new_inferred`].
+    infer_outputs: bool,
 }
 
 impl<T: AsMut<Hugr> + AsRef<Hugr>> DFGBuilder<T> {
@@ -64,8 +70,35 @@ impl<T: AsMut<Hugr> + AsRef<Hugr>> DFGBuilder<T> {
             dfg_node: parent,
             num_in_wires,
             num_out_wires,
+            infer_outputs: false,
         })
     }
+
+    /// Replace the output row of the graph, updating the output node and the
+    /// container node.
+    fn set_output_row(&mut self, outputs: TypeRow) {
+        let [_, out] = self.io();
+        let hugr = self.base.as_mut();
+        let mut signature = hugr
+            .get_optype(self.dfg_node)
+            .dataflow_signature()
+            .expect("DFG builder container has a dataflow signature.");
+        signature.output = outputs.clone();
+        self.num_out_wires = outputs.len();
+        let new_ops: [(Node, OpType); 2] = [
+            (out, ops::Output { types: outputs }.into()),
+            (self.dfg_node, ops::DFG { signature }.into()),
+        ];
+        for (node, op) in new_ops {
+            let input_extensions = hugr.get_nodetype(node).input_extensions().cloned();
+            // The root of a Hugr has no ports.
+            if node != hugr.root() {
+                hugr.set_num_ports(node, op.input_count(), op.output_count());
+            }
+            hugr.replace_op(node, NodeType::new(op, input_extensions))
+                .expect("Replacing the root of a Hugr cannot fail.");
+        }
+    }
 }
 
 impl DFGBuilder<Hugr> {
@@ -83,6 +116,21 @@ impl DFGBuilder<Hugr> {
         let root = base.root();
         DFGBuilder::create_with_io(base, root, signature, None)
     }
+
+    /// Begin building a new DFG rooted HUGR with the given inputs, whose
+    /// outputs are given by the types of the wires passed to
+    /// [`Dataflow::set_outputs`] (or the `finish` methods calling it).
+    ///
+    /// Input extensions default to being an open variable.
+    ///
+    /// # Errors
+    ///
+    /// Error in adding DFG child nodes.
+    pub fn new_inferred(inputs: impl Into<TypeRow>) -> Result<DFGBuilder<Hugr>, BuildError> {
+        let mut builder = Self::new(FunctionType::new(inputs, TypeRow::new()))?;
+        builder.infer_outputs = true;
+        Ok(builder)
+    }
 }
 
 impl HugrBuilder for DFGBuilder<Hugr> {
@@ -125,6 +173,18 @@ impl<T: AsMut<Hugr> + AsRef<Hugr>> Dataflow for DFGBuilder<T> {
     fn num_inputs(&self) -> usize {
         self.num_in_wires
     }
+
+    fn prepare_outputs(&mut self, output_wires: &[Wire]) -> Result<(), BuildError> {
+        if self.infer_outputs {
+            let outputs: Vec<_> = output_wires
+                .iter()
+                .map(|&w| self.get_wire_type(w))
+                .try_collect()?;
+            check_output_wires(self, output_wires)?;
+            self.set_output_row(outputs.into());
+        }
+        Ok(())
+    }
 }
 
 /// Wrapper around [`DFGBuilder`] used to build other dataflow regions.
@@ -183,6 +243,11 @@ impl<B: AsMut<Hugr> + AsRef<Hugr>, T> Dataflow for DFGWrapper<B, T> {
     fn num_inputs(&self) -> usize {
         self.0.num_inputs()
     }
+
+    #[inline]
+    fn prepare_outputs(&mut self, output_wires: &[Wire]) -> Result<(), BuildError> {
+        self.0.prepare_outputs(output_wires)
+    }
 }
 
 impl<B: AsMut<Hugr> + AsRef<Hugr>, T: From<BuildHandle<DfgID>>> SubContainer for DFGWrapper<B, T> {
@@ -200,6 +265,72 @@ impl<T> HugrBuilder for DFGWrapper<Hugr, T> {
     }
 }
 
+/// Wrapper around a dataflow builder that checks each connection as it is
+/// made, see [`Dataflow::strict`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrictBuilder<B>(B);
+
+impl<B> StrictBuilder<B> {
+    /// Wrap a builder so that connections are checked eagerly.
+    pub fn new(builder: B) -> Self {
+        Self(builder)
+    }
+
+    /// Returns the wrapped builder.
+    pub fn into_inner(self) -> B {
+        self.0
+    }
+}
+
+impl<B: Container> Container for StrictBuilder<B> {
+    #[inline]
+    fn container_node(&self) -> Node {
+        self.0.container_node()
+    }
+
+    #[inline]
+    fn hugr_mut(&mut self) -> &mut Hugr {
+        self.0.hugr_mut()
+    }
+
+    #[inline]
+    fn hugr(&self) -> &Hugr {
+        self.0.hugr()
+    }
+}
+
+impl<B: Dataflow> Dataflow for StrictBuilder<B> {
+    #[inline]
+    fn num_inputs(&self) -> usize {
+        self.0.num_inputs()
+    }
+
+    #[inline]
+    fn is_strict(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn prepare_outputs(&mut self, output_wires: &[Wire]) -> Result<(), BuildError> {
+        self.0.prepare_outputs(output_wires)
+    }
+}
+
+impl<B: SubContainer> SubContainer for StrictBuilder<B> {
+    type ContainerHandle = B::ContainerHandle;
+
+    #[inline]
+    fn finish_sub_container(self) -> Result<Self::ContainerHandle, BuildError> {
+        self.0.finish_sub_container()
+    }
+}
+
+impl<B: HugrBuilder> HugrBuilder for StrictBuilder<B> {
+    fn finish_hugr(self, extension_registry: &ExtensionRegistry) -> Result<Hugr, ValidationError> {
+        self.0.finish_hugr(extension_registry)
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod test {
     use cool_asserts::assert_matches;
@@ -215,7 +346,7 @@ pub(crate) mod test {
 
     use crate::std_extensions::logic::test::and_op;
     use crate::types::Type;
-    use crate::utils::test_quantum_extension::h_gate;
+    use crate::utils::test_quantum_extension::{cx_gate, h_gate};
     use crate::{
         builder::test::{n_identity, BIT, NAT, QB},
         type_row, Wire,
@@ -550,4 +681,161 @@ pub(crate) mod test {
         );
         Ok(())
     }
+
+    #[test]
+    fn add_op1() -> Result<(), BuildError> {
+        let mut builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB, QB]))?;
+        let [q0, q1] = builder.input_wires_arr();
+        assert_matches!(
+            builder.add_op1(cx_gate(), [q0, q1]),
+            Err(BuildError::ExpectedSingleOutput { n_outputs: 2, .. })
+        );
+        let q0 = builder.add_op1(h_gate(), [q0])?;
+        builder.finish_prelude_hugr_with_outputs([q0, q1])?;
+        Ok(())
+    }
+
+    #[test]
+    fn wire_type() -> Result<(), BuildError> {
+        let mut builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB, QB]))?;
+        let cx = builder.add_dataflow_op(cx_gate(), builder.input_wires())?;
+        let [q0, q1] = cx.outputs_arr();
+        assert_eq!(builder.wire_type(q1), Some(QB));
+        assert_eq!(builder.wire_type(Wire::new(cx.node(), 2)), None);
+        builder.finish_prelude_hugr_with_outputs([q0, q1])?;
+        Ok(())
+    }
+
+    #[test]
+    fn inferred_outputs() -> Result<(), BuildError> {
+        let mut builder = DFGBuilder::new_inferred(type_row![NAT, BOOL_T])?;
+        let [n, b] = builder.input_wires_arr();
+        let noop = builder.add_dataflow_op(Noop { ty: NAT }, [n])?;
+        let h = builder.finish_prelude_hugr_with_outputs([b, noop.out_wire(0)])?;
+
+        let expected = FunctionType::new(type_row![NAT, BOOL_T], type_row![BOOL_T, NAT]);
+        assert_eq!(h.get_function_type(), Some(expected.clone().into()));
+        let [_, output] = h.get_io(h.root()).unwrap();
+        assert_eq!(
+            h.get_optype(output).dataflow_signature(),
+            Some(FunctionType::new(expected.output, type_row![]))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn strict_inferred_outputs() -> Result<(), BuildError> {
+        let builder = DFGBuilder::new_inferred(type_row![BOOL_T])?.strict();
+        let ins = builder.input_wires();
+        let h = builder.finish_prelude_hugr_with_outputs(ins)?;
+        assert_eq!(
+            h.get_function_type(),
+            Some(FunctionType::new_endo(type_row![BOOL_T]).into())
+        );
+
+        // A rejected output wire leaves the output row unchanged.
+        let mut builder = DFGBuilder::new_inferred(type_row![BOOL_T])?.strict();
+        let [b] = builder.input_wires_arr();
+        let nested = builder.dfg_builder(FunctionType::new_endo(type_row![BOOL_T]), None, [b])?;
+        let [inner] = nested.input_wires_arr();
+        nested.finish_with_outputs([inner])?;
+        assert_matches!(
+            builder.set_outputs([b, inner]),
+            Err(BuildError::OutputWiring {
+                error: BuilderWiringError::NoRelationIntergraph { .. },
+                ..
+            })
+        );
+        let root = builder.container_node();
+        assert_eq!(
+            builder.hugr().get_optype(root).dataflow_signature(),
+            Some(FunctionType::new(type_row![BOOL_T], type_row![]))
+        );
+        builder.finish_prelude_hugr_with_outputs([b])?;
+        Ok(())
+    }
+
+    #[test]
+    fn strict_type_mismatch() -> Result<(), BuildError> {
+        use crate::std_extensions::logic;
+        let reg = ExtensionRegistry::with_prelude([logic::EXTENSION.to_owned()]).unwrap();
+        let sig = FunctionType::new(type_row![NAT, BIT], type_row![BIT]);
+
+        // A lazy builder accepts the connection and fails on validation.
+        let mut lazy = DFGBuilder::new(sig.clone())?;
+        let [n, b] = lazy.input_wires_arr();
+        let and = lazy.add_dataflow_op(and_op(), [n, b])?;
+        assert_matches!(
+            lazy.finish_hugr_with_outputs(and.outputs(), &reg),
+            Err(BuildError::InvalidHUGR(_))
+        );
+
+        // A strict builder reports the mismatch when connecting.
+        let mut strict = DFGBuilder::new(sig)?.strict();
+        let [n, b] = strict.input_wires_arr();
+        assert_matches!(
+            strict.add_dataflow_op(and_op(), [n, b]),
+            Err(BuildError::OperationWiring {
+                error: BuilderWiringError::TypeMismatch { .. },
+                ..
+            })
+        );
+        assert_matches!(
+            strict.add_dataflow_op(and_op(), [b]),
+            Err(BuildError::OperationWiring {
+                error: BuilderWiringError::UnconnectedInput { .. },
+                ..
+            })
+        );
+        let and = strict.add_dataflow_op(and_op(), [b, b])?;
+        assert_matches!(
+            strict.set_outputs([n]),
+            Err(BuildError::OutputWiring {
+                error: BuilderWiringError::TypeMismatch { .. },
+                ..
+            })
+        );
+        strict.finish_hugr_with_outputs(and.outputs(), &reg)?;
+        Ok(())
+    }
+
+    #[test]
+    fn strict_failure_leaves_graph_unchanged() -> Result<(), BuildError> {
+        let mut outer = DFGBuilder::new(FunctionType::new(type_row![BIT, NAT], type_row![]))?;
+        let [b, n] = outer.input_wires_arr();
+        let mut nested = outer
+            .dfg_builder(
+                FunctionType::new(type_row![], type_row![BIT, BIT]),
+                None,
+                [],
+            )?
+            .strict();
+        let counts = |builder: &StrictBuilder<DFGBuilder<&mut Hugr>>| {
+            (builder.hugr().node_count(), builder.hugr().edge_count())
+        };
+        let before = counts(&nested);
+
+        // The first wire is a valid non-local edge, which needs an order edge
+        // from the outer Input node; the second one has the wrong type.
+        assert_matches!(
+            nested.add_dataflow_op(and_op(), [b, n]),
+            Err(BuildError::OperationWiring {
+                error: BuilderWiringError::TypeMismatch { .. },
+                ..
+            })
+        );
+        assert_eq!(counts(&nested), before);
+        assert_matches!(
+            nested.set_outputs([b, n]),
+            Err(BuildError::OutputWiring {
+                error: BuilderWiringError::TypeMismatch { .. },
+                ..
+            })
+        );
+        assert_eq!(counts(&nested), before);
+
+        nested.finish_with_outputs([b, b])?;
+        outer.finish_prelude_hugr_with_outputs([])?;
+        Ok(())
+    }
 }
@@ -0,0 +1,242 @@
+use std::collections::{HashMap, VecDeque};
+
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::extension::ExtensionRegistry;
+use crate::hugr::{HugrMut, HugrView, NodeType};
+use crate::ops::OpType;
+use crate::{Hugr, Node, Port, PortIndex};
+
+use super::BuildError;
+
+/// Error in the description passed to [`Hugr::from_description`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum DescriptionError {
+    /// A node is described more than once.
+    #[error("Node {0:?} is described more than once.")]
+    DuplicateNode(Node),
+    /// An edge or hierarchy entry refers to a node with no operation.
+    #[error("Node {0:?} has no operation in the description.")]
+    UnknownNode(Node),
+    /// A node is given more than one parent.
+    #[error("Node {0:?} has more than one parent.")]
+    MultipleParents(Node),
+    /// The description does not have exactly one node without a parent.
+    #[error("Expected exactly one root node without a parent, found {0:?}.")]
+    InvalidRoot(Vec<Node>),
+    /// A node is not a descendant of the root.
+    #[error("Node {0:?} is not a descendant of the root.")]
+    Unreachable(Node),
+    /// An edge endpoint has the wrong direction, or is not a port of its node.
+    #[error("Port {port:?} of node {node:?} is not a valid edge endpoint.")]
+    #[allow(missing_docs)]
+    InvalidPort { node: Node, port: Port },
+}
+
+impl Hugr {
+    /// Build and validate a HUGR from a declarative description.
+    ///
+    /// The nodes in the description are only identifiers: each is mapped to a
+    /// new node of the HUGR. `ops` gives the operation of every node,
+    /// `hierarchy` lists `(parent, child)` pairs with the children of each
+    /// parent in order, and `edges` lists `(source, source port, target,
+    /// target port)` links. The root is the only node without a parent.
+    ///
+    /// Returns the HUGR together with the map from the described nodes to
+    /// the nodes of the HUGR.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DescriptionError`] if the description is inconsistent, or
+    /// [`BuildError::InvalidHUGR`] if the resulting HUGR does not validate.
+    pub fn from_description(
+        ops: Vec<(Node, OpType)>,
+        edges: Vec<(Node, Port, Node, Port)>,
+        hierarchy: Vec<(Node, Node)>,
+        extension_registry: &ExtensionRegistry,
+    ) -> Result<(Hugr, HashMap<Node, Node>), BuildError> {
+        let mut ops_map = HashMap::with_capacity(ops.len());
+        for (node, op) in ops.iter() {
+            if ops_map.insert(*node, op).is_some() {
+                return Err(DescriptionError::DuplicateNode(*node).into());
+            }
+        }
+
+        let mut children: HashMap<Node, Vec<Node>> = HashMap::new();
+        let mut has_parent = HashMap::new();
+        for &(parent, child) in &hierarchy {
+            for n in [parent, child] {
+                if !ops_map.contains_key(&n) {
+                    return Err(DescriptionError::UnknownNode(n).into());
+                }
+            }
+            if has_parent.insert(child, parent).is_some() {
+                return Err(DescriptionError::MultipleParents(child).into());
+            }
+            children.entry(parent).or_default().push(child);
+        }
+        let roots = ops
+            .iter()
+            .map(|(n, _)| *n)
+            .filter(|n| !has_parent.contains_key(n))
+            .collect_vec();
+        let [root] = roots[..] else {
+            return Err(DescriptionError::InvalidRoot(roots).into());
+        };
+
+        let mut hugr = Hugr::new(NodeType::new_open(ops_map[&root].clone()));
+        let mut node_map = HashMap::from([(root, hugr.root())]);
+        let mut queue = VecDeque::from([root]);
+        while let Some(parent) = queue.pop_front() {
+            for &child in children.get(&parent).into_iter().flatten() {
+                let new_child =
+                    hugr.add_node_with_parent(node_map[&parent], ops_map[&child].clone());
+                node_map.insert(child, new_child);
+                queue.push_back(child);
+            }
+        }
+        if let Some(&(node, _)) = ops.iter().find(|(n, _)| !node_map.contains_key(n)) {
+            return Err(DescriptionError::Unreachable(node).into());
+        }
+
+        for (src, src_port, dst, dst_port) in edges {
+            let (&new_src, &new_dst) = (
+                node_map
+                    .get(&src)
+                    .ok_or(DescriptionError::UnknownNode(src))?,
+                node_map
+                    .get(&dst)
+                    .ok_or(DescriptionError::UnknownNode(dst))?,
+            );
+            let src_port = src_port
+                .as_outgoing()
+                .ok()
+                .filter(|p| p.index() < hugr.num_outputs(new_src))
+                .ok_or(DescriptionError::InvalidPort {
+                    node: src,
+                    port: src_port,
+                })?;
+            let dst_port = dst_port
+                .as_incoming()
+                .ok()
+                .filter(|p| p.index() < hugr.num_inputs(new_dst))
+                .ok_or(DescriptionError::InvalidPort {
+                    node: dst,
+                    port: dst_port,
+                })?;
+            hugr.connect(new_src, src_port, new_dst, dst_port);
+        }
+
+        hugr.update_validate(extension_registry)?;
+        Ok((hugr, node_map))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cool_asserts::assert_matches;
+
+    use super::*;
+    use crate::extension::prelude::BOOL_T;
+    use crate::ops::{self, dataflow::IOTrait};
+    use crate::std_extensions::logic::{NotOp, LOGIC_REG};
+    use crate::type_row;
+    use crate::types::FunctionType;
+    use crate::{IncomingPort, OutgoingPort};
+
+    fn node(i: usize) -> Node {
+        portgraph::NodeIndex::new(i).into()
+    }
+
+    fn out_port(i: usize) -> Port {
+        OutgoingPort::from(i).into()
+    }
+
+    fn in_port(i: usize) -> Port {
+        IncomingPort::from(i).into()
+    }
+
+    /// A DFG negating a single bit.
+    #[allow(clippy::type_complexity)]
+    fn not_description() -> (
+        Vec<(Node, OpType)>,
+        Vec<(Node, Port, Node, Port)>,
+        Vec<(Node, Node)>,
+    ) {
+        let [dfg, input, output, not] = [10, 11, 12, 13].map(node);
+        let ops = vec![
+            (
+                dfg,
+                ops::DFG {
+                    signature: FunctionType::new_endo(type_row![BOOL_T]),
+                }
+                .into(),
+            ),
+            (input, ops::Input::new(type_row![BOOL_T]).into()),
+            (output, ops::Output::new(type_row![BOOL_T]).into()),
+            (not, NotOp.into()),
+        ];
+        let edges = vec![
+            (input, out_port(0), not, in_port(0)),
+            (not, out_port(0), output, in_port(0)),
+        ];
+        let hierarchy = vec![(dfg, input), (dfg, output), (dfg, not)];
+        (ops, edges, hierarchy)
+    }
+
+    #[test]
+    fn from_description() -> Result<(), BuildError> {
+        let (ops, edges, hierarchy) = not_description();
+        let (h, node_map) = Hugr::from_description(ops, edges, hierarchy, &LOGIC_REG)?;
+        assert_eq!(h.node_count(), 4);
+        assert_eq!(node_map[&node(10)], h.root());
+        let [input, output] = h.get_io(h.root()).unwrap();
+        assert_eq!(node_map[&node(11)], input);
+        assert_eq!(node_map[&node(12)], output);
+        assert_eq!(h.get_optype(node_map[&node(13)]), &NotOp.into());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_description() {
+        let (ops, edges, mut hierarchy) = not_description();
+        hierarchy.pop();
+        assert_matches!(
+            Hugr::from_description(ops, edges, hierarchy, &LOGIC_REG),
+            Err(BuildError::DescriptionError(DescriptionError::InvalidRoot(roots))) => {
+                assert_eq!(roots, [node(10), node(13)])
+            }
+        );
+
+        let (ops, mut edges, hierarchy) = not_description();
+        edges[0].1 = in_port(0);
+        assert_matches!(
+            Hugr::from_description(ops, edges, hierarchy, &LOGIC_REG),
+            Err(BuildError::DescriptionError(
+                DescriptionError::InvalidPort { .. }
+            ))
+        );
+
+        // The Not operation has a value output and an order output.
+        let (ops, mut edges, hierarchy) = not_description();
+        edges[1].1 = out_port(2);
+        assert_matches!(
+            Hugr::from_description(ops, edges, hierarchy, &LOGIC_REG),
+            Err(BuildError::DescriptionError(
+                DescriptionError::InvalidPort { node: n, port }
+            )) => {
+                assert_eq!((n, port), (node(13), out_port(2)))
+            }
+        );
+
+        // A well-formed description of an invalid HUGR fails validation.
+        let (ops, mut edges, hierarchy) = not_description();
+        edges.pop();
+        assert_matches!(
+            Hugr::from_description(ops, edges, hierarchy, &LOGIC_REG),
+            Err(BuildError::InvalidHUGR(_))
+        );
+    }
+}
@@ -4,7 +4,7 @@ use crate::hugr::{views::HugrView, NodeType};
 use crate::types::{FunctionType, TypeRow};
 use crate::{Hugr, Node};
 
-use super::build_traits::SubContainer;
+use super::build_traits::{check_predicate, SubContainer};
 use super::handle::BuildHandle;
 use super::{
     dataflow::{DFGBuilder, DFGWrapper},
@@ -27,11 +27,15 @@ impl<B: AsMut<Hugr> + AsRef<Hugr>> TailLoopBuilder<B> {
     }
     /// Set the outputs of the [`ops::TailLoop`], with `out_variant` as the value of the
     /// termination Sum, and `rest` being the remaining outputs
+    ///
+    /// Returns [`BuildError::InvalidPredicate`] if `out_variant` is not a sum
+    /// with two variants.
     pub fn set_outputs(
         &mut self,
         out_variant: Wire,
         rest: impl IntoIterator<Item = Wire>,
     ) -> Result<(), BuildError> {
+        check_predicate(self, out_variant, 2)?;
         Dataflow::set_outputs(self, [out_variant].into_iter().chain(rest))
     }
 
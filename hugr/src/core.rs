@@ -10,14 +10,25 @@ use itertools::Either::{Left, Right};
 use crate::hugr::HugrError;
 
 /// A handle to a node in the HUGR.
-#[derive(
-    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, From, serde::Serialize, serde::Deserialize,
-)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, From, serde::Serialize)]
 #[serde(transparent)]
 pub struct Node {
     index: portgraph::NodeIndex,
 }
 
+impl<'de> serde::Deserialize<'de> for Node {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // Report out-of-range indices as errors rather than panicking.
+        let index = usize::deserialize(deserializer)?;
+        portgraph::NodeIndex::try_from(index)
+            .map(Node::from)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 /// A handle to a port for a node in the HUGR.
 #[derive(
     Clone,
@@ -129,6 +140,26 @@ impl Port {
         self.offset.direction()
     }
 
+    /// Returns whether this is an incoming port.
+    #[inline]
+    pub fn is_incoming(self) -> bool {
+        self.direction() == Direction::Incoming
+    }
+
+    /// Returns whether this is an outgoing port.
+    #[inline]
+    pub fn is_outgoing(self) -> bool {
+        self.direction() == Direction::Outgoing
+    }
+
+    /// Returns the direction of the ports this port can be linked to.
+    ///
+    /// Equivalent to `self.direction().reverse()`.
+    #[inline]
+    pub fn opposite_direction(self) -> Direction {
+        self.direction().reverse()
+    }
+
     /// Returns the port as a portgraph `PortOffset`.
     #[inline]
     pub(crate) fn pg_offset(self) -> portgraph::PortOffset {
@@ -3,12 +3,14 @@
 //! TODO: YAML declaration and parsing. This should be similar to a plugin
 //! system (outside the `types` module), which also parses nested [`OpDef`]s.
 
-use std::collections::btree_map;
 use std::collections::hash_map;
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+use itertools::Itertools;
 use thiserror::Error;
 
 use crate::hugr::IdentList;
@@ -17,7 +19,9 @@ use crate::ops::custom::{ExtensionOp, OpaqueOp};
 use crate::ops::{self, OpName, OpNameRef};
 use crate::types::type_param::{check_type_args, TypeArgError};
 use crate::types::type_param::{TypeArg, TypeParam};
-use crate::types::{check_typevar_decl, CustomType, Substitution, TypeBound, TypeName};
+use crate::types::{
+    check_typevar_decl, visit_custom_types, CustomType, Substitution, Type, TypeBound, TypeName,
+};
 use crate::types::{FunctionType, TypeNameRef};
 
 #[allow(dead_code)]
@@ -28,8 +32,8 @@ pub use infer::{ExtensionSolution, InferExtensionError};
 
 mod op_def;
 pub use op_def::{
-    CustomSignatureFunc, CustomValidator, OpDef, SignatureFromArgs, SignatureFunc,
-    ValidateJustArgs, ValidateTypeArgs,
+    check_deprecations, CustomSignatureFunc, CustomValidator, OpCapabilities, OpDef,
+    SignatureFromArgs, SignatureFunc, ValidateJustArgs, ValidateTypeArgs,
 };
 mod type_def;
 pub use type_def::{TypeDef, TypeDefBound};
@@ -44,7 +48,7 @@ pub mod declarative;
 
 /// Extension Registries store extensions to be looked up e.g. during validation.
 #[derive(Clone, Debug)]
-pub struct ExtensionRegistry(BTreeMap<ExtensionId, Extension>);
+pub struct ExtensionRegistry(BTreeMap<ExtensionId, Extension>, u64);
 
 impl ExtensionRegistry {
     /// Gets the Extension with the given name
@@ -61,21 +65,12 @@ impl ExtensionRegistry {
     pub fn try_new(
         value: impl IntoIterator<Item = Extension>,
     ) -> Result<Self, ExtensionRegistryError> {
-        let mut exts = BTreeMap::new();
-        for ext in value.into_iter() {
-            let prev = exts.insert(ext.name.clone(), ext);
-            if let Some(prev) = prev {
-                return Err(ExtensionRegistryError::AlreadyRegistered(
-                    prev.name().clone(),
-                ));
-            };
-        }
+        let res = Self::try_new_trusted(value)?;
         // Note this potentially asks extensions to validate themselves against other extensions that
         // may *not* be valid themselves yet. It'd be better to order these respecting dependencies,
         // or at least to validate the types first - which we don't do at all yet:
         // TODO https://github.com/CQCL/hugr/issues/624. However, parametrized types could be
         // cyclically dependent, so there is no perfect solution, and this is at least simple.
-        let res = ExtensionRegistry(exts);
         for ext in res.0.values() {
             ext.validate(&res)
                 .map_err(|e| ExtensionRegistryError::InvalidSignature(ext.name().clone(), e))?;
@@ -83,16 +78,116 @@ impl ExtensionRegistry {
         Ok(res)
     }
 
+    /// Makes a new ExtensionRegistry without validating the extensions in it.
+    ///
+    /// This avoids the cost of [`ExtensionRegistry::try_new`] when the
+    /// extensions are known to be valid together, e.g. because the same set
+    /// has already been validated. Only duplicate names are checked: if the
+    /// extensions are not in fact valid, computing signatures of their
+    /// operations or validating hugrs against the registry may fail in
+    /// unexpected ways, or give incorrect results.
+    pub fn try_new_trusted(
+        value: impl IntoIterator<Item = Extension>,
+    ) -> Result<Self, ExtensionRegistryError> {
+        let mut exts = BTreeMap::new();
+        for ext in value.into_iter() {
+            let prev = exts.insert(ext.name.clone(), ext);
+            if let Some(prev) = prev {
+                return Err(ExtensionRegistryError::AlreadyRegistered(
+                    prev.name().clone(),
+                ));
+            };
+        }
+        let fingerprint = fingerprint(&exts);
+        Ok(ExtensionRegistry(exts, fingerprint))
+    }
+
+    /// Makes a new ExtensionRegistry as [`ExtensionRegistry::try_new`], and
+    /// returns a fingerprint recording that the extensions validated together.
+    ///
+    /// The fingerprint may be stored, e.g. serialized alongside the
+    /// extensions, and passed to [`ExtensionRegistry::try_new_with_fingerprint`]
+    /// to skip validating the same extensions again, in this or another
+    /// process.
+    pub fn try_new_fingerprinted(
+        value: impl IntoIterator<Item = Extension>,
+    ) -> Result<(Self, ValidationFingerprint), ExtensionRegistryError> {
+        let res = Self::try_new(value)?;
+        let fingerprint = ValidationFingerprint::of(&res.0)
+            .map_err(|(name, e)| ExtensionRegistryError::Unencodable(name, e.to_string()))?;
+        Ok((res, fingerprint))
+    }
+
+    /// Makes a new ExtensionRegistry, skipping validation if `fingerprint`
+    /// was returned by [`ExtensionRegistry::try_new_fingerprinted`] for the
+    /// same extensions.
+    ///
+    /// Otherwise the extensions are validated as in
+    /// [`ExtensionRegistry::try_new`]. Checking the fingerprint serializes the
+    /// extensions, which for simple signatures costs more than validating
+    /// them: about 90µs against 35µs for an extension of 100 monomorphic
+    /// operations, see the `extension/registry_with_fingerprint_100_ops`
+    /// benchmark.
+    pub fn try_new_with_fingerprint(
+        value: impl IntoIterator<Item = Extension>,
+        fingerprint: &ValidationFingerprint,
+    ) -> Result<Self, ExtensionRegistryError> {
+        let res = Self::try_new_trusted(value)?;
+        if ValidationFingerprint::of(&res.0).is_ok_and(|fp| fp == *fingerprint) {
+            Ok(res)
+        } else {
+            Self::try_new(res.0.into_values())
+        }
+    }
+
+    /// Makes a new ExtensionRegistry containing the [`PRELUDE`] and the given
+    /// extensions, validating all of them.
+    ///
+    /// This is equivalent to merging [`PRELUDE_REGISTRY`] with a registry of
+    /// `exts`, but the extensions are only validated once they can refer to
+    /// the prelude.
+    pub fn with_prelude(
+        exts: impl IntoIterator<Item = Extension>,
+    ) -> Result<Self, ExtensionRegistryError> {
+        Self::try_new([PRELUDE.to_owned()].into_iter().chain(exts))
+    }
+
     /// Registers a new extension to the registry.
     ///
     /// Returns a reference to the registered extension if successful.
     pub fn register(&mut self, extension: Extension) -> Result<&Extension, ExtensionRegistryError> {
-        match self.0.entry(extension.name().clone()) {
-            btree_map::Entry::Occupied(_) => Err(ExtensionRegistryError::AlreadyRegistered(
-                extension.name().clone(),
-            )),
-            btree_map::Entry::Vacant(ve) => Ok(ve.insert(extension)),
+        let name = extension.name().clone();
+        if self.0.contains_key(&name) {
+            return Err(ExtensionRegistryError::AlreadyRegistered(name));
         }
+        self.0.insert(name.clone(), extension);
+        self.1 = fingerprint(&self.0);
+        Ok(&self.0[&name])
+    }
+
+    /// Applies a batch of edits to the registry atomically.
+    ///
+    /// If `edit` returns an error, the registry is restored to its state
+    /// before the call. Otherwise the edits are kept and the result of `edit`
+    /// returned.
+    pub fn with_transaction<T, E>(
+        &mut self,
+        edit: impl FnOnce(&mut Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let checkpoint = self.clone();
+        edit(self).map_err(|e| {
+            *self = checkpoint;
+            e
+        })
+    }
+
+    /// Merges the extensions of `other` into this registry.
+    ///
+    /// All the extensions are re-validated against the merged registry.
+    /// Returns [`ExtensionRegistryError::AlreadyRegistered`] if both registries
+    /// contain an extension with the same name.
+    pub fn merge(self, other: Self) -> Result<Self, ExtensionRegistryError> {
+        Self::try_new(self.0.into_values().chain(other.0.into_values()))
     }
 
     /// Returns the number of extensions in the registry.
@@ -105,10 +200,34 @@ impl ExtensionRegistry {
         self.0.is_empty()
     }
 
+    /// Looks up the [`TypeDef`] of a [`CustomType`], checking that the type
+    /// is a valid instance of it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SignatureError::ExtensionNotFound`] or
+    /// [`SignatureError::ExtensionTypeNotFound`] if the definition is not in
+    /// the registry, and [`SignatureError::WrongBound`] (or another error
+    /// from [`TypeDef::check_custom`]) if the type does not match it.
+    pub fn resolve_type(&self, custom: &CustomType) -> Result<&TypeDef, SignatureError> {
+        let def = custom.get_type_def(self)?;
+        def.check_custom(custom)?;
+        Ok(def)
+    }
+
     /// Returns an iterator over the extensions in the registry.
     pub fn iter(&self) -> impl Iterator<Item = (&ExtensionId, &Extension)> {
         self.0.iter()
     }
+
+    /// A value identifying the contents of the registry.
+    ///
+    /// Registries holding the same versions of the same extensions have equal
+    /// fingerprints. Adding a definition to an extension gives it a new
+    /// version, and hence a registry containing it a new fingerprint.
+    pub(crate) fn fingerprint(&self) -> u64 {
+        self.1
+    }
 }
 
 impl IntoIterator for ExtensionRegistry {
@@ -121,8 +240,87 @@ impl IntoIterator for ExtensionRegistry {
     }
 }
 
+/// Hashes the names and versions of the extensions in a registry; the empty
+/// registry has fingerprint 0 so that [EMPTY_REG] can be a constant.
+fn fingerprint(exts: &BTreeMap<ExtensionId, Extension>) -> u64 {
+    if exts.is_empty() {
+        return 0;
+    }
+    let mut hasher = hash_map::DefaultHasher::new();
+    for (name, ext) in exts {
+        name.hash(&mut hasher);
+        ext.generation.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// A record that a set of extensions validated together, see
+/// [`ExtensionRegistry::try_new_fingerprinted`].
+///
+/// This is a hash of a canonical serialization of the extensions, which does
+/// not depend on the order definitions were added in, and is stable across
+/// processes. Binary functions, such as custom signature functions, cannot be
+/// serialized and are only recorded as present.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct ValidationFingerprint(u64);
+
+impl ValidationFingerprint {
+    /// Computes the fingerprint of `exts`, failing with the first extension
+    /// that cannot be serialized.
+    fn of(
+        exts: &BTreeMap<ExtensionId, Extension>,
+    ) -> Result<Self, (ExtensionId, serde_json::Error)> {
+        // Maps are sorted by key, so the encoding does not depend on the order
+        // definitions were added in.
+        fn sorted<K: Ord, V>(map: &HashMap<K, V>) -> BTreeMap<&K, &V> {
+            map.iter().collect()
+        }
+        let mut hasher = Fnv1a::default();
+        for (name, ext) in exts {
+            let encoding = (
+                &ext.name,
+                &ext.extension_reqs,
+                sorted(&ext.types),
+                sorted(&ext.type_aliases),
+                sorted(&ext.values),
+                (ext.operations.iter())
+                    .map(|(name, def)| (name, def.fingerprint_repr()))
+                    .collect::<BTreeMap<_, _>>(),
+                sorted(&ext.op_aliases),
+            );
+            serde_json::to_writer(&mut hasher, &encoding).map_err(|e| (name.clone(), e))?;
+        }
+        Ok(Self(hasher.0))
+    }
+}
+
+/// The 64-bit FNV-1a hash, which unlike the hashers in `std` is fully
+/// specified and so stable across processes and platforms.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl std::io::Write for Fnv1a {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for &byte in buf {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 /// An Extension Registry containing no extensions.
-pub const EMPTY_REG: ExtensionRegistry = ExtensionRegistry(BTreeMap::new());
+pub const EMPTY_REG: ExtensionRegistry = ExtensionRegistry(BTreeMap::new(), 0);
 
 /// An error that can occur in computing the signature of a node.
 /// TODO: decide on failure modes
@@ -137,7 +335,11 @@ pub enum SignatureError {
     ExtensionMismatch(ExtensionId, ExtensionId),
     /// When the type arguments of the node did not match the params declared by the OpDef
     #[error("Type arguments of node did not match params declared by definition: {0}")]
-    TypeArgMismatch(#[from] TypeArgError),
+    TypeArgMismatch(TypeArgError),
+    /// The number of type arguments does not match the number of params
+    /// declared by the definition.
+    #[error("Expected {expected} type arguments, got {got}")]
+    MissingTypeArgs { expected: usize, got: usize },
     /// Invalid type arguments
     #[error("Invalid type arguments for operation")]
     InvalidTypeArgs,
@@ -147,6 +349,18 @@ pub enum SignatureError {
     /// The Extension was found in the registry, but did not contain the Type(Def) referenced in the Signature
     #[error("Extension '{exn}' did not contain expected TypeDef '{typ}'")]
     ExtensionTypeNotFound { exn: ExtensionId, typ: TypeName },
+    /// The Extension was found in the registry, but did not contain the OpDef referenced by an operation
+    #[error("Extension '{exn}' did not contain expected OpDef '{op}'")]
+    ExtensionOpNotFound { exn: ExtensionId, op: OpName },
+    /// A type alias refers to itself, possibly through other aliases
+    #[error("Type alias '{0}' is defined in terms of itself")]
+    CyclicTypeAlias(TypeName),
+    /// A type parameter declares an empty range of values
+    #[error("Type parameter {0} admits no values")]
+    EmptyTypeParam(TypeParam),
+    /// The bound of a TypeDef is computed from a parameter that is not a type
+    #[error("Bound of TypeDef '{typ}' refers to parameter {idx}, which is not a type parameter")]
+    InvalidBoundParam { typ: TypeName, idx: usize },
     /// The bound recorded for a CustomType doesn't match what the TypeDef would compute
     #[error("Bound on CustomType ({actual}) did not match TypeDef ({expected})")]
     WrongBound {
@@ -162,6 +376,10 @@ pub enum SignatureError {
     /// A type variable that was used has not been declared
     #[error("Type variable {idx} was not declared ({num_decls} in scope)")]
     FreeTypeVar { idx: usize, num_decls: usize },
+    /// A type variable is used where the types must be monomorphic, see
+    /// [`check_monomorphic`](crate::hugr::check_monomorphic)
+    #[error("Type variable {idx} was used in a monomorphic context")]
+    NotMonomorphic { idx: usize },
     /// The result of the type application stored in a [Call]
     /// is not what we get by applying the type-args to the polymorphic function
     ///
@@ -186,6 +404,17 @@ pub enum SignatureError {
     },
 }
 
+impl From<TypeArgError> for SignatureError {
+    fn from(err: TypeArgError) -> Self {
+        match err {
+            TypeArgError::WrongNumberArgs(got, expected) => {
+                SignatureError::MissingTypeArgs { expected, got }
+            }
+            err => SignatureError::TypeArgMismatch(err),
+        }
+    }
+}
+
 /// Concrete instantiations of types and operations defined in extensions.
 trait CustomConcrete {
     /// The identifier type for the concrete object.
@@ -245,13 +474,13 @@ trait TypeParametrised {
     fn extension(&self) -> &ExtensionId;
     /// Check provided type arguments are valid against parameters.
     fn check_args_impl(&self, args: &[TypeArg]) -> Result<(), SignatureError> {
-        check_type_args(args, self.params()).map_err(SignatureError::TypeArgMismatch)
+        check_type_args(args, self.params()).map_err(Into::into)
     }
 }
 
 /// A constant value provided by a extension.
 /// Must be an instance of a type available to the extension.
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ExtensionValue {
     extension: ExtensionId,
     name: ValueName,
@@ -289,9 +518,12 @@ pub struct Extension {
     /// That is, an upper-bound on the types that can be returned by
     /// computing the signature of any operation in this extension,
     /// for any possible [TypeArg].
-    pub extension_reqs: ExtensionSet,
+    extension_reqs: ExtensionSet,
     /// Types defined by this extension.
     types: HashMap<TypeName, TypeDef>,
+    /// Named aliases for types, expanded when computing signatures.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    type_aliases: HashMap<TypeName, Type>,
     /// Static values defined by this extension.
     values: HashMap<ValueName, ExtensionValue>,
     /// Operation declarations with serializable definitions.
@@ -301,6 +533,20 @@ pub struct Extension {
     // and the other references to the OpDef are from ExternalOp's in the Hugr
     // (which are serialized as OpaqueOp's i.e. Strings).
     operations: HashMap<OpName, Arc<op_def::OpDef>>,
+    /// Former names of operations, mapped to the current name of the
+    /// operation they resolve to.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    op_aliases: HashMap<OpName, OpName>,
+    /// Version of the definitions above, unique across all extensions and
+    /// renewed whenever a definition is added. Clones share the version.
+    #[serde(skip, default = "next_generation")]
+    generation: u64,
+}
+
+/// Returns a fresh [`Extension`] version.
+fn next_generation() -> u64 {
+    static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
 }
 
 impl Extension {
@@ -315,14 +561,28 @@ impl Extension {
             name,
             extension_reqs: extension_reqs.into(),
             types: Default::default(),
+            type_aliases: Default::default(),
             values: Default::default(),
             operations: Default::default(),
+            op_aliases: Default::default(),
+            generation: next_generation(),
         }
     }
 
+    /// Marks the definitions of the extension as changed.
+    fn bump_generation(&mut self) {
+        self.generation = next_generation();
+    }
+
     /// Allows read-only access to the operations in this Extension
+    ///
+    /// Names registered with [`Extension::add_op_alias`] resolve to the
+    /// operation they alias.
     pub fn get_op(&self, op_name: &OpNameRef) -> Option<&Arc<op_def::OpDef>> {
-        self.operations.get(op_name)
+        self.operations.get(op_name).or_else(|| {
+            let current_name = self.op_aliases.get(op_name)?;
+            self.operations.get(current_name)
+        })
     }
 
     /// Allows read-only access to the types in this Extension
@@ -330,6 +590,23 @@ impl Extension {
         self.types.get(type_name)
     }
 
+    /// Allows read-only access to the type aliases in this Extension
+    pub fn get_type_alias(&self, alias_name: &TypeNameRef) -> Option<&Type> {
+        self.type_aliases.get(alias_name)
+    }
+
+    /// Returns a reference to the type alias `alias_name` of this Extension,
+    /// to be used in place of its definition. See [`Extension::add_type_alias`].
+    pub fn instantiate_type_alias(&self, alias_name: &TypeNameRef) -> Option<CustomType> {
+        let definition = self.type_aliases.get(alias_name)?;
+        Some(CustomType::new(
+            alias_name,
+            [],
+            self.name.clone(),
+            definition.least_upper_bound(),
+        ))
+    }
+
     /// Allows read-only access to the values in this Extension
     pub fn get_value(&self, value_name: &ValueNameRef) -> Option<&ExtensionValue> {
         self.values.get(value_name)
@@ -340,6 +617,17 @@ impl Extension {
         &self.name
     }
 
+    /// Returns the other extensions defining types used by this extension.
+    pub fn extension_reqs(&self) -> &ExtensionSet {
+        &self.extension_reqs
+    }
+
+    /// Replaces the other extensions defining types used by this extension.
+    pub fn set_extension_reqs(&mut self, extension_reqs: impl Into<ExtensionSet>) {
+        self.extension_reqs = extension_reqs.into();
+        self.bump_generation();
+    }
+
     /// Iterator over the operations of this [`Extension`].
     pub fn operations(&self) -> impl Iterator<Item = (&OpName, &Arc<OpDef>)> {
         self.operations.iter()
@@ -350,6 +638,88 @@ impl Extension {
         self.types.iter()
     }
 
+    /// Returns `true` if the two extensions have the same name, requirements,
+    /// types, type aliases, values and operations.
+    ///
+    /// Unlike `==`, which only compares names, this detects changes to the
+    /// contents of an extension. Signatures of operations computed by custom
+    /// binaries are only compared by their static parameters, and lowerings
+    /// and constant folders are not compared.
+    pub fn content_eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.extension_reqs == other.extension_reqs
+            && self.types == other.types
+            && self.type_aliases == other.type_aliases
+            && self.values == other.values
+            && self.op_aliases == other.op_aliases
+            && self.operations.len() == other.operations.len()
+            && self.operations.iter().all(|(name, op)| {
+                other
+                    .operations
+                    .get(name)
+                    .is_some_and(|other_op| op.content_eq(other_op))
+            })
+    }
+
+    /// Iterator over the type aliases of this [`Extension`].
+    pub fn type_aliases(&self) -> impl Iterator<Item = (&TypeName, &Type)> {
+        self.type_aliases.iter()
+    }
+
+    /// Add a named alias for a type to the extension.
+    ///
+    /// The alias is referred to by a [`CustomType`] of this extension with the
+    /// alias name, no arguments and the bound of the definition, as returned
+    /// by [`Extension::instantiate_type_alias`]. Such references are replaced
+    /// by the definition when computing the signature of an operation
+    /// against a registry containing this extension.
+    pub fn add_type_alias(
+        &mut self,
+        name: impl Into<TypeName>,
+        definition: Type,
+    ) -> Result<&Type, ExtensionBuildError> {
+        let name = name.into();
+        if self.types.contains_key(&name) {
+            return Err(ExtensionBuildError::TypeDefExists(name));
+        }
+        self.bump_generation();
+        match self.type_aliases.entry(name) {
+            hash_map::Entry::Occupied(oe) => {
+                Err(ExtensionBuildError::TypeAliasExists(oe.key().clone()))
+            }
+            hash_map::Entry::Vacant(ve) => Ok(ve.insert(definition)),
+        }
+    }
+
+    /// Add a former name of an operation in the extension.
+    ///
+    /// Looking up `old_name` with [`Extension::get_op`] returns the definition
+    /// of `current_name`, so that operations serialized before a rename can
+    /// still be resolved.
+    pub fn add_op_alias(
+        &mut self,
+        old_name: impl Into<OpName>,
+        current_name: impl Into<OpName>,
+    ) -> Result<(), ExtensionBuildError> {
+        let (old_name, current_name) = (old_name.into(), current_name.into());
+        if !self.operations.contains_key(&current_name) {
+            return Err(ExtensionBuildError::UnknownOp(current_name));
+        }
+        if self.operations.contains_key(&old_name) {
+            return Err(ExtensionBuildError::OpDefExists(old_name));
+        }
+        self.bump_generation();
+        match self.op_aliases.entry(old_name) {
+            hash_map::Entry::Occupied(oe) => {
+                Err(ExtensionBuildError::OpAliasExists(oe.key().clone()))
+            }
+            hash_map::Entry::Vacant(ve) => {
+                ve.insert(current_name);
+                Ok(())
+            }
+        }
+    }
+
     /// Add a named static value to the extension.
     pub fn add_value(
         &mut self,
@@ -361,6 +731,7 @@ impl Extension {
             name: name.into(),
             typed_value,
         };
+        self.bump_generation();
         match self.values.entry(extension_value.name.clone()) {
             hash_map::Entry::Occupied(_) => {
                 Err(ExtensionBuildError::ValueExists(extension_value.name))
@@ -383,14 +754,48 @@ impl Extension {
     // Validates against a registry, which we can assume includes this extension itself.
     // (TODO deal with the registry itself containing invalid extensions!)
     fn validate(&self, all_exts: &ExtensionRegistry) -> Result<(), SignatureError> {
-        // We should validate TypeParams of TypeDefs too - https://github.com/CQCL/hugr/issues/624
+        for type_def in self.types.values() {
+            type_def.validate(all_exts)?;
+        }
         for op_def in self.operations.values() {
             op_def.validate(all_exts)?;
         }
+        for (name, definition) in self.type_aliases.iter() {
+            definition.validate(all_exts, &[])?;
+            validate_alias_acyclic(&self.name, name, definition, all_exts)?;
+        }
         Ok(())
     }
 }
 
+/// Checks that expanding the aliases used by `definition` never leads back to
+/// the alias `name` of the extension `ext`.
+fn validate_alias_acyclic(
+    ext: &ExtensionId,
+    name: &TypeNameRef,
+    definition: &Type,
+    all_exts: &ExtensionRegistry,
+) -> Result<(), SignatureError> {
+    let mut visited = HashSet::new();
+    let mut stack = vec![definition];
+    while let Some(ty) = stack.pop() {
+        let mut custom_types = Vec::new();
+        visit_custom_types(ty, &mut |custom| custom_types.push(custom));
+        for custom in custom_types {
+            let Some(definition) = custom.get_type_alias(all_exts) else {
+                continue;
+            };
+            if custom.extension() == ext && custom.name() == name {
+                return Err(SignatureError::CyclicTypeAlias(custom.name().clone()));
+            }
+            if visited.insert((custom.extension(), custom.name())) {
+                stack.push(definition);
+            }
+        }
+    }
+    Ok(())
+}
+
 impl PartialEq for Extension {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
@@ -406,6 +811,9 @@ pub enum ExtensionRegistryError {
     /// A registered extension has invalid signatures.
     #[error("The extension {0} contains an invalid signature, {1}.")]
     InvalidSignature(ExtensionId, #[source] SignatureError),
+    /// A registered extension cannot be encoded in a [`ValidationFingerprint`].
+    #[error("The extension {0} cannot be fingerprinted: {1}")]
+    Unencodable(ExtensionId, String),
 }
 
 /// An error that can occur in building a new extension.
@@ -417,9 +825,18 @@ pub enum ExtensionBuildError {
     /// Existing [`TypeDef`]
     #[error("Extension already has an type called {0}.")]
     TypeDefExists(TypeName),
+    /// Existing type alias
+    #[error("Extension already has a type alias called {0}.")]
+    TypeAliasExists(TypeName),
     /// Existing [`ExtensionValue`]
     #[error("Extension already has an extension value called {0}.")]
     ValueExists(ValueName),
+    /// Existing alias for an [`OpDef`]
+    #[error("Extension already has an op alias called {0}.")]
+    OpAliasExists(OpName),
+    /// Alias for an [`OpDef`] that does not exist
+    #[error("Extension has no op called {0}.")]
+    UnknownOp(OpName),
 }
 
 /// A set of extensions identified by their unique [`ExtensionId`].
@@ -496,6 +913,22 @@ impl ExtensionSet {
         ExtensionSet::from_iter(other.0.difference(&self.0).cloned())
     }
 
+    /// Retains only the extensions for which `f` returns `true`.
+    ///
+    /// Type variables are not passed to `f` and are always retained.
+    pub fn retain(&mut self, f: impl Fn(&ExtensionId) -> bool) {
+        self.0.retain(|e| as_typevar(e).is_some() || f(e));
+    }
+
+    /// Returns the set of extensions for which `f` returns `true`.
+    ///
+    /// Type variables are not passed to `f` and are always retained.
+    pub fn filtered(&self, f: impl Fn(&ExtensionId) -> bool) -> Self {
+        let mut set = self.clone();
+        set.retain(f);
+        set
+    }
+
     /// Iterate over the contained ExtensionIds
     pub fn iter(&self) -> impl Iterator<Item = &ExtensionId> {
         self.0.iter()
@@ -506,6 +939,11 @@ impl ExtensionSet {
         self.0.is_empty()
     }
 
+    /// Iterate over the indices of the type variables in the set.
+    pub(crate) fn type_vars(&self) -> impl Iterator<Item = usize> + '_ {
+        self.iter().filter_map(as_typevar)
+    }
+
     pub(crate) fn validate(&self, params: &[TypeParam]) -> Result<(), SignatureError> {
         self.iter()
             .filter_map(as_typevar)
@@ -540,8 +978,13 @@ fn as_typevar(e: &ExtensionId) -> Option<usize> {
 }
 
 impl Display for ExtensionSet {
+    /// Formats the set as `[a, b, ?0]`, with type variables prefixed by `?`.
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        f.debug_list().entries(self.0.iter()).finish()
+        let entries = self.0.iter().map(|e| match as_typevar(e) {
+            Some(idx) => format!("?{idx}"),
+            None => e.to_string(),
+        });
+        write!(f, "[{}]", entries.format(", "))
     }
 }
 
@@ -550,3 +993,377 @@ impl FromIterator<ExtensionId> for ExtensionSet {
         Self(BTreeSet::from_iter(iter))
     }
 }
+
+impl IntoIterator for ExtensionSet {
+    type Item = ExtensionId;
+
+    type IntoIter = <BTreeSet<ExtensionId> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ExtensionSet {
+    type Item = &'a ExtensionId;
+
+    type IntoIter = <&'a BTreeSet<ExtensionId> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cool_asserts::assert_matches;
+
+    use super::*;
+    use crate::extension::prelude::{BOOL_T, PRELUDE_ID, QB_T};
+    use crate::ops::AliasDecl;
+    use crate::type_row;
+
+    const EXT_ID: ExtensionId = ExtensionId::new_unchecked("aliases");
+
+    /// A reference to the type alias `name` of [EXT_ID].
+    fn alias(name: &str) -> Type {
+        Type::new_extension(CustomType::new(name, [], EXT_ID, TypeBound::Eq))
+    }
+
+    #[test]
+    fn type_alias_expanded() -> Result<(), Box<dyn std::error::Error>> {
+        let mut e = Extension::new(EXT_ID);
+        e.add_type_alias("Bit", BOOL_T)?;
+        let bit = Type::new_extension(e.instantiate_type_alias("Bit").unwrap());
+        assert_eq!(bit, alias("Bit"));
+        e.add_type_alias("Bits", Type::new_tuple(vec![bit.clone(), bit]))?;
+        e.add_op(
+            "MeasureTwo".into(),
+            "".to_string(),
+            FunctionType::new(type_row![QB_T, QB_T], vec![alias("Bits")]),
+        )?;
+        assert_eq!(
+            e.add_type_alias("Bit", BOOL_T),
+            Err(ExtensionBuildError::TypeAliasExists("Bit".into()))
+        );
+
+        let reg = ExtensionRegistry::with_prelude([e])?;
+        let sig = reg
+            .get(&EXT_ID)
+            .unwrap()
+            .get_op("MeasureTwo")
+            .unwrap()
+            .compute_signature(&[], &reg)?;
+        assert_eq!(
+            sig,
+            FunctionType::new(
+                type_row![QB_T, QB_T],
+                vec![Type::new_tuple(type_row![BOOL_T, BOOL_T])]
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn type_alias_scoped() -> Result<(), Box<dyn std::error::Error>> {
+        const OTHER_ID: ExtensionId = ExtensionId::new_unchecked("other_aliases");
+        let mut e = Extension::new(EXT_ID);
+        e.add_type_alias("Bit", BOOL_T)?;
+        let mut other = Extension::new(OTHER_ID);
+        other.add_type_alias("Bit", Type::new_unit_sum(4))?;
+        let other_bit = Type::new_extension(other.instantiate_type_alias("Bit").unwrap());
+        // A module-level alias with the same name is not expanded.
+        let module_alias = Type::new_alias(AliasDecl::new("Bit", TypeBound::Eq));
+        e.add_op(
+            "Op".into(),
+            "".to_string(),
+            FunctionType::new(vec![alias("Bit"), other_bit], vec![module_alias.clone()]),
+        )?;
+
+        let reg = ExtensionRegistry::with_prelude([e, other])?;
+        let sig = reg
+            .get(&EXT_ID)
+            .unwrap()
+            .get_op("Op")
+            .unwrap()
+            .compute_signature(&[], &reg)?;
+        assert_eq!(
+            sig,
+            FunctionType::new(vec![BOOL_T, Type::new_unit_sum(4)], vec![module_alias])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn type_alias_bound() {
+        let mut e = Extension::new(EXT_ID);
+        e.add_type_alias("Qubit", QB_T).unwrap();
+        e.add_op(
+            "Op".into(),
+            "".to_string(),
+            FunctionType::new(vec![alias("Qubit")], type_row![]),
+        )
+        .unwrap();
+        assert_matches!(
+            ExtensionRegistry::with_prelude([e]),
+            Err(ExtensionRegistryError::InvalidSignature(
+                _,
+                SignatureError::WrongBound {
+                    actual: TypeBound::Eq,
+                    expected: TypeBound::Any
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn type_alias_cycle() {
+        let mut e = Extension::new(EXT_ID);
+        e.add_type_alias("A", Type::new_tuple(vec![alias("B")]))
+            .unwrap();
+        e.add_type_alias("B", alias("A")).unwrap();
+        assert_matches!(
+            ExtensionRegistry::try_new([e]),
+            Err(ExtensionRegistryError::InvalidSignature(
+                _,
+                SignatureError::CyclicTypeAlias(_)
+            ))
+        );
+    }
+
+    #[test]
+    fn op_alias() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::builder::{DFGBuilder, Dataflow, DataflowHugr};
+        use crate::ops::custom::CustomOp;
+        use crate::ops::handle::NodeHandle;
+        use crate::ops::OpType;
+        use crate::{Hugr, HugrView};
+
+        let sig = FunctionType::new_endo(type_row![QB_T]);
+        let mut e = Extension::new(EXT_ID);
+        e.add_op("NewName".into(), "".to_string(), sig.clone())?;
+        e.add_op_alias("OldName", "NewName")?;
+        assert_eq!(
+            e.add_op_alias("OldName", "NewName"),
+            Err(ExtensionBuildError::OpAliasExists("OldName".into()))
+        );
+        assert_eq!(
+            e.add_op_alias("Other", "Missing"),
+            Err(ExtensionBuildError::UnknownOp("Missing".into()))
+        );
+        assert_matches!(
+            e.add_op("OldName".into(), "".to_string(), sig.clone()),
+            Err(ExtensionBuildError::OpAliasExists(_))
+        );
+        assert_eq!(e.get_op("OldName").unwrap().name(), "NewName");
+        let reg = ExtensionRegistry::with_prelude([e])?;
+
+        // A hugr serialized before the op was renamed.
+        let old_op = OpaqueOp::new(EXT_ID, "OldName", "".to_string(), vec![], sig.clone());
+        let mut dfg = DFGBuilder::new(sig.with_extension_delta(EXT_ID))?;
+        let [q] = dfg.input_wires_arr();
+        let old = dfg.add_dataflow_op(CustomOp::new_opaque(old_op), [q])?;
+        let hugr = dfg.finish_hugr_with_outputs(old.outputs(), &reg)?;
+
+        let mut loaded: Hugr = serde_json::from_str(&serde_json::to_string(&hugr)?)?;
+        loaded.update_validate(&reg)?;
+        let OpType::CustomOp(op) = loaded.get_optype(old.node()) else {
+            panic!("expected a custom op");
+        };
+        assert_eq!(op.as_extension_op().unwrap().def().name(), "NewName");
+        Ok(())
+    }
+
+    #[test]
+    fn registry_trusted() {
+        let mut e = Extension::new(EXT_ID);
+        e.add_type_alias("A", alias("A")).unwrap();
+        // The cyclic alias is only caught by validation.
+        assert_matches!(ExtensionRegistry::try_new([e.clone()]), Err(_));
+        let reg = ExtensionRegistry::try_new_trusted([e.clone()]).unwrap();
+        assert!(reg.contains(&EXT_ID));
+
+        assert_eq!(
+            ExtensionRegistry::try_new_trusted([e.clone(), e]).unwrap_err(),
+            ExtensionRegistryError::AlreadyRegistered(EXT_ID)
+        );
+    }
+
+    #[test]
+    fn validation_fingerprint() -> Result<(), Box<dyn std::error::Error>> {
+        let ext = |ops: &[&str]| {
+            let mut e = Extension::new(EXT_ID);
+            for &op in ops {
+                e.add_op(
+                    op.into(),
+                    "".into(),
+                    FunctionType::new_endo(type_row![BOOL_T]),
+                )
+                .unwrap();
+            }
+            e
+        };
+        let (_, fingerprint) =
+            ExtensionRegistry::try_new_fingerprinted([PRELUDE.to_owned(), ext(&["a", "b", "c"])])?;
+        let fingerprint: ValidationFingerprint =
+            serde_json::from_str(&serde_json::to_string(&fingerprint)?)?;
+
+        // The fingerprint does not depend on the order of the definitions.
+        let reordered = ExtensionRegistry::try_new_with_fingerprint(
+            [PRELUDE.to_owned(), ext(&["c", "b", "a"])],
+            &fingerprint,
+        )?;
+        assert_eq!(
+            ValidationFingerprint::of(&reordered.0).ok(),
+            Some(fingerprint)
+        );
+        let other = ExtensionRegistry::try_new([PRELUDE.to_owned(), ext(&["a", "b"])])?;
+        assert_ne!(ValidationFingerprint::of(&other.0).ok(), Some(fingerprint));
+
+        // Extensions not matching the fingerprint are validated.
+        let mut invalid = Extension::new(EXT_ID);
+        invalid.add_type_alias("A", alias("A"))?;
+        assert_matches!(
+            ExtensionRegistry::try_new_with_fingerprint([invalid], &fingerprint),
+            Err(ExtensionRegistryError::InvalidSignature(..))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn registry_merge() {
+        let logic = crate::std_extensions::logic::LOGIC_REG.to_owned();
+        let merged = PRELUDE_REGISTRY.to_owned().merge(logic).unwrap();
+        assert_eq!(merged.len(), 2);
+        assert!(merged.contains(&PRELUDE_ID));
+        assert!(merged.contains(&crate::std_extensions::logic::EXTENSION_ID));
+
+        assert_eq!(
+            merged.merge(PRELUDE_REGISTRY.to_owned()).unwrap_err(),
+            ExtensionRegistryError::AlreadyRegistered(PRELUDE_ID)
+        );
+    }
+
+    #[test]
+    fn resolve_type() {
+        use crate::extension::prelude::USIZE_CUSTOM_T;
+
+        let def = PRELUDE_REGISTRY.resolve_type(&USIZE_CUSTOM_T).unwrap();
+        assert_eq!(def.name(), USIZE_CUSTOM_T.name());
+        assert_eq!(def.extension(), &PRELUDE_ID);
+
+        let wrong_bound = CustomType::new("usize", [], PRELUDE_ID, TypeBound::Any);
+        assert_eq!(
+            PRELUDE_REGISTRY.resolve_type(&wrong_bound).unwrap_err(),
+            SignatureError::WrongBound {
+                actual: TypeBound::Any,
+                expected: TypeBound::Eq
+            }
+        );
+        let unknown = CustomType::new("unknown", [], PRELUDE_ID, TypeBound::Any);
+        assert_eq!(
+            PRELUDE_REGISTRY.resolve_type(&unknown).unwrap_err(),
+            SignatureError::ExtensionTypeNotFound {
+                exn: PRELUDE_ID,
+                typ: "unknown".into()
+            }
+        );
+        assert_eq!(
+            EMPTY_REG.resolve_type(&USIZE_CUSTOM_T).unwrap_err(),
+            SignatureError::ExtensionNotFound(PRELUDE_ID)
+        );
+    }
+
+    #[test]
+    fn registry_transaction() {
+        let logic = crate::std_extensions::logic::EXTENSION.to_owned();
+        let mut reg = PRELUDE_REGISTRY.to_owned();
+
+        let res = reg.with_transaction(|reg| {
+            reg.register(logic.clone())?;
+            reg.register(PRELUDE.to_owned())?;
+            Ok(())
+        });
+        assert_eq!(
+            res,
+            Err(ExtensionRegistryError::AlreadyRegistered(PRELUDE_ID))
+        );
+        assert_eq!(reg.len(), 1);
+        assert!(!reg.contains(&crate::std_extensions::logic::EXTENSION_ID));
+        assert_eq!(reg.fingerprint(), PRELUDE_REGISTRY.fingerprint());
+
+        reg.with_transaction(|reg| reg.register(logic).map(|_| ()))
+            .unwrap();
+        assert_eq!(reg.len(), 2);
+    }
+
+    #[test]
+    fn extension_set_display() {
+        let logic = ExtensionId::new_unchecked("logic");
+        assert_eq!(logic.to_string(), "logic");
+
+        let set = ExtensionSet::singleton(&logic).union(ExtensionSet::type_var(3));
+        assert_eq!(set.to_string(), "[?3, logic]");
+        assert_eq!(ExtensionSet::new().to_string(), "[]");
+    }
+
+    #[test]
+    fn extension_set_into_iter() {
+        let a = ExtensionId::new_unchecked("A");
+        let b = ExtensionId::new_unchecked("B");
+        let set = ExtensionSet::from_iter([b.clone(), a.clone()]);
+
+        let mut by_ref = Vec::new();
+        for e in &set {
+            by_ref.push(e);
+        }
+        assert_eq!(by_ref, [&a, &b]);
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), [a, b]);
+    }
+
+    #[test]
+    fn filter_extension_set() {
+        let logic = ExtensionId::new_unchecked("logic");
+        let mut set =
+            ExtensionSet::from_iter([PRELUDE_ID, logic.clone()]).union(ExtensionSet::type_var(0));
+        let expected = ExtensionSet::singleton(&logic).union(ExtensionSet::type_var(0));
+        assert_eq!(set.filtered(|e| e != &PRELUDE_ID), expected);
+
+        set.retain(|e| e != &PRELUDE_ID);
+        assert_eq!(set, expected);
+        set.retain(|_| false);
+        assert_eq!(set, ExtensionSet::type_var(0));
+    }
+
+    #[test]
+    fn extension_content_eq() -> Result<(), ExtensionBuildError> {
+        let make = |ops: &[&str]| -> Result<Extension, ExtensionBuildError> {
+            let mut e = Extension::new(EXT_ID);
+            for op in ops {
+                e.add_op(
+                    (*op).into(),
+                    "".to_string(),
+                    FunctionType::new_endo(type_row![BOOL_T]),
+                )?;
+            }
+            Ok(e)
+        };
+        let a = make(&["Not", "And"])?;
+        let b = make(&["And", "Not"])?;
+        let c = make(&["Not"])?;
+        assert!(a.content_eq(&b));
+        // Extensions with the same name are equal even if their contents differ.
+        assert_eq!(a, c);
+        assert!(!a.content_eq(&c));
+
+        let mut d = make(&["And"])?;
+        d.add_op(
+            "Not".into(),
+            "".to_string(),
+            FunctionType::new_endo(type_row![BOOL_T]),
+        )?
+        .set_deprecated("use Xor");
+        assert!(!a.content_eq(&d));
+        Ok(())
+    }
+}
@@ -1,8 +1,8 @@
 use std::cmp::min;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fmt::{Debug, Formatter};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock};
 
 use super::{
     ConstFold, ConstFoldResult, Extension, ExtensionBuildError, ExtensionId, ExtensionRegistry,
@@ -12,7 +12,7 @@ use super::{
 use crate::ops::{OpName, OpNameRef};
 use crate::types::type_param::{check_type_args, TypeArg, TypeParam};
 use crate::types::{FunctionType, PolyFuncType};
-use crate::Hugr;
+use crate::{Hugr, HugrView, Node};
 
 /// Trait necessary for binary computations of OpDef signature
 pub trait CustomSignatureFunc: Send + Sync {
@@ -236,11 +236,15 @@ impl SignatureFunc {
                 (&custom.poly_func, args)
             }
             SignatureFunc::CustomFunc(func) => {
-                let static_params = func.static_params();
-                let (static_args, other_args) = args.split_at(min(static_params.len(), args.len()));
-
-                check_type_args(static_args, static_params)?;
-                temp = func.compute_signature(static_args, def, exts)?;
+                let (static_args, other_args) =
+                    args.split_at(min(func.static_params().len(), args.len()));
+                temp = if std::ptr::eq(self, &def.signature_func) {
+                    def.custom_signature(func.as_ref(), static_args, exts)?
+                } else {
+                    // Only results of the OpDef's own signature function are cached.
+                    check_type_args(static_args, func.static_params())?;
+                    func.compute_signature(static_args, def, exts)?
+                };
                 (&temp, other_args)
             }
         };
@@ -285,6 +289,51 @@ impl Debug for LowerFunc {
     }
 }
 
+/// A set of flags describing behaviour of an operation that passes may need
+/// to respect, e.g. when reordering operations.
+///
+/// Flags are combined with `|`.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(transparent)]
+pub struct OpCapabilities(u8);
+
+impl OpCapabilities {
+    /// No capabilities.
+    pub const NONE: Self = Self(0);
+    /// The operation measures a qubit.
+    pub const MEASUREMENT: Self = Self(1);
+    /// The operation resets a qubit.
+    pub const RESET: Self = Self(1 << 1);
+    /// The operation has side effects beyond its outputs.
+    pub const SIDE_EFFECT: Self = Self(1 << 2);
+
+    /// Whether all the flags of `other` are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether no flag is set.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl std::ops::BitOr for OpCapabilities {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for OpCapabilities {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
 /// Serializable definition for dynamically loaded operations.
 ///
 /// TODO: Define a way to construct new OpDef's from a serialized definition.
@@ -300,6 +349,12 @@ pub struct OpDef {
     /// Miscellaneous data associated with the operation.
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     misc: HashMap<String, serde_yaml::Value>,
+    /// If the operation is deprecated, the reason why.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deprecated: Option<String>,
+    /// Behaviour of the operation that passes may need to respect.
+    #[serde(default, skip_serializing_if = "OpCapabilities::is_empty")]
+    capabilities: OpCapabilities,
 
     #[serde(flatten)]
     signature_func: SignatureFunc,
@@ -311,6 +366,63 @@ pub struct OpDef {
     /// Operations can optionally implement [`ConstFold`] to implement constant folding.
     #[serde(skip)]
     constant_folder: Option<Box<dyn ConstFold>>,
+
+    /// Signatures previously computed by a [`SignatureFunc::CustomFunc`].
+    #[serde(skip)]
+    signature_cache: SignatureCache,
+
+    /// The fingerprint of the first registry the definition was successfully
+    /// validated against, see [`OpDef::validate`].
+    #[serde(skip)]
+    validated: OnceLock<u64>,
+}
+
+/// Memoized results of a [CustomSignatureFunc], keyed on the static arguments
+/// and the [ExtensionRegistry] they were computed from.
+///
+/// Operations are usually instantiated with only a handful of distinct static
+/// arguments, so the entries are kept in a queue and searched linearly. Once
+/// [SignatureCache::CAPACITY] entries are stored, the oldest one is evicted.
+#[derive(Default)]
+struct SignatureCache(Mutex<VecDeque<(Vec<TypeArg>, u64, PolyFuncType)>>);
+
+impl SignatureCache {
+    /// The maximum number of signatures kept per [OpDef].
+    const CAPACITY: usize = 64;
+
+    /// Returns the cached signature for `static_args` and `exts`, computing
+    /// and storing it with `compute` if there is none. Errors are not cached.
+    fn get_or_try_insert(
+        &self,
+        static_args: &[TypeArg],
+        exts: &ExtensionRegistry,
+        compute: impl FnOnce() -> Result<PolyFuncType, SignatureError>,
+    ) -> Result<PolyFuncType, SignatureError> {
+        let fingerprint = exts.fingerprint();
+        let cached = self.0.lock().ok().and_then(|cache| {
+            cache
+                .iter()
+                .find(|(args, fp, _)| *fp == fingerprint && args == static_args)
+                .map(|(_, _, pf)| pf.clone())
+        });
+        if let Some(pf) = cached {
+            return Ok(pf);
+        }
+        let pf = compute()?;
+        if let Ok(mut cache) = self.0.lock() {
+            if cache.len() == Self::CAPACITY {
+                cache.pop_front();
+            }
+            cache.push_back((static_args.to_vec(), fingerprint, pf.clone()));
+        }
+        Ok(pf)
+    }
+}
+
+impl Debug for SignatureCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SignatureCache")
+    }
 }
 
 impl OpDef {
@@ -332,8 +444,7 @@ impl OpDef {
                 static_args
                     .iter()
                     .try_for_each(|ta| ta.validate(exts, &[]))?;
-                check_type_args(static_args, custom.static_params())?;
-                temp = custom.compute_signature(static_args, self, exts)?;
+                temp = self.custom_signature(custom.as_ref(), static_args, exts)?;
                 (&temp, other_args)
             }
         };
@@ -353,6 +464,22 @@ impl OpDef {
         self.signature_func.compute_signature(self, args, exts)
     }
 
+    /// Computes the polymorphic signature of a custom signature function of
+    /// this OpDef, reusing the result of any previous call with the same
+    /// static arguments and registry contents.
+    fn custom_signature(
+        &self,
+        func: &dyn CustomSignatureFunc,
+        static_args: &[TypeArg],
+        exts: &ExtensionRegistry,
+    ) -> Result<PolyFuncType, SignatureError> {
+        self.signature_cache
+            .get_or_try_insert(static_args, exts, || {
+                check_type_args(static_args, func.static_params())?;
+                func.compute_signature(static_args, self, exts)
+            })
+    }
+
     /// Fallibly returns a Hugr that may replace an instance of this OpDef
     /// given a set of available extensions that may be used in the Hugr.
     pub fn try_lower(&self, args: &[TypeArg], available_extensions: &ExtensionSet) -> Option<Hugr> {
@@ -394,12 +521,27 @@ impl OpDef {
         self.signature_func.static_params()
     }
 
+    /// Validate the definition against `exts`.
+    ///
+    /// The signature of an [`OpDef`] cannot change once it is created, so its
+    /// validity only depends on the contents of the registry, which
+    /// [`ExtensionRegistry::fingerprint`] identifies. Hence once a definition
+    /// has been validated against a registry, validating it against one with
+    /// the same fingerprint, e.g. a registry rebuilt from the same
+    /// extensions, is skipped. Only the first such fingerprint is remembered:
+    /// validating against registries with any other contents always does the
+    /// full check.
     pub(super) fn validate(&self, exts: &ExtensionRegistry) -> Result<(), SignatureError> {
+        let fingerprint = exts.fingerprint();
+        if self.validated.get() == Some(&fingerprint) {
+            return Ok(());
+        }
         // TODO https://github.com/CQCL/hugr/issues/624 validate declared TypeParams
         // for both type scheme and custom binary
         if let SignatureFunc::TypeScheme(ts) = &self.signature_func {
             ts.poly_func.validate(exts)?;
         }
+        let _ = self.validated.set(fingerprint);
         Ok(())
     }
 
@@ -408,6 +550,12 @@ impl OpDef {
         self.lower_funcs.push(lower);
     }
 
+    /// Whether any lowering functions have been added to the [OpDef], i.e.
+    /// whether [`OpDef::try_lower`] can ever succeed.
+    pub fn has_lower_funcs(&self) -> bool {
+        !self.lower_funcs.is_empty()
+    }
+
     /// Insert miscellaneous data `v` to the [OpDef], keyed by `k`.
     pub fn add_misc(
         &mut self,
@@ -417,12 +565,63 @@ impl OpDef {
         self.misc.insert(k.to_string(), v)
     }
 
+    /// Returns `true` if the two definitions have the same name, description,
+    /// metadata, deprecation and signature.
+    ///
+    /// Signatures computed by custom binaries are only compared by their
+    /// static parameters, and lowerings and constant folders are not compared.
+    pub(super) fn content_eq(&self, other: &Self) -> bool {
+        let same_signature = match (&self.signature_func, &other.signature_func) {
+            (SignatureFunc::TypeScheme(a), SignatureFunc::TypeScheme(b)) => {
+                a.poly_func == b.poly_func
+            }
+            (SignatureFunc::CustomFunc(a), SignatureFunc::CustomFunc(b)) => {
+                a.static_params() == b.static_params()
+            }
+            _ => false,
+        };
+        self.extension == other.extension
+            && self.name == other.name
+            && self.description == other.description
+            && self.misc == other.misc
+            && self.deprecated == other.deprecated
+            && self.capabilities == other.capabilities
+            && same_signature
+    }
+
+    /// Mark the operation as deprecated, giving the `reason` (e.g. what to use
+    /// instead).
+    pub fn set_deprecated(&mut self, reason: impl ToString) {
+        self.deprecated = Some(reason.to_string());
+    }
+
+    /// If the operation is deprecated, the reason why.
+    pub fn deprecation(&self) -> Option<&str> {
+        self.deprecated.as_deref()
+    }
+
+    /// The capabilities of the operation.
+    pub fn capabilities(&self) -> OpCapabilities {
+        self.capabilities
+    }
+
+    /// Add `capabilities` to those of the operation.
+    pub fn add_capabilities(&mut self, capabilities: OpCapabilities) {
+        self.capabilities |= capabilities;
+    }
+
     /// Set the constant folding function for this Op, which can evaluate it
     /// given constant inputs.
     pub fn set_constant_folder(&mut self, fold: impl ConstFold + 'static) {
         self.constant_folder = Some(Box::new(fold))
     }
 
+    /// Whether a constant folding function has been set for this Op. If not,
+    /// [`OpDef::constant_fold`] always returns `None`.
+    pub fn has_const_folder(&self) -> bool {
+        self.constant_folder.is_some()
+    }
+
     /// Evaluate an instance of this [`OpDef`] defined by the `type_args`, given
     /// [`crate::ops::Const`] values for inputs at [`crate::IncomingPort`]s.
     pub fn constant_fold(
@@ -432,6 +631,53 @@ impl OpDef {
     ) -> ConstFoldResult {
         (self.constant_folder.as_ref())?.fold(type_args, consts)
     }
+
+    /// A serializable view of the definition for a [`ValidationFingerprint`].
+    /// Binary functions cannot be serialized, so only their presence (and the
+    /// static parameters of a [`SignatureFunc::CustomFunc`]) is recorded.
+    ///
+    /// [`ValidationFingerprint`]: super::ValidationFingerprint
+    pub(super) fn fingerprint_repr(&self) -> impl serde::Serialize + '_ {
+        let signature = match &self.signature_func {
+            SignatureFunc::TypeScheme(ts) => (Some(&ts.poly_func), None),
+            SignatureFunc::CustomFunc(func) => (None, Some(func.static_params())),
+        };
+        let lower_funcs: Vec<_> = self
+            .lower_funcs
+            .iter()
+            .map(|lower| match lower {
+                LowerFunc::FixedHugr(exts, hugr) => Some((exts, hugr)),
+                LowerFunc::CustomFunc(_) => None,
+            })
+            .collect();
+        (
+            &self.extension,
+            &self.name,
+            &self.description,
+            self.misc.iter().collect::<BTreeMap<_, _>>(),
+            &self.deprecated,
+            self.capabilities,
+            signature,
+            lower_funcs,
+            self.constant_folder.is_some(),
+        )
+    }
+}
+
+/// Returns the nodes of `hugr` that are instances of deprecated operations,
+/// together with their definitions.
+///
+/// Only resolved [`ExtensionOp`]s are checked; opaque operations should be
+/// resolved against a registry first.
+///
+/// [`ExtensionOp`]: crate::ops::custom::ExtensionOp
+pub fn check_deprecations<H: HugrView>(hugr: &H) -> Vec<(Node, &OpDef)> {
+    hugr.nodes()
+        .filter_map(|n| {
+            let def = hugr.get_optype(n).as_custom_op()?.as_extension_op()?.def();
+            def.deprecation().is_some().then_some((n, def))
+        })
+        .collect()
 }
 
 impl Extension {
@@ -451,10 +697,18 @@ impl Extension {
             description,
             signature_func: signature_func.into(),
             misc: Default::default(),
+            deprecated: None,
+            capabilities: Default::default(),
             lower_funcs: Default::default(),
             constant_folder: Default::default(),
+            signature_cache: Default::default(),
+            validated: Default::default(),
         };
 
+        if self.op_aliases.contains_key(&op.name) {
+            return Err(ExtensionBuildError::OpAliasExists(op.name));
+        }
+        self.bump_generation();
         match self.operations.entry(op.name.clone()) {
             Entry::Occupied(_) => Err(ExtensionBuildError::OpDefExists(op.name)),
             // Just made the arc so should only be one reference to it, can get_mut,
@@ -467,18 +721,19 @@ impl Extension {
 mod test {
     use std::num::NonZeroU64;
 
-    use super::SignatureFromArgs;
+    use super::{check_deprecations, OpCapabilities, SignatureFromArgs};
     use crate::builder::{DFGBuilder, Dataflow, DataflowHugr};
     use crate::extension::op_def::LowerFunc;
     use crate::extension::prelude::USIZE_T;
     use crate::extension::{ExtensionRegistry, ExtensionSet, PRELUDE};
     use crate::extension::{SignatureError, EMPTY_REG, PRELUDE_REGISTRY};
+    use crate::ops::handle::NodeHandle;
     use crate::ops::{CustomOp, OpName};
     use crate::std_extensions::collections::{EXTENSION, LIST_TYPENAME};
     use crate::types::Type;
     use crate::types::{type_param::TypeParam, FunctionType, PolyFuncType, TypeArg, TypeBound};
-    use crate::Hugr;
     use crate::{const_extension_ids, Extension};
+    use crate::{type_row, Hugr};
 
     const_extension_ids! {
         const EXT_ID: ExtensionId = "MyExt";
@@ -517,6 +772,223 @@ mod test {
         )?;
         dfg.finish_hugr_with_outputs(rev.outputs(), &reg)?;
 
+        assert_eq!(
+            e.instantiate_extension_op(&OP_NAME, [], &reg),
+            Err(SignatureError::MissingTypeArgs {
+                expected: 1,
+                got: 0
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn op_capabilities() {
+        use crate::ops::OpType;
+        use crate::utils::test_quantum_extension::{h_gate, measure};
+
+        let measure: OpType = measure().into();
+        assert_eq!(measure.capabilities(), OpCapabilities::MEASUREMENT);
+        assert!(!measure.capabilities().contains(OpCapabilities::RESET));
+        assert!(OpType::from(h_gate()).capabilities().is_empty());
+
+        let caps = OpCapabilities::RESET | OpCapabilities::SIDE_EFFECT;
+        assert!(caps.contains(OpCapabilities::RESET));
+        assert!(!caps.contains(OpCapabilities::MEASUREMENT | OpCapabilities::RESET));
+    }
+
+    #[test]
+    fn validation_cache() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::extension::{ExtensionRegistryError, TypeDefBound};
+        use crate::types::CustomType;
+        use cool_asserts::assert_matches;
+
+        const OP: OpName = OpName::new_inline("Op");
+        let make_ext = |bound| {
+            let mut e = Extension::new(EXT_ID);
+            e.add_type("T".into(), vec![], "".into(), TypeDefBound::Explicit(bound))
+                .unwrap();
+            let t = Type::new_extension(CustomType::new("T", [], EXT_ID, TypeBound::Eq));
+            e.add_op(OP, "".into(), FunctionType::new_endo(vec![t]))
+                .unwrap();
+            e
+        };
+        let e = make_ext(TypeBound::Eq);
+        let reg = ExtensionRegistry::try_new([e.clone()])?;
+        let fingerprint = reg.fingerprint();
+        let def = e.get_op(&OP).unwrap();
+        assert_eq!(def.validated.get(), Some(&fingerprint));
+
+        // Rebuilding from the same extensions reuses the result.
+        let rebuilt = ExtensionRegistry::try_new([e.clone()])?;
+        assert_eq!(rebuilt.fingerprint(), fingerprint);
+
+        // Changing the types the op refers to invalidates it.
+        let mut changed = make_ext(TypeBound::Any);
+        changed.operations = e.operations.clone();
+        assert_matches!(
+            ExtensionRegistry::try_new([changed]),
+            Err(ExtensionRegistryError::InvalidSignature(_, _))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn deprecated_op() -> Result<(), Box<dyn std::error::Error>> {
+        const OLD: OpName = OpName::new_inline("Old");
+        const NEW: OpName = OpName::new_inline("New");
+        let mut e = Extension::new(EXT_ID);
+        let sig = FunctionType::new_endo(type_row![USIZE_T]);
+        e.add_op(OLD, "".into(), sig.clone())?
+            .set_deprecated("use New instead");
+        e.add_op(NEW, "".into(), sig.clone())?;
+        assert_eq!(
+            e.get_op(&OLD).unwrap().deprecation(),
+            Some("use New instead")
+        );
+        assert_eq!(e.get_op(&NEW).unwrap().deprecation(), None);
+
+        let reg = ExtensionRegistry::try_new([PRELUDE.to_owned(), e]).unwrap();
+        let e = reg.get(&EXT_ID).unwrap();
+        let mut dfg = DFGBuilder::new(sig)?;
+        let [w] = dfg.input_wires_arr();
+        let new = dfg.add_dataflow_op(
+            CustomOp::new_extension(e.instantiate_extension_op(&NEW, [], &reg)?),
+            [w],
+        )?;
+        let old = dfg.add_dataflow_op(
+            CustomOp::new_extension(e.instantiate_extension_op(&OLD, [], &reg)?),
+            new.outputs(),
+        )?;
+        let h = dfg.finish_hugr_with_outputs(old.outputs(), &reg)?;
+
+        let deprecated = check_deprecations(&h);
+        assert_eq!(deprecated.len(), 1);
+        assert_eq!(deprecated[0].0, old.node());
+        assert_eq!(deprecated[0].1.name(), &OLD);
+        Ok(())
+    }
+
+    #[test]
+    fn signature_cache() -> Result<(), Box<dyn std::error::Error>> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        /// Counts how many times the signature has been computed.
+        struct CountingSig(Arc<AtomicUsize>);
+        impl SignatureFromArgs for CountingSig {
+            fn compute_signature(
+                &self,
+                arg_values: &[TypeArg],
+            ) -> Result<PolyFuncType, SignatureError> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                let [TypeArg::BoundedNat { n }] = arg_values else {
+                    return Err(SignatureError::InvalidTypeArgs);
+                };
+                Ok(FunctionType::new(vec![USIZE_T; *n as usize], type_row![USIZE_T]).into())
+            }
+
+            fn static_params(&self) -> &[TypeParam] {
+                const MAX_NAT: &[TypeParam] = &[TypeParam::max_nat()];
+                MAX_NAT
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut e = Extension::new(EXT_ID);
+        let def = e.add_op("Count".into(), "".to_string(), CountingSig(count.clone()))?;
+
+        let two = [TypeArg::BoundedNat { n: 2 }];
+        let three = [TypeArg::BoundedNat { n: 3 }];
+        let sig = def.compute_signature(&two, &PRELUDE_REGISTRY)?;
+        assert_eq!(def.compute_signature(&two, &PRELUDE_REGISTRY)?, sig);
+        def.validate_args(&two, &PRELUDE_REGISTRY, &[])?;
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        assert_ne!(def.compute_signature(&three, &PRELUDE_REGISTRY)?, sig);
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+
+        // Errors are not cached.
+        let bad = [TypeArg::new_var_use(0, TypeParam::max_nat())];
+        def.compute_signature(&bad, &PRELUDE_REGISTRY).unwrap_err();
+        def.compute_signature(&bad, &PRELUDE_REGISTRY).unwrap_err();
+        assert_eq!(count.load(Ordering::SeqCst), 4);
+
+        // Signatures are recomputed for registries with different contents...
+        let other_reg = ExtensionRegistry::with_prelude([EXTENSION.to_owned()])?;
+        def.compute_signature(&two, &other_reg)?;
+        assert_eq!(count.load(Ordering::SeqCst), 5);
+        let mut prelude = PRELUDE.to_owned();
+        prelude.add_type_alias("Alias", USIZE_T)?;
+        def.compute_signature(&two, &ExtensionRegistry::try_new([prelude])?)?;
+        assert_eq!(count.load(Ordering::SeqCst), 6);
+        let mut prelude = PRELUDE.to_owned();
+        prelude.set_extension_reqs(ExtensionSet::singleton(&EXT_ID));
+        def.compute_signature(&two, &ExtensionRegistry::try_new([prelude])?)?;
+        assert_eq!(count.load(Ordering::SeqCst), 7);
+        // ...but not for copies of the same registry.
+        def.compute_signature(&two, &PRELUDE_REGISTRY.clone())?;
+        def.compute_signature(&two, &ExtensionRegistry::try_new([PRELUDE.to_owned()])?)?;
+        assert_eq!(count.load(Ordering::SeqCst), 7);
+
+        // The oldest entries are evicted once the cache is full.
+        for n in 0..64 {
+            def.compute_signature(&[TypeArg::BoundedNat { n }], &other_reg)?;
+        }
+        let count_before = count.load(Ordering::SeqCst);
+        def.compute_signature(&two, &PRELUDE_REGISTRY)?;
+        assert_eq!(count.load(Ordering::SeqCst), count_before + 1);
+        Ok(())
+    }
+
+    #[test]
+    fn cross_extension_signature() -> Result<(), Box<dyn std::error::Error>> {
+        use super::{CustomSignatureFunc, OpDef};
+        use crate::std_extensions::collections::{self, list_type};
+
+        /// Returns a list, a type from the collections extension.
+        struct MakeList;
+        impl CustomSignatureFunc for MakeList {
+            fn compute_signature<'o, 'a: 'o>(
+                &'a self,
+                _arg_values: &[TypeArg],
+                _def: &'o OpDef,
+                extension_registry: &ExtensionRegistry,
+            ) -> Result<PolyFuncType, SignatureError> {
+                let list_def = extension_registry
+                    .get(&collections::EXTENSION_NAME)
+                    .ok_or(SignatureError::ExtensionNotFound(
+                        collections::EXTENSION_NAME,
+                    ))?
+                    .get_type(&LIST_TYPENAME)
+                    .unwrap();
+                let list = list_def.instantiate([TypeArg::Type { ty: USIZE_T }])?;
+                Ok(FunctionType::new(type_row![], vec![Type::new_extension(list)]).into())
+            }
+
+            fn static_params(&self) -> &[TypeParam] {
+                &[]
+            }
+        }
+
+        let mut e = Extension::new(EXT_ID);
+        e.add_op("MakeList".into(), "".to_string(), MakeList)?;
+        let reg = ExtensionRegistry::try_new([PRELUDE.to_owned(), EXTENSION.to_owned(), e])?;
+        let def = reg.get(&EXT_ID).unwrap().get_op("MakeList").unwrap();
+        assert_eq!(
+            def.compute_signature(&[], &reg)?,
+            FunctionType::new(type_row![], vec![list_type(USIZE_T)])
+        );
+
+        // The signature computed against `reg` is not reused for a registry
+        // without the collections extension.
+        let no_collections = ExtensionRegistry::try_new([PRELUDE.to_owned()])?;
+        assert_eq!(
+            def.compute_signature(&[], &no_collections),
+            Err(SignatureError::ExtensionNotFound(
+                collections::EXTENSION_NAME
+            ))
+        );
         Ok(())
     }
 
@@ -98,6 +98,27 @@ lazy_static! {
             TypeDefBound::Explicit(TypeBound::Eq),
         )
         .unwrap();
+        let copyable = Type::new_var_use(0, TypeBound::Copyable);
+        prelude
+            .add_op(
+                COPY_OP_ID,
+                "Copy a copyable value".to_string(),
+                PolyFuncType::new(
+                    vec![TypeBound::Copyable.into()],
+                    FunctionType::new(vec![copyable.clone()], vec![copyable.clone(); 2]),
+                ),
+            )
+            .unwrap();
+        prelude
+            .add_op(
+                DISCARD_OP_ID,
+                "Discard a copyable value".to_string(),
+                PolyFuncType::new(
+                    vec![TypeBound::Copyable.into()],
+                    FunctionType::new(vec![copyable], type_row![]),
+                ),
+            )
+            .unwrap();
         prelude
         .add_op(
             PANIC_OP_ID,
@@ -127,8 +148,20 @@ pub const QB_T: Type = Type::new_extension(QB_CUSTOM_T);
 /// Unsigned size type.
 pub const USIZE_T: Type = Type::new_extension(USIZE_CUSTOM_T);
 /// Boolean type - Sum of two units.
+///
+/// This is the canonical representation of booleans: `false` is the first
+/// variant (tag 0) and `true` the second (tag 1). Boolean constants should be
+/// built with [`Value::from_bool`] and read back with [`Value::as_bool`].
+///
+/// [`Value::from_bool`]: crate::ops::Value::from_bool
+/// [`Value::as_bool`]: crate::ops::Value::as_bool
 pub const BOOL_T: Type = Type::new_unit_sum(2);
 
+/// The canonical boolean type, see [`BOOL_T`].
+pub fn bool_t() -> Type {
+    BOOL_T
+}
+
 /// Initialize a new array of element type `element_ty` of length `size`
 pub fn array_type(size: TypeArg, element_ty: Type) -> Type {
     let array_def = PRELUDE.get_type("array").unwrap();
@@ -143,6 +176,33 @@ pub const NEW_ARRAY_OP_ID: OpName = OpName::new_inline("new_array");
 /// Name of the prelude panic operation.
 pub const PANIC_OP_ID: OpName = OpName::new_inline("panic");
 
+/// Name of the prelude operation copying a copyable value.
+pub const COPY_OP_ID: OpName = OpName::new_inline("copy");
+/// Name of the prelude operation discarding a copyable value.
+pub const DISCARD_OP_ID: OpName = OpName::new_inline("discard");
+
+/// A copy op for values of type `ty`, with signature `[ty] -> [ty, ty]`.
+///
+/// # Errors
+///
+/// Returns a [`SignatureError`] if `ty` is not copyable.
+pub fn copy_op(ty: Type) -> Result<CustomOp, SignatureError> {
+    Ok(PRELUDE
+        .instantiate_extension_op(&COPY_OP_ID, [TypeArg::Type { ty }], &PRELUDE_REGISTRY)?
+        .into())
+}
+
+/// A discard op for values of type `ty`, with signature `[ty] -> []`.
+///
+/// # Errors
+///
+/// Returns a [`SignatureError`] if `ty` is not copyable.
+pub fn discard_op(ty: Type) -> Result<CustomOp, SignatureError> {
+    Ok(PRELUDE
+        .instantiate_extension_op(&DISCARD_OP_ID, [TypeArg::Type { ty }], &PRELUDE_REGISTRY)?
+        .into())
+}
+
 /// Initialize a new array op of element type `element_ty` of length `size`
 pub fn new_array_op(element_ty: Type, size: u64) -> CustomOp {
     PRELUDE
@@ -357,6 +417,35 @@ mod test {
         b.finish_prelude_hugr_with_outputs([]).unwrap();
     }
 
+    #[test]
+    /// Test copying and discarding values.
+    fn test_copy_discard() {
+        use crate::builder::BuildError;
+        use crate::types::type_param::TypeArgError;
+        use crate::HugrView;
+
+        let mut b = DFGBuilder::new(FunctionType::new(
+            type_row![BOOL_T, QB_T],
+            type_row![BOOL_T, QB_T],
+        ))
+        .unwrap();
+        let [bit, qb] = b.input_wires_arr();
+        let [bit, copy] = b.add_copy(bit).unwrap();
+        b.add_discard(copy).unwrap();
+
+        let not_copyable = BuildError::SignatureError(SignatureError::TypeArgMismatch(
+            TypeArgError::TypeMismatch {
+                param: TypeBound::Copyable.into(),
+                arg: TypeArg::Type { ty: QB_T },
+            },
+        ));
+        assert_eq!(b.add_copy(qb), Err(not_copyable.clone()));
+        assert_eq!(b.add_discard(qb), Err(not_copyable));
+
+        let h = b.finish_prelude_hugr_with_outputs([bit, qb]).unwrap();
+        assert_eq!(h.node_count(), 5);
+    }
+
     #[test]
     /// Test string type.
     fn test_string_type() {
@@ -378,6 +467,29 @@ mod test {
         assert!(!string_const.equal_consts(&ConstString::new("Lorem ispum".into())));
     }
 
+    #[test]
+    /// Test the boolean type and constants round-trip.
+    fn test_bool() {
+        use crate::ops::Value;
+
+        assert_eq!(bool_t(), Type::new_sum([type_row![], type_row![]]));
+        for b in [false, true] {
+            let v = Value::from_bool(b);
+            assert_eq!(v.const_type(), bool_t());
+            assert_eq!(v.as_bool(), Some(b));
+
+            let sum = Value::sum(b as usize, [], SumType::new([type_row![], type_row![]])).unwrap();
+            assert_eq!(sum, v);
+            assert_eq!(sum.as_bool(), Some(b));
+        }
+        assert_eq!(Value::unit_sum(0, 3).unwrap().as_bool(), None);
+        assert_eq!(Value::unit().as_bool(), None);
+
+        let mut b = DFGBuilder::new(FunctionType::new(type_row![], type_row![BOOL_T])).unwrap();
+        let t = b.add_load_value(Value::true_val());
+        b.finish_prelude_hugr_with_outputs([t]).unwrap();
+    }
+
     #[test]
     /// Test print operation
     fn test_print() {
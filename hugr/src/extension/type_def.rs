@@ -1,7 +1,7 @@
 use std::collections::hash_map::Entry;
 
 use super::{CustomConcrete, ExtensionBuildError};
-use super::{Extension, ExtensionId, SignatureError, TypeParametrised};
+use super::{Extension, ExtensionId, ExtensionRegistry, SignatureError, TypeParametrised};
 
 use crate::types::{least_upper_bound, CustomType, TypeName};
 
@@ -12,7 +12,7 @@ use crate::types::type_param::TypeParam;
 use crate::types::TypeBound;
 
 /// The type bound of a [`TypeDef`]
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum TypeDefBound {
     /// Defined by an explicit bound.
     Explicit(TypeBound),
@@ -29,7 +29,7 @@ impl From<TypeBound> for TypeDefBound {
 /// A declaration of an opaque type.
 /// Note this does not provide any way to create instances
 /// - typically these are operations also provided by the Extension.
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct TypeDef {
     /// The unique Extension owning this TypeDef (of which this TypeDef is a member)
     extension: ExtensionId,
@@ -102,6 +102,24 @@ impl TypeDef {
             bound,
         ))
     }
+    /// Checks that the declared parameters are well-formed and that a bound
+    /// computed from parameters only refers to type parameters.
+    pub(super) fn validate(&self, exts: &ExtensionRegistry) -> Result<(), SignatureError> {
+        self.params.iter().try_for_each(|p| p.validate(exts))?;
+        if let TypeDefBound::FromParams(indices) = &self.bound {
+            if let Some(&idx) = indices
+                .iter()
+                .find(|&&i| !matches!(self.params.get(i), Some(TypeParam::Type { .. })))
+            {
+                return Err(SignatureError::InvalidBoundParam {
+                    typ: self.name.clone(),
+                    idx,
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// The [`TypeBound`] of the definition.
     pub fn bound(&self, args: &[TypeArg]) -> TypeBound {
         match &self.bound {
@@ -156,6 +174,10 @@ impl Extension {
             description,
             bound,
         };
+        if self.type_aliases.contains_key(&ty.name) {
+            return Err(ExtensionBuildError::TypeAliasExists(ty.name));
+        }
+        self.bump_generation();
         match self.types.entry(ty.name.clone()) {
             Entry::Occupied(_) => Err(ExtensionBuildError::TypeDefExists(ty.name)),
             Entry::Vacant(ve) => Ok(ve.insert(ty)),
@@ -165,11 +187,14 @@ impl Extension {
 
 #[cfg(test)]
 mod test {
+    use std::num::NonZeroU64;
+
     use crate::extension::prelude::{QB_T, USIZE_T};
-    use crate::extension::SignatureError;
+    use crate::extension::{ExtensionRegistry, ExtensionRegistryError, SignatureError};
     use crate::std_extensions::arithmetic::float_types::FLOAT64_TYPE;
     use crate::types::type_param::{TypeArg, TypeArgError, TypeParam};
     use crate::types::{FunctionType, Type, TypeBound};
+    use crate::Extension;
 
     use super::{TypeDef, TypeDefBound};
 
@@ -207,7 +232,10 @@ mod test {
         // Too few arguments:
         assert_eq!(
             def.instantiate([]).unwrap_err(),
-            SignatureError::TypeArgMismatch(TypeArgError::WrongNumberArgs(0, 1))
+            SignatureError::MissingTypeArgs {
+                expected: 1,
+                got: 0
+            }
         );
         // Too many arguments:
         assert_eq!(
@@ -216,7 +244,49 @@ mod test {
                 TypeArg::Type { ty: FLOAT64_TYPE },
             ])
             .unwrap_err(),
-            SignatureError::TypeArgMismatch(TypeArgError::WrongNumberArgs(2, 1))
+            SignatureError::MissingTypeArgs {
+                expected: 1,
+                got: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_typedef() {
+        let empty_param = TypeParam::bounded_nat_range(5, NonZeroU64::new(3));
+        let mut e = Extension::new("MyRsrc".try_into().unwrap());
+        e.add_type(
+            "EmptyNat".into(),
+            vec![empty_param.clone()],
+            "".into(),
+            TypeBound::Eq.into(),
+        )
+        .unwrap();
+        assert_eq!(
+            ExtensionRegistry::try_new([e]).unwrap_err(),
+            ExtensionRegistryError::InvalidSignature(
+                "MyRsrc".try_into().unwrap(),
+                SignatureError::EmptyTypeParam(empty_param)
+            )
+        );
+
+        let mut e = Extension::new("MyRsrc".try_into().unwrap());
+        e.add_type(
+            "NatBound".into(),
+            vec![TypeParam::max_nat(), TypeBound::Any.into()],
+            "".into(),
+            TypeDefBound::FromParams(vec![1, 0]),
+        )
+        .unwrap();
+        assert_eq!(
+            ExtensionRegistry::try_new([e]).unwrap_err(),
+            ExtensionRegistryError::InvalidSignature(
+                "MyRsrc".try_into().unwrap(),
+                SignatureError::InvalidBoundParam {
+                    typ: "NatBound".into(),
+                    idx: 0
+                }
+            )
         );
     }
 }
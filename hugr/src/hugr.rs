@@ -8,14 +8,14 @@ pub mod serialize;
 pub mod validate;
 pub mod views;
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::iter;
 
 pub(crate) use self::hugrmut::HugrMut;
-pub use self::validate::ValidationError;
+pub use self::validate::{check_linearity, check_monomorphic, LinearityError, ValidationError};
 
 pub use ident::{IdentList, InvalidIdentifier};
-pub use rewrite::{Rewrite, SimpleReplacement, SimpleReplacementError};
+pub use rewrite::{flatten_dfg, Rewrite, SimpleReplacement, SimpleReplacementError};
 
 use portgraph::multiportgraph::MultiPortGraph;
 use portgraph::{Hierarchy, PortMut, UnmanagedDenseMap};
@@ -28,13 +28,17 @@ use crate::extension::infer_extensions;
 use crate::extension::{ExtensionRegistry, ExtensionSet, ExtensionSolution, InferExtensionError};
 use crate::ops::custom::resolve_extension_ops;
 use crate::ops::{OpTag, OpTrait, OpType, DEFAULT_OPTYPE};
-use crate::types::FunctionType;
+use crate::types::{EdgeKind, FunctionType};
 use crate::{Direction, Node};
 
 use delegate::delegate;
 
 /// The Hugr data structure.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Two hugrs compare equal if they have the same structure and operations.
+/// Node metadata is not taken into account, see the [`PartialEq`]
+/// implementation.
+#[derive(Clone, Debug)]
 pub struct Hugr {
     /// The graph encoding the adjacency structure of the HUGR.
     graph: MultiPortGraph,
@@ -168,6 +172,20 @@ impl Default for Hugr {
     }
 }
 
+/// Compares the graph, hierarchy, root and node types of two hugrs.
+///
+/// Node metadata is deliberately excluded: passes attach annotations to
+/// nodes, which must not make otherwise identical hugrs unequal. Compare
+/// [`HugrView::get_node_metadata`] for each node to take it into account.
+impl PartialEq for Hugr {
+    fn eq(&self, other: &Self) -> bool {
+        self.graph == other.graph
+            && self.hierarchy == other.hierarchy
+            && self.root == other.root
+            && self.op_types == other.op_types
+    }
+}
+
 impl AsRef<Hugr> for Hugr {
     fn as_ref(&self) -> &Hugr {
         self
@@ -314,6 +332,7 @@ impl Hugr {
                 let pg_source = source.pg_index();
                 self.graph.swap_nodes(pg_target, pg_source);
                 self.op_types.swap(pg_target, pg_source);
+                self.metadata.swap(pg_target, pg_source);
                 self.hierarchy.swap_nodes(pg_target, pg_source);
                 rekey(source, target);
             }
@@ -325,6 +344,48 @@ impl Hugr {
         // This step is not strictly necessary.
         self.graph.compact_nodes(|_, _| {});
     }
+
+    /// Renumber the nodes of the hugr into the contiguous range
+    /// `0..node_count()`, as in [`Hugr::canonicalize_nodes`].
+    ///
+    /// Returns the mapping from the old node indices to the new ones, which
+    /// can be used to update any nodes (e.g. in a
+    /// [`views::SiblingSubgraph`]) held outside of the hugr.
+    pub fn compact(&mut self) -> HashMap<Node, Node> {
+        let mapping = self
+            .canonical_order(self.root())
+            .enumerate()
+            .map(|(position, node)| (node, portgraph::NodeIndex::new(position).into()))
+            .collect();
+        self.canonicalize_nodes(|_, _| {});
+        mapping
+    }
+}
+
+/// The extensions required to run `hugr`.
+///
+/// This is the union of the extension requirements of every node's signature
+/// (and of the functions it defines), the extensions of the operations it
+/// uses, and the extensions defining the custom types that appear in it. It
+/// can be used to prune an [ExtensionRegistry] to only what a hugr needs.
+pub fn required_extensions(hugr: &impl HugrView) -> ExtensionSet {
+    let mut exts = ExtensionSet::new();
+    for node in hugr.nodes() {
+        let op = hugr.get_optype(node);
+        if let Some(sig) = op.dataflow_signature() {
+            exts = exts.union(sig.extension_reqs);
+        }
+        if let Some(EdgeKind::Function(poly)) = op.static_output() {
+            exts = exts.union(poly.body().extension_reqs.clone());
+        }
+        if let OpType::CustomOp(custom) = op {
+            exts.insert(custom.extension());
+        }
+    }
+    for custom in hugr.custom_types() {
+        exts.insert(custom.extension());
+    }
+    exts
 }
 
 /// Errors that can occur while manipulating a Hugr.
@@ -358,6 +419,33 @@ mod test {
         impl Test for Hugr {}
     }
 
+    #[test]
+    fn required_extensions() {
+        use crate::builder::{DFGBuilder, Dataflow, DataflowHugr};
+        use crate::extension::prelude::{BOOL_T, PRELUDE_ID, USIZE_T};
+        use crate::extension::{ExtensionRegistry, ExtensionSet, PRELUDE};
+        use crate::std_extensions::logic::{self, NotOp};
+        use crate::types::FunctionType;
+
+        let reg =
+            ExtensionRegistry::try_new([PRELUDE.to_owned(), logic::EXTENSION.to_owned()]).unwrap();
+        let mut dfg = DFGBuilder::new(FunctionType::new(
+            vec![BOOL_T, USIZE_T],
+            vec![BOOL_T, USIZE_T],
+        ))
+        .unwrap();
+        let [b, n] = dfg.input_wires_arr();
+        let not = dfg.add_dataflow_op(NotOp, [b]).unwrap();
+        let h = dfg
+            .finish_hugr_with_outputs([not.out_wire(0), n], &reg)
+            .unwrap();
+
+        assert_eq!(
+            super::required_extensions(&h),
+            ExtensionSet::from_iter([PRELUDE_ID, logic::EXTENSION_ID])
+        );
+    }
+
     #[test]
     fn io_node() {
         use crate::builder::test::simple_dfg_hugr;
@@ -367,6 +455,66 @@ mod test {
         assert_matches!(hugr.get_io(hugr.root()), Some(_));
     }
 
+    #[test]
+    fn metadata() {
+        use crate::builder::test::simple_dfg_hugr;
+        use crate::extension::PRELUDE_REGISTRY;
+        use crate::hugr::HugrMut;
+        use serde_json::json;
+
+        let hugr = simple_dfg_hugr();
+        let mut annotated = hugr.clone();
+        let [input, _] = annotated.get_io(annotated.root()).unwrap();
+        annotated.set_metadata(input, "visited", true);
+        assert_eq!(annotated.get_metadata(input, "visited"), Some(&json!(true)));
+
+        // Metadata is ignored by equality and validation.
+        assert_eq!(annotated, hugr);
+        annotated.validate(&PRELUDE_REGISTRY).unwrap();
+
+        let ser = serde_json::to_string(&annotated).unwrap();
+        let deser: Hugr = serde_json::from_str(&ser).unwrap();
+        let [input, _] = deser.get_io(deser.root()).unwrap();
+        assert_eq!(deser.get_metadata(input, "visited"), Some(&json!(true)));
+    }
+
+    #[test]
+    fn compact() {
+        use crate::builder::ModuleBuilder;
+        use crate::builder::{Container, Dataflow, DataflowSubContainer, HugrBuilder};
+        use crate::core::NodeIndex;
+        use crate::extension::{prelude::BOOL_T, PRELUDE_REGISTRY};
+        use crate::hugr::HugrMut;
+        use crate::ops::handle::{FuncID, NodeHandle};
+        use crate::{type_row, types::FunctionType};
+
+        let mut module = ModuleBuilder::new();
+        let sig = FunctionType::new_endo(type_row![BOOL_T]);
+        let decls: Vec<FuncID<false>> = (0..8)
+            .map(|i| module.declare(format!("f{i}"), sig.clone().into()).unwrap())
+            .collect();
+        let main = module.define_function("main", sig.into()).unwrap();
+        let inputs = main.input_wires();
+        let main = main.finish_with_outputs(inputs).unwrap().node();
+        let mut hugr = module.finish_prelude_hugr().unwrap();
+
+        // Delete half of the declarations, leaving gaps in the indices.
+        for decl in decls.iter().step_by(2) {
+            hugr.remove_node(decl.node());
+        }
+        let node_count = hugr.node_count();
+        assert!(hugr.nodes().map(|n| n.index()).max() >= Some(node_count));
+
+        let mapping = hugr.compact();
+        assert_eq!(
+            hugr.nodes().map(|n| n.index()).collect::<Vec<_>>(),
+            (0..node_count).collect::<Vec<_>>()
+        );
+        assert_eq!(mapping.len(), node_count);
+        assert!(hugr.get_optype(mapping[&main]).is_func_defn());
+        hugr.validate(&PRELUDE_REGISTRY).unwrap();
+    }
+
     #[cfg(feature = "extension_inference")]
     #[test]
     fn extension_instantiation() -> Result<(), Box<dyn Error>> {
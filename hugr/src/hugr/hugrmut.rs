@@ -6,10 +6,15 @@ use std::ops::Range;
 
 use portgraph::view::{NodeFilter, NodeFiltered};
 use portgraph::{LinkMut, NodeIndex, PortMut, PortView, SecondaryMap};
+use thiserror::Error;
 
+use crate::hugr::rewrite::{simple_replace, BatchRewriteError, SimpleReplacement};
 use crate::hugr::views::SiblingSubgraph;
 use crate::hugr::{Direction, HugrError, HugrView, Node, NodeType, RootTagged};
 use crate::hugr::{NodeMetadata, Rewrite};
+use crate::ops::handle::NodeHandle;
+use crate::ops::{NamedOp, OpName, OpTag, OpTrait, OpType};
+use crate::types::EdgeKind;
 use crate::{Hugr, IncomingPort, OutgoingPort, Port, PortIndex};
 
 use self::sealed::HugrMutInternals;
@@ -136,6 +141,44 @@ pub trait HugrMut: HugrMutInternals {
         self.hugr_mut().connect(src, src_port, dst, dst_port);
     }
 
+    /// Connect two nodes at the given ports, checking that the kinds of the
+    /// ports agree.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConnectError::TypeMismatch`] if the source port's
+    /// [`EdgeKind`] differs from the target port's, or
+    /// [`ConnectError::NoEdgeKind`] if either port is out of range or does not
+    /// carry an edge. The hugr is unchanged if an error is returned.
+    ///
+    /// # Panics
+    ///
+    /// If either node is not in the graph.
+    fn connect_checked(
+        &mut self,
+        src: Node,
+        src_port: impl Into<OutgoingPort>,
+        dst: Node,
+        dst_port: impl Into<IncomingPort>,
+    ) -> Result<(), ConnectError> {
+        panic_invalid_node(self, src);
+        panic_invalid_node(self, dst);
+        let (src_port, dst_port) = (src_port.into(), dst_port.into());
+        let kind = |node: Node, port: Port| {
+            Some(port)
+                .filter(|p| p.index() < self.num_ports(node, p.direction()))
+                .and_then(|p| self.get_optype(node).port_kind(p))
+                .ok_or(ConnectError::NoEdgeKind { node, port })
+        };
+        let actual = kind(src, src_port.into())?;
+        let expected = kind(dst, dst_port.into())?;
+        if expected != actual {
+            return Err(ConnectError::TypeMismatch { expected, actual });
+        }
+        self.connect(src, src_port, dst, dst_port);
+        Ok(())
+    }
+
     /// Disconnects all edges from the given port.
     ///
     /// The port is left in place.
@@ -219,6 +262,85 @@ pub trait HugrMut: HugrMutInternals {
     {
         rw.apply(self)
     }
+
+    /// Applies a batch of [`SimpleReplacement`]s whose subgraphs are pairwise
+    /// disjoint.
+    ///
+    /// The replacements are applied in order. Wires from one subgraph into
+    /// another are redirected as the replacements are applied, so node
+    /// references in later replacements remain valid.
+    ///
+    /// Returns [`BatchRewriteError::Overlap`] if two subgraphs share a node,
+    /// and [`BatchRewriteError::UnmappedWire`] if a wire between two
+    /// subgraphs is dropped by the replacement it leaves. All the
+    /// replacements are checked before any is applied, so the hugr is
+    /// unchanged if an error is returned.
+    fn apply_rewrites(&mut self, rewrites: Vec<SimpleReplacement>) -> Result<(), BatchRewriteError>
+    where
+        Self: Sized,
+    {
+        simple_replace::apply_batch(self, rewrites)
+    }
+
+    /// Replaces the operations of nodes in place.
+    ///
+    /// `f` is called on every node with its current operation; where it
+    /// returns `Some(op)`, the node's operation is replaced by `op`. The input
+    /// extensions of the node are kept. This is much cheaper than a
+    /// [`SimpleReplacement`] per node, but only allows replacements with the
+    /// same ports as the original operation.
+    ///
+    /// All the replacements are checked before any is applied, so the hugr is
+    /// unchanged if an error is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MapOpsError::SignatureMismatch`] if a replacement's ports
+    /// differ from those of the original operation, or
+    /// [`MapOpsError::InvalidTag`] if a replacement of the root would break the
+    /// bound ([`RootTagged::RootHandle`]) on the root node's [`OpTag`].
+    ///
+    /// [`OpTag`]: crate::ops::OpTag
+    fn map_ops(
+        &mut self,
+        mut f: impl FnMut(Node, &OpType) -> Option<OpType>,
+    ) -> Result<(), MapOpsError>
+    where
+        Self: Sized,
+    {
+        let mut replacements = Vec::new();
+        for node in self.nodes() {
+            let old = self.get_optype(node);
+            let Some(new) = f(node, old) else {
+                continue;
+            };
+            let same_ports = old.dataflow_signature() == new.dataflow_signature()
+                && Direction::BOTH.iter().all(|&dir| {
+                    old.static_port_kind(dir) == new.static_port_kind(dir)
+                        && old.other_port_kind(dir) == new.other_port_kind(dir)
+                });
+            if !same_ports {
+                return Err(MapOpsError::SignatureMismatch {
+                    node,
+                    old: old.name(),
+                    new: new.name(),
+                });
+            }
+            if node == self.root() && !Self::RootHandle::TAG.is_superset(new.tag()) {
+                return Err(MapOpsError::InvalidTag {
+                    required: Self::RootHandle::TAG,
+                    actual: new.tag(),
+                });
+            }
+            replacements.push((node, new));
+        }
+        for (node, op) in replacements {
+            let input_extensions = self.get_nodetype(node).input_extensions().cloned();
+            self.replace_op(node, NodeType::new(op, input_extensions))
+                .expect("The root tag has been checked.");
+        }
+        Ok(())
+    }
 }
 
 /// Records the result of inserting a Hugr or view
@@ -233,6 +355,44 @@ pub struct InsertionResult {
     pub node_map: HashMap<Node, Node>,
 }
 
+/// Error from [`HugrMut::connect_checked`].
+#[derive(Debug, Clone, PartialEq, Error)]
+#[non_exhaustive]
+pub enum ConnectError {
+    /// The kind of the source port differs from that of the target port.
+    #[error("Cannot connect a port of kind {actual:?} to a port of kind {expected:?}.")]
+    TypeMismatch {
+        /// The kind of the target port.
+        expected: EdgeKind,
+        /// The kind of the source port.
+        actual: EdgeKind,
+    },
+    /// A port does not carry an edge.
+    #[error("Port {port} of node {node} does not carry an edge.")]
+    #[allow(missing_docs)]
+    NoEdgeKind { node: Node, port: Port },
+}
+
+/// Error from [`HugrMut::map_ops`].
+#[derive(Debug, Clone, PartialEq, Error)]
+#[non_exhaustive]
+pub enum MapOpsError {
+    /// A replacement operation does not have the same ports as the original.
+    #[error(
+        "Cannot replace the operation {old} of node {node} by {new}, which has different ports."
+    )]
+    #[allow(missing_docs)]
+    SignatureMismatch {
+        node: Node,
+        old: OpName,
+        new: OpName,
+    },
+    /// The replacement of the root does not satisfy the root's tag bound.
+    #[error("Invalid tag for the root: required a tag in {required} but found {actual}")]
+    #[allow(missing_docs)]
+    InvalidTag { required: OpTag, actual: OpTag },
+}
+
 fn translate_indices(node_map: HashMap<NodeIndex, NodeIndex>) -> HashMap<Node, Node> {
     HashMap::from_iter(node_map.into_iter().map(|(k, v)| (k.into(), v.into())))
 }
@@ -503,7 +663,6 @@ fn panic_invalid_port<H: HugrView + ?Sized>(hugr: &H, node: Node, port: impl Int
 
 pub(crate) mod sealed {
     use super::*;
-    use crate::ops::handle::NodeHandle;
 
     /// Trait for accessing the mutable internals of a Hugr(Mut).
     ///
@@ -673,7 +832,7 @@ pub(crate) mod sealed {
 #[cfg(test)]
 mod test {
     use crate::{
-        extension::prelude::USIZE_T,
+        extension::prelude::{BOOL_T, USIZE_T},
         extension::PRELUDE_REGISTRY,
         macros::type_row,
         ops::{self, dataflow::IOTrait, Noop},
@@ -715,4 +874,78 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn connect_checked() {
+        let mut hugr = Hugr::default();
+        let module = hugr.root();
+        let f = hugr.add_node_with_parent(
+            module,
+            ops::FuncDefn {
+                name: "main".into(),
+                signature: FunctionType::new(type_row![NAT], type_row![BOOL_T]).into(),
+            },
+        );
+        let f_in = hugr.add_node_with_parent(f, ops::Input::new(type_row![NAT]));
+        let f_out = hugr.add_node_with_parent(f, ops::Output::new(type_row![BOOL_T]));
+        let noop = hugr.add_node_with_parent(f, Noop { ty: NAT });
+
+        assert_eq!(hugr.connect_checked(f_in, 0, noop, 0), Ok(()));
+        assert_eq!(
+            hugr.connect_checked(noop, 0, f_out, 0),
+            Err(ConnectError::TypeMismatch {
+                expected: EdgeKind::Value(BOOL_T),
+                actual: EdgeKind::Value(NAT),
+            })
+        );
+        // The order port of the noop cannot feed a value port.
+        assert_eq!(
+            hugr.connect_checked(noop, 1, f_out, 0),
+            Err(ConnectError::TypeMismatch {
+                expected: EdgeKind::Value(BOOL_T),
+                actual: EdgeKind::StateOrder,
+            })
+        );
+        // Out-of-range ports are rejected rather than panicking.
+        assert_eq!(
+            hugr.connect_checked(noop, 2, f_out, 0),
+            Err(ConnectError::NoEdgeKind {
+                node: noop,
+                port: OutgoingPort::from(2).into(),
+            })
+        );
+        assert_eq!(hugr.all_linked_inputs(noop).count(), 0);
+    }
+
+    #[test]
+    fn map_ops() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::builder::{DFGBuilder, Dataflow, DataflowHugr};
+        use crate::std_extensions::logic::test::{and_op, or_op};
+        use crate::std_extensions::logic::{NaryLogic, LOGIC_REG};
+
+        let mut builder = DFGBuilder::new(FunctionType::new(
+            type_row![BOOL_T, BOOL_T, BOOL_T],
+            type_row![BOOL_T],
+        ))?;
+        let [a, b, c] = builder.input_wires_arr();
+        let [ab] = builder.add_dataflow_op(and_op(), [a, b])?.outputs_arr();
+        let [abc] = builder.add_dataflow_op(and_op(), [ab, c])?.outputs_arr();
+        let mut hugr = builder.finish_hugr_with_outputs([abc], &LOGIC_REG)?;
+
+        let (and, or): (OpType, OpType) = (and_op().into(), or_op().into());
+        let count =
+            |hugr: &Hugr, op: &OpType| hugr.nodes().filter(|&n| hugr.get_optype(n) == op).count();
+
+        // An op with more inputs cannot replace a binary one.
+        let and3: OpType = NaryLogic::And.with_n_inputs(3).into();
+        let res = hugr.map_ops(|_, op| (op == &and).then(|| and3.clone()));
+        assert!(matches!(res, Err(MapOpsError::SignatureMismatch { .. })));
+        assert_eq!(count(&hugr, &and), 2);
+
+        hugr.map_ops(|_, op| (op == &and).then(|| or.clone()))?;
+        assert_eq!(count(&hugr, &and), 0);
+        assert_eq!(count(&hugr, &or), 2);
+        hugr.validate(&LOGIC_REG)?;
+        Ok(())
+    }
 }
@@ -1,5 +1,6 @@
 //! Rewrite operations on the HUGR - replacement, outlining, etc.
 
+pub mod commute_nodes;
 pub mod consts;
 pub mod inline_dfg;
 pub mod insert_identity;
@@ -8,7 +9,9 @@ pub mod replace;
 pub mod simple_replace;
 
 use crate::{Hugr, HugrView, Node};
-pub use simple_replace::{SimpleReplacement, SimpleReplacementError};
+pub use commute_nodes::{CommuteNodes, CommuteNodesError};
+pub use inline_dfg::flatten_dfg;
+pub use simple_replace::{BatchRewriteError, SimpleReplacement, SimpleReplacementError};
 
 use super::HugrMut;
 
@@ -0,0 +1,268 @@
+//! Rewrite swapping the order of two adjacent, independent dataflow nodes.
+
+use std::collections::{HashSet, VecDeque};
+
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::hugr::{HugrMut, Node};
+use crate::ops::{OpTag, OpTrait};
+use crate::HugrView;
+
+use super::Rewrite;
+
+/// Swaps the execution order of two sibling dataflow nodes.
+///
+/// Node `a` must be ordered before node `b` by an order edge, and `b` must not
+/// otherwise depend on `a`. Applying the rewrite replaces the order edges from
+/// `a` to `b` with a single order edge from `b` to `a`. The order predecessors
+/// of `a` become order predecessors of `b`, and the order successors of `b`
+/// become order successors of `a`, so that all other orderings are preserved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommuteNodes {
+    /// The node currently ordered first.
+    pub a: Node,
+    /// The node currently ordered second.
+    pub b: Node,
+}
+
+impl CommuteNodes {
+    /// Create a new [`CommuteNodes`] specification.
+    pub fn new(a: Node, b: Node) -> Self {
+        Self { a, b }
+    }
+}
+
+/// Error from a [`CommuteNodes`] rewrite.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CommuteNodesError {
+    /// The node is not a dataflow operation that can be reordered.
+    #[error("Node {0} is not a dataflow operation.")]
+    NotDataflowOp(Node),
+    /// The nodes do not share a parent.
+    #[error("Nodes {0} and {1} are not siblings.")]
+    NotSiblings(Node, Node),
+    /// There is no order edge between the nodes.
+    #[error("There is no order edge from {0} to {1}.")]
+    NotAdjacent(Node, Node),
+    /// The second node depends on the outputs of the first.
+    #[error("Node {1} depends on node {0}, so they cannot be commuted.")]
+    NotCommutable(Node, Node),
+}
+
+impl Rewrite for CommuteNodes {
+    type Error = CommuteNodesError;
+    type ApplyResult = ();
+    const UNCHANGED_ON_FAILURE: bool = true;
+
+    fn verify(&self, h: &impl HugrView) -> Result<(), CommuteNodesError> {
+        let Self { a, b } = *self;
+        for n in [a, b] {
+            let tag = h.get_optype(n).tag();
+            if !h.valid_non_root(n)
+                || !OpTag::DataflowChild.is_superset(tag)
+                || matches!(tag, OpTag::Input | OpTag::Output)
+            {
+                return Err(CommuteNodesError::NotDataflowOp(n));
+            }
+        }
+        let parent = h.get_parent(a).unwrap();
+        if h.get_parent(b) != Some(parent) {
+            return Err(CommuteNodesError::NotSiblings(a, b));
+        }
+
+        let order_port = h.get_optype(a).other_output_port().unwrap();
+        if !h.linked_inputs(a, order_port).any(|(n, _)| n == b) {
+            return Err(CommuteNodesError::NotAdjacent(a, b));
+        }
+
+        // Look for any path from `a` to `b` other than the order edges.
+        let mut queue: VecDeque<Node> = h
+            .node_outputs(a)
+            .flat_map(|p| h.linked_inputs(a, p).map(move |(n, _)| (p, n)))
+            .filter(|&(p, n)| p != order_port || n != b)
+            .filter_map(|(_, n)| sibling_ancestor(h, n, parent))
+            .collect();
+        let mut visited: HashSet<Node> = queue.iter().copied().collect();
+        while let Some(n) = queue.pop_front() {
+            if n == b {
+                return Err(CommuteNodesError::NotCommutable(a, b));
+            }
+            for succ in h.output_neighbours(n) {
+                if let Some(succ) = sibling_ancestor(h, succ, parent) {
+                    if visited.insert(succ) {
+                        queue.push_back(succ);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(self, h: &mut impl HugrMut) -> Result<Self::ApplyResult, CommuteNodesError> {
+        self.verify(h)?;
+        let Self { a, b } = self;
+        let [a_in, b_in] = [a, b].map(|n| h.get_optype(n).other_input_port().unwrap());
+        let [a_out, b_out] = [a, b].map(|n| h.get_optype(n).other_output_port().unwrap());
+        let preds: Vec<_> = h
+            .linked_outputs(b, b_in)
+            .filter(|&(n, _)| n != a)
+            .chain(h.linked_outputs(a, a_in))
+            .unique()
+            .collect();
+        let succs: Vec<_> = h
+            .linked_inputs(a, a_out)
+            .filter(|&(n, _)| n != b)
+            .chain(h.linked_inputs(b, b_out))
+            .unique()
+            .collect();
+        h.disconnect(a, a_in);
+        h.disconnect(a, a_out);
+        h.disconnect(b, b_in);
+        h.disconnect(b, b_out);
+        for (pred, pred_port) in preds {
+            h.connect(pred, pred_port, b, b_in);
+        }
+        for (succ, succ_port) in succs {
+            h.connect(a, a_out, succ, succ_port);
+        }
+        h.add_other_edge(b, a);
+        Ok(())
+    }
+
+    #[inline]
+    fn invalidation_set(&self) -> impl Iterator<Item = Node> {
+        [self.a, self.b].into_iter()
+    }
+}
+
+/// Returns the ancestor of `node` that is a child of `parent`, if any.
+fn sibling_ancestor(h: &impl HugrView, mut node: Node, parent: Node) -> Option<Node> {
+    loop {
+        let node_parent = h.get_parent(node)?;
+        if node_parent == parent {
+            return Some(node);
+        }
+        node = node_parent;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{BuildError, Container, DFGBuilder, Dataflow, DataflowHugr};
+    use crate::extension::prelude::QB_T;
+    use crate::ops::handle::NodeHandle;
+    use crate::types::FunctionType;
+    use crate::utils::test_quantum_extension::{self, cx_gate, h_gate};
+    use crate::{type_row, Hugr};
+
+    /// A circuit applying a CX and then a H gate to each qubit, with an order
+    /// edge between the two H gates.
+    ///
+    /// Returns the hugr, the CX node and the two H nodes.
+    fn build_circuit() -> Result<(Hugr, [Node; 3]), BuildError> {
+        let mut dfg = DFGBuilder::new(FunctionType::new_endo(type_row![QB_T, QB_T]))?;
+        let [q0, q1] = dfg.input_wires_arr();
+        let cx = dfg.add_dataflow_op(cx_gate(), [q0, q1])?;
+        let [q0, q1] = cx.outputs_arr();
+        let h0 = dfg.add_dataflow_op(h_gate(), [q0])?;
+        let h1 = dfg.add_dataflow_op(h_gate(), [q1])?;
+        dfg.add_other_wire(h0.node(), h1.node());
+        let hugr = dfg.finish_hugr_with_outputs(
+            h0.outputs().chain(h1.outputs()),
+            &test_quantum_extension::REG,
+        )?;
+        Ok((hugr, [cx.node(), h0.node(), h1.node()]))
+    }
+
+    #[test]
+    fn commute_independent() {
+        let (mut h, [_, h0, h1]) = build_circuit().unwrap();
+        h.apply_rewrite(CommuteNodes::new(h0, h1)).unwrap();
+
+        assert!(h.output_neighbours(h0).all(|n| n != h1));
+        assert!(h.output_neighbours(h1).any(|n| n == h0));
+        h.validate(&test_quantum_extension::REG).unwrap();
+
+        // And back again.
+        h.apply_rewrite(CommuteNodes::new(h1, h0)).unwrap();
+        assert!(h.output_neighbours(h0).any(|n| n == h1));
+        h.validate(&test_quantum_extension::REG).unwrap();
+    }
+
+    /// Returns `true` if there is a path of edges from `from` to `to`.
+    fn reaches(h: &Hugr, from: Node, to: Node) -> bool {
+        let mut queue = vec![from];
+        let mut visited = HashSet::new();
+        while let Some(n) = queue.pop() {
+            if n == to {
+                return true;
+            }
+            queue.extend(h.output_neighbours(n).filter(|&n| visited.insert(n)));
+        }
+        false
+    }
+
+    #[test]
+    fn commute_keeps_other_orderings() {
+        // Six independent H gates with order edges p -> a -> b -> s, r -> b
+        // and a -> t.
+        let mut dfg = DFGBuilder::new(FunctionType::new_endo(vec![QB_T; 6])).unwrap();
+        let gates = dfg
+            .input_wires()
+            .map(|q| dfg.add_dataflow_op(h_gate(), [q]).unwrap())
+            .collect_vec();
+        let nodes = gates.iter().map(|g| g.node()).collect_vec();
+        let [p, a, b, s, r, t] = nodes[..] else {
+            unreachable!()
+        };
+        for (from, to) in [(p, a), (a, b), (b, s), (r, b), (a, t)] {
+            dfg.add_other_wire(from, to);
+        }
+        let outputs = gates.iter().flat_map(|g| g.outputs()).collect_vec();
+        let mut h = dfg
+            .finish_hugr_with_outputs(outputs, &test_quantum_extension::REG)
+            .unwrap();
+
+        let orderings = nodes
+            .iter()
+            .cartesian_product(&nodes)
+            .filter(|&(&x, &y)| x != y && (x, y) != (a, b) && reaches(&h, x, y))
+            .collect_vec();
+        assert_eq!(orderings.len(), 9);
+
+        h.apply_rewrite(CommuteNodes::new(a, b)).unwrap();
+        h.validate(&test_quantum_extension::REG).unwrap();
+        assert!(reaches(&h, b, a));
+        assert!(!reaches(&h, a, b));
+        for (&x, &y) in orderings {
+            assert!(reaches(&h, x, y), "lost ordering {x} -> {y}");
+        }
+    }
+
+    #[test]
+    fn commute_errors() {
+        let (mut h, [cx, h0, h1]) = build_circuit().unwrap();
+        let input = h.get_io(h.root()).unwrap()[0];
+
+        assert_eq!(
+            h.apply_rewrite(CommuteNodes::new(input, cx)),
+            Err(CommuteNodesError::NotDataflowOp(input))
+        );
+        assert_eq!(
+            h.apply_rewrite(CommuteNodes::new(h1, h0)),
+            Err(CommuteNodesError::NotAdjacent(h1, h0))
+        );
+
+        // The H gates consume the outputs of the CX.
+        h.add_other_edge(cx, h1);
+        let backup = h.clone();
+        assert_eq!(
+            h.apply_rewrite(CommuteNodes::new(cx, h1)),
+            Err(CommuteNodesError::NotCommutable(cx, h1))
+        );
+        assert_eq!(h, backup);
+    }
+}
@@ -3,7 +3,10 @@
 //! and deleting the DFG along with its Input + Output
 
 use super::Rewrite;
+use crate::extension::ExtensionSet;
+use crate::hugr::HugrMut;
 use crate::ops::handle::{DfgID, NodeHandle};
+use crate::ops::OpParent;
 use crate::{IncomingPort, Node, OutgoingPort, PortIndex};
 
 /// Structure identifying an `InlineDFG` rewrite from the spec
@@ -19,6 +22,15 @@ pub enum InlineDFGError {
     /// DFG has no parent (is the root).
     #[error("Node did not have a parent into which to inline")]
     NoParent,
+    /// The DFG requires extensions that are not in the extension delta of
+    /// its parent, so its contents cannot be moved there.
+    #[error("DFG {node} requires extensions {missing} not in the extension delta of its parent")]
+    ExtensionsMissingInParent {
+        /// The DFG node.
+        node: Node,
+        /// The extensions missing from the parent's delta.
+        missing: ExtensionSet,
+    },
 }
 
 impl Rewrite for InlineDFG {
@@ -33,9 +45,16 @@ impl Rewrite for InlineDFG {
         if h.get_optype(n).as_dfg().is_none() {
             return Err(InlineDFGError::NotDFG(n));
         };
-        if h.get_parent(n).is_none() {
+        let Some(parent) = h.get_parent(n) else {
             return Err(InlineDFGError::NoParent);
         };
+        if let Some(parent_sig) = h.get_optype(parent).inner_function_type() {
+            let dfg_reqs = &h.get_optype(n).as_dfg().unwrap().signature.extension_reqs;
+            let missing = parent_sig.extension_reqs.missing_from(dfg_reqs);
+            if !missing.is_empty() {
+                return Err(InlineDFGError::ExtensionsMissingInParent { node: n, missing });
+            }
+        }
         Ok(())
     }
 
@@ -126,10 +145,22 @@ impl Rewrite for InlineDFG {
     }
 }
 
+/// Flattens the DFG `dfg_node` into its parent.
+///
+/// The children of the DFG other than its Input and Output nodes are moved
+/// into the parent and wired directly to the edges of the DFG, which is then
+/// removed. This applies an [InlineDFG] rewrite, so the hugr is unchanged if
+/// it fails, e.g. because the DFG requires extensions that its parent lacks.
+pub fn flatten_dfg(hugr: &mut impl HugrMut, dfg_node: Node) -> Result<(), InlineDFGError> {
+    hugr.apply_rewrite(InlineDFG(dfg_node.into()))?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use std::collections::HashSet;
 
+    use itertools::Itertools;
     use rstest::rstest;
 
     use crate::builder::{
@@ -137,10 +168,11 @@ mod test {
     };
     use crate::extension::prelude::QB_T;
     use crate::extension::{ExtensionRegistry, ExtensionSet, PRELUDE};
+    use crate::hugr::hugrmut::sealed::HugrMutInternals;
     use crate::hugr::rewrite::inline_dfg::InlineDFGError;
-    use crate::hugr::HugrMut;
+    use crate::hugr::{HugrMut, NodeType};
     use crate::ops::handle::{DfgID, NodeHandle};
-    use crate::ops::{Lift, OpType, Value};
+    use crate::ops::{Lift, OpType, Value, DFG};
     use crate::std_extensions::arithmetic::float_types;
     use crate::std_extensions::arithmetic::int_ops::{self, IntOpDef};
     use crate::std_extensions::arithmetic::int_types::{self, ConstInt};
@@ -149,7 +181,7 @@ mod test {
     use crate::{type_row, Direction, HugrView, Node, Port};
     use crate::{Hugr, Wire};
 
-    use super::InlineDFG;
+    use super::{flatten_dfg, InlineDFG};
 
     fn find_dfgs(h: &impl HugrView) -> Vec<Node> {
         h.nodes()
@@ -322,6 +354,88 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn extensions_missing_in_parent() -> Result<(), Box<dyn std::error::Error>> {
+        let delta = ExtensionSet::singleton(&test_quantum_extension::EXTENSION_ID);
+        let mut outer = DFGBuilder::new(
+            FunctionType::new_endo(type_row![QB_T]).with_extension_delta(delta.clone()),
+        )?;
+        let [q] = outer.input_wires_arr();
+        let inner = {
+            let mut inner = outer.dfg_builder(
+                FunctionType::new_endo(type_row![QB_T]).with_extension_delta(delta.clone()),
+                None,
+                [q],
+            )?;
+            let [q] = inner.input_wires_arr();
+            let h_gate = inner.add_dataflow_op(test_quantum_extension::h_gate(), [q])?;
+            inner.finish_with_outputs(h_gate.outputs())?
+        };
+        let outer =
+            outer.finish_hugr_with_outputs(inner.outputs(), &test_quantum_extension::REG)?;
+
+        // Inlining preserves the signature of the parent.
+        let mut h = outer.clone();
+        h.apply_rewrite(InlineDFG(*inner.handle()))?;
+        h.validate(&test_quantum_extension::REG)?;
+        assert_eq!(find_dfgs(&h), vec![h.root()]);
+        assert_eq!(h.get_function_type(), outer.get_function_type());
+
+        // Without the extension delta on the parent, the DFG cannot be inlined.
+        let mut h = outer;
+        h.replace_op(
+            h.root(),
+            NodeType::new_open(DFG {
+                signature: FunctionType::new_endo(type_row![QB_T]),
+            }),
+        )?;
+        let backup = h.clone();
+        assert_eq!(
+            h.apply_rewrite(InlineDFG(*inner.handle())),
+            Err(InlineDFGError::ExtensionsMissingInParent {
+                node: inner.node(),
+                missing: delta
+            })
+        );
+        assert_eq!(h, backup);
+        Ok(())
+    }
+
+    #[test]
+    fn flatten() -> Result<(), Box<dyn std::error::Error>> {
+        let sig = FunctionType::new_endo(type_row![QB_T, QB_T])
+            .with_extension_delta(test_quantum_extension::EXTENSION_ID);
+        let mut outer = DFGBuilder::new(sig.clone())?;
+        let inner = {
+            let mut inner = outer.dfg_builder(sig, None, outer.input_wires())?;
+            let [q0, q1] = inner.input_wires_arr();
+            let cx = inner.add_dataflow_op(test_quantum_extension::cx_gate(), [q0, q1])?;
+            inner.finish_with_outputs(cx.outputs())?
+        };
+        let outer =
+            outer.finish_hugr_with_outputs(inner.outputs(), &test_quantum_extension::REG)?;
+
+        let mut h = outer.clone();
+        flatten_dfg(&mut h, inner.node())?;
+        h.validate(&test_quantum_extension::REG)?;
+        assert_eq!(find_dfgs(&h), vec![h.root()]);
+        assert_eq!(h.node_count(), outer.node_count() - 3);
+        assert_eq!(h.get_function_type(), outer.get_function_type());
+
+        // The CX is now wired directly to the boundary of the outer DFG.
+        let [input, output] = h.get_io(h.root()).unwrap();
+        let cx = h
+            .output_neighbours(input)
+            .dedup()
+            .exactly_one()
+            .ok()
+            .unwrap();
+        assert_eq!(h.input_neighbours(output).dedup().collect_vec(), [cx]);
+
+        assert_eq!(flatten_dfg(&mut h, cx), Err(InlineDFGError::NotDFG(cx)));
+        Ok(())
+    }
+
     #[test]
     fn order_edges() -> Result<(), Box<dyn std::error::Error>> {
         /*      -----|-----|-----
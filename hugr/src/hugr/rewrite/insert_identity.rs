@@ -3,7 +3,7 @@
 use std::iter;
 
 use crate::hugr::{HugrMut, Node};
-use crate::ops::{Noop, OpTag, OpTrait};
+use crate::ops::Noop;
 use crate::types::EdgeKind;
 use crate::{HugrView, IncomingPort};
 
@@ -77,7 +77,7 @@ impl Rewrite for IdentityInsertion {
         let parent = h
             .get_parent(self.post_node)
             .ok_or(IdentityInsertionError::InvalidParentNode)?;
-        if !OpTag::DataflowParent.is_superset(h.get_optype(parent).tag()) {
+        if !h.get_optype(parent).is_dataflow_parent() {
             return Err(IdentityInsertionError::InvalidParentNode);
         }
         let new_node = h.add_node_with_parent(parent, Noop { ty });
@@ -249,7 +249,7 @@ mod test {
     use std::collections::HashSet;
 
     use crate::algorithm::nest_cfgs::test::{
-        build_cond_then_loop_cfg, build_conditional_in_loop, build_conditional_in_loop_cfg, depth,
+        build_cond_then_loop_cfg, build_conditional_in_loop, build_conditional_in_loop_cfg,
     };
     use crate::builder::{
         Container, Dataflow, DataflowSubContainer, HugrBuilder, ModuleBuilder, SubContainer,
@@ -305,7 +305,7 @@ mod test {
         h: &mut impl HugrMut,
         head: BasicBlockID,
         tail: BasicBlockID,
-        expected_depth: u32,
+        expected_depth: usize,
     ) {
         let head = head.node();
         let tail = tail.node();
@@ -319,19 +319,19 @@ mod test {
         let merge = h.input_neighbours(tail).exactly_one().ok().unwrap();
         let [left, right]: [Node; 2] = h.output_neighbours(head).collect_vec().try_into().unwrap();
         for n in [head, tail, merge] {
-            assert_eq!(depth(h.base_hugr(), n), expected_depth);
+            assert_eq!(h.base_hugr().depth(n), expected_depth);
         }
         let blocks = [head, left, right, merge];
         let (new_block, new_cfg) = h.apply_rewrite(OutlineCfg::new(blocks)).unwrap();
         for n in blocks {
-            assert_eq!(depth(h.base_hugr(), n), expected_depth + 2);
+            assert_eq!(h.base_hugr().depth(n), expected_depth + 2);
         }
         assert_eq!(
             new_block,
             h.output_neighbours(entry).exactly_one().ok().unwrap()
         );
         for n in [entry, exit, tail, new_block] {
-            assert_eq!(depth(h.base_hugr(), n), expected_depth);
+            assert_eq!(h.base_hugr().depth(n), expected_depth);
         }
         assert_eq!(
             h.input_neighbours(tail).exactly_one().ok().unwrap(),
@@ -392,7 +392,7 @@ mod test {
         let blocks_to_move = [entry, left, right, merge];
         let other_blocks = [head, tail, exit];
         for &n in blocks_to_move.iter().chain(other_blocks.iter()) {
-            assert_eq!(depth(&h, n), 1);
+            assert_eq!(h.depth(n), 1);
         }
         let (new_block, new_cfg) = h
             .apply_rewrite(OutlineCfg::new(blocks_to_move.iter().copied()))
@@ -403,7 +403,7 @@ mod test {
         assert_eq!(h.get_parent(new_cfg), Some(new_block));
         assert!(h.get_optype(new_cfg).is_cfg());
         for n in other_blocks {
-            assert_eq!(depth(&h, n), 1);
+            assert_eq!(h.depth(n), 1);
         }
         for n in blocks_to_move {
             assert_eq!(h.get_parent(n).unwrap(), new_cfg);
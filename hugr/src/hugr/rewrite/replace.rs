@@ -445,15 +445,12 @@ mod test {
     use cool_asserts::assert_matches;
     use itertools::Itertools;
 
-    use crate::algorithm::nest_cfgs::test::depth;
     use crate::builder::{
         BuildError, CFGBuilder, Container, DFGBuilder, Dataflow, DataflowHugr,
         DataflowSubContainer, HugrBuilder, SubContainer,
     };
     use crate::extension::prelude::{BOOL_T, USIZE_T};
-    use crate::extension::{
-        ExtensionId, ExtensionRegistry, ExtensionSet, PRELUDE, PRELUDE_REGISTRY,
-    };
+    use crate::extension::{ExtensionId, ExtensionRegistry, ExtensionSet, PRELUDE_REGISTRY};
     use crate::hugr::hugrmut::sealed::HugrMutInternals;
     use crate::hugr::rewrite::replace::WhichHugr;
     use crate::hugr::{HugrMut, NodeType, Rewrite};
@@ -469,9 +466,7 @@ mod test {
 
     #[test]
     fn cfg() -> Result<(), Box<dyn std::error::Error>> {
-        let reg =
-            ExtensionRegistry::try_new([PRELUDE.to_owned(), collections::EXTENSION.to_owned()])
-                .unwrap();
+        let reg = ExtensionRegistry::with_prelude([collections::EXTENSION.to_owned()]).unwrap();
         let listy = Type::new_extension(
             collections::EXTENSION
                 .get_type(&collections::LIST_TYPENAME)
@@ -509,8 +504,8 @@ mod test {
         {
             let pop = find_node(&h, "pop");
             let push = find_node(&h, "push");
-            assert_eq!(depth(&h, pop), 2); // BB, CFG
-            assert_eq!(depth(&h, push), 2);
+            assert_eq!(h.depth(pop), 2); // BB, CFG
+            assert_eq!(h.depth(push), 2);
 
             let popp = h.get_parent(pop).unwrap();
             let pushp = h.get_parent(push).unwrap();
@@ -592,8 +587,8 @@ mod test {
         {
             let pop = find_node(&h, "pop");
             let push = find_node(&h, "push");
-            assert_eq!(depth(&h, pop), 3); // DFG, BB, CFG
-            assert_eq!(depth(&h, push), 3);
+            assert_eq!(h.depth(pop), 3); // DFG, BB, CFG
+            assert_eq!(h.depth(push), 3);
 
             let popp = h.get_parent(pop).unwrap();
             let pushp = h.get_parent(push).unwrap();
@@ -1,7 +1,11 @@
 //! Implementation of the `SimpleReplace` operation.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::mem;
 
+use itertools::Itertools;
+
+use crate::hugr::views::sibling_subgraph::InvalidReplacement;
 use crate::hugr::views::SiblingSubgraph;
 use crate::hugr::{HugrMut, HugrView, NodeMetadataMap, Rewrite};
 use crate::ops::{OpTag, OpTrait, OpType};
@@ -9,6 +13,11 @@ use crate::{Hugr, IncomingPort, Node};
 use thiserror::Error;
 
 /// Specification of a simple replacement operation.
+///
+/// Node metadata in the replacement hugr is carried over to the new nodes,
+/// while the metadata of the removed nodes is discarded. Annotations may be
+/// transferred from removed nodes to new ones with
+/// [`SimpleReplacement::with_metadata_map`].
 #[derive(Debug, Clone)]
 pub struct SimpleReplacement {
     /// The subgraph of the hugr to be replaced.
@@ -21,6 +30,9 @@ pub struct SimpleReplacement {
     /// A map from (target ports of edges from nodes in `removal` to nodes not in `removal`) to
     /// (input ports of the Output node of `replacement`).
     nu_out: HashMap<(Node, IncomingPort), IncomingPort>,
+    /// A map from nodes in `removal` to nodes in `replacement` that should
+    /// inherit their metadata.
+    metadata_map: HashMap<Node, Node>,
 }
 
 impl SimpleReplacement {
@@ -37,9 +49,105 @@ impl SimpleReplacement {
             replacement,
             nu_inp,
             nu_out,
+            metadata_map: HashMap::new(),
         }
     }
 
+    /// Transfer the metadata of removed nodes to replacement nodes.
+    ///
+    /// Keys of `metadata_map` are nodes of the subgraph, and values are
+    /// non-IO children of the replacement root. Entries already present on a
+    /// replacement node take precedence over the transferred ones.
+    pub fn with_metadata_map(mut self, metadata_map: HashMap<Node, Node>) -> Self {
+        self.metadata_map = metadata_map;
+        self
+    }
+
+    /// Create a new [`SimpleReplacement`] specification with an explicit port
+    /// mapping, checking it against the boundaries of `subgraph` in `hugr`
+    /// and of `replacement`.
+    ///
+    /// This allows for non-identity mappings between the subgraph and the
+    /// replacement boundaries, e.g. permuting the outputs. The replacement
+    /// boundary ports can be obtained with
+    /// [`SiblingSubgraph::replacement_io_ports`].
+    ///
+    /// The keys of `nu_inp` must be exactly the targets of edges from the
+    /// replacement's Input node, and its values ports in the incoming boundary
+    /// of `subgraph`. The keys of `nu_out` must be exactly the targets of edges
+    /// from the outgoing boundary of `subgraph`, and its values input ports of
+    /// the replacement's Output node.
+    ///
+    /// Returns [`InvalidReplacement::InvalidReplacementPort`] or
+    /// [`InvalidReplacement::InvalidHostPort`] for a port of the replacement
+    /// or of `hugr`, respectively, that is wrongly mapped or not mapped.
+    pub fn new_with_mapping(
+        subgraph: SiblingSubgraph,
+        replacement: Hugr,
+        nu_inp: HashMap<(Node, IncomingPort), (Node, IncomingPort)>,
+        nu_out: HashMap<(Node, IncomingPort), IncomingPort>,
+        hugr: &impl HugrView,
+    ) -> Result<Self, InvalidReplacement> {
+        let (rep_inputs, rep_outputs) = subgraph.replacement_io_ports(hugr, &replacement)?;
+
+        let rep_targets: HashSet<_> = rep_inputs
+            .iter()
+            .flat_map(|&(n, p)| replacement.linked_inputs(n, p))
+            .collect();
+        let subgraph_inputs: HashSet<_> = subgraph
+            .incoming_ports()
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+        for (&(rep_n, rep_p), &(n, p)) in &nu_inp {
+            if !rep_targets.contains(&(rep_n, rep_p)) {
+                return Err(InvalidReplacement::InvalidReplacementPort(
+                    rep_n,
+                    rep_p.into(),
+                ));
+            }
+            if !subgraph_inputs.contains(&(n, p)) {
+                return Err(InvalidReplacement::InvalidHostPort(n, p.into()));
+            }
+        }
+        if let Some((n, p)) = rep_inputs
+            .iter()
+            .flat_map(|&(n, p)| replacement.linked_inputs(n, p))
+            .find(|target| !nu_inp.contains_key(target))
+        {
+            return Err(InvalidReplacement::InvalidReplacementPort(n, p.into()));
+        }
+
+        let self_targets = subgraph
+            .outgoing_ports()
+            .iter()
+            .flat_map(|&(n, p)| hugr.linked_inputs(n, p))
+            .collect_vec();
+        let self_target_set: HashSet<_> = self_targets.iter().copied().collect();
+        let rep_output_ports: HashSet<_> = rep_outputs.iter().map(|&(_, p)| p).collect();
+        let rep_output = replacement.get_io(replacement.root()).unwrap()[1];
+        for (&(n, p), &rep_port) in &nu_out {
+            if !self_target_set.contains(&(n, p)) {
+                return Err(InvalidReplacement::InvalidHostPort(n, p.into()));
+            }
+            if !rep_output_ports.contains(&rep_port) {
+                return Err(InvalidReplacement::InvalidReplacementPort(
+                    rep_output,
+                    rep_port.into(),
+                ));
+            }
+        }
+        if let Some(&(n, p)) = self_targets
+            .iter()
+            .find(|target| !nu_out.contains_key(target))
+        {
+            return Err(InvalidReplacement::InvalidHostPort(n, p.into()));
+        }
+
+        Ok(Self::new(subgraph, replacement, nu_inp, nu_out))
+    }
+
     /// The replacement hugr.
     #[inline]
     pub fn replacement(&self) -> &Hugr {
@@ -51,6 +159,45 @@ impl SimpleReplacement {
     pub fn subgraph(&self) -> &SiblingSubgraph {
         &self.subgraph
     }
+
+    /// The map from targets of the replacement's Input node to the incoming
+    /// boundary ports of the subgraph.
+    #[inline]
+    pub fn nu_inp(&self) -> &HashMap<(Node, IncomingPort), (Node, IncomingPort)> {
+        &self.nu_inp
+    }
+
+    /// The map from targets of the subgraph's outgoing boundary to the input
+    /// ports of the replacement's Output node.
+    #[inline]
+    pub fn nu_out(&self) -> &HashMap<(Node, IncomingPort), IncomingPort> {
+        &self.nu_out
+    }
+
+    /// The map from removed nodes to the replacement nodes inheriting their
+    /// metadata, see [`SimpleReplacement::with_metadata_map`].
+    #[inline]
+    pub fn metadata_map(&self) -> &HashMap<Node, Node> {
+        &self.metadata_map
+    }
+
+    /// Decompose the replacement into the arguments of
+    /// [`SimpleReplacement::new`]: the subgraph, the replacement hugr, and the
+    /// `nu_inp` and `nu_out` maps.
+    ///
+    /// The metadata map is dropped; read it beforehand with
+    /// [`SimpleReplacement::metadata_map`] if needed.
+    #[allow(clippy::type_complexity)]
+    pub fn into_parts(
+        self,
+    ) -> (
+        SiblingSubgraph,
+        Hugr,
+        HashMap<(Node, IncomingPort), (Node, IncomingPort)>,
+        HashMap<(Node, IncomingPort), IncomingPort>,
+    ) {
+        (self.subgraph, self.replacement, self.nu_inp, self.nu_out)
+    }
 }
 
 impl Rewrite for SimpleReplacement {
@@ -58,22 +205,86 @@ impl Rewrite for SimpleReplacement {
     type ApplyResult = ();
     const UNCHANGED_ON_FAILURE: bool = true;
 
-    fn verify(&self, _h: &impl HugrView) -> Result<(), SimpleReplacementError> {
-        unimplemented!()
-    }
-
-    fn apply(mut self, h: &mut impl HugrMut) -> Result<(), SimpleReplacementError> {
-        let parent = self.subgraph.get_parent(h);
+    fn verify(&self, h: &impl HugrView) -> Result<(), SimpleReplacementError> {
+        let parent = self.subgraph.parent();
         // 1. Check the parent node exists and is a DataflowParent.
-        if !OpTag::DataflowParent.is_superset(h.get_optype(parent).tag()) {
+        if !h.contains_node(parent) || !h.get_optype(parent).is_dataflow_parent() {
             return Err(SimpleReplacementError::InvalidParentNode());
         }
         // 2. Check that all the to-be-removed nodes are children of it and are leaves.
         for node in self.subgraph.nodes() {
-            if h.get_parent(*node) != Some(parent) || h.children(*node).next().is_some() {
+            if !h.contains_node(*node)
+                || h.get_parent(*node) != Some(parent)
+                || h.children(*node).next().is_some()
+            {
                 return Err(SimpleReplacementError::InvalidRemovedNode());
             }
         }
+        // 3. Check that the boundary edges still exist: the incoming ports are
+        // fed from outside the subgraph, and the targets in `nu_out` from inside.
+        let in_subgraph = |src: Option<(Node, _)>| src.map(|(n, _)| self.subgraph.contains(n));
+        for &(node, port) in self.subgraph.incoming_ports().iter().flatten() {
+            if in_subgraph(h.single_linked_output(node, port)) != Some(false) {
+                return Err(SimpleReplacementError::InvalidBoundaryPort(node, port));
+            }
+        }
+        for &(node, port) in self.nu_out.keys() {
+            if !h.contains_node(node)
+                || in_subgraph(h.single_linked_output(node, port)) != Some(true)
+            {
+                return Err(SimpleReplacementError::InvalidBoundaryPort(node, port));
+            }
+        }
+        // The metadata map must relate removed nodes to replacement nodes.
+        let replacement_root = self.replacement.root();
+        for (&node, &rep_node) in &self.metadata_map {
+            if !self.subgraph.contains(node) {
+                return Err(SimpleReplacementError::InvalidRemovedNode());
+            }
+            if self.replacement.get_parent(rep_node) != Some(replacement_root)
+                || self
+                    .replacement
+                    .get_io(replacement_root)
+                    .unwrap()
+                    .contains(&rep_node)
+            {
+                return Err(SimpleReplacementError::InvalidReplacementNode());
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(self, h: &mut impl HugrMut) -> Result<(), SimpleReplacementError> {
+        self.verify(h)?;
+        self.apply_unchecked(h);
+        Ok(())
+    }
+
+    #[inline]
+    fn invalidation_set(&self) -> impl Iterator<Item = Node> {
+        let subcirc = self.subgraph.nodes().iter().copied();
+        let out_neighs = self.nu_out.keys().map(|key| key.0);
+        subcirc.chain(out_neighs)
+    }
+}
+
+/// For each incoming boundary port of a replaced subgraph, the ports that
+/// receive its wire after the replacement.
+type InputRemap = HashMap<(Node, IncomingPort), Vec<(Node, IncomingPort)>>;
+
+impl SimpleReplacement {
+    /// Applies the replacement, assuming it has been verified.
+    ///
+    /// Returns where the wires entering the removed subgraph now lead.
+    fn apply_unchecked(mut self, h: &mut impl HugrMut) -> InputRemap {
+        let parent = self.subgraph.parent();
+        let mut input_remap: InputRemap = self
+            .subgraph
+            .incoming_ports()
+            .iter()
+            .flatten()
+            .map(|&p| (p, Vec::new()))
+            .collect();
         // 3. Do the replacement.
         // 3.1. Add copies of all replacement nodes and edges to h. Exclude Input/Output nodes.
         // Create map from old NodeIndex (in self.replacement) to new NodeIndex (in self).
@@ -117,7 +328,8 @@ impl Rewrite for SimpleReplacement {
                 let (rem_inp_pred_node, rem_inp_pred_port) = h
                     .single_linked_output(*rem_inp_node, *rem_inp_port)
                     .unwrap();
-                h.disconnect(*rem_inp_node, *rem_inp_port);
+                // The removed port is left connected, as it may feed several
+                // replacement ports. It is disconnected when its node is removed.
                 let new_inp_node = index_map.get(rep_inp_node).unwrap();
                 h.connect(
                     rem_inp_pred_node,
@@ -125,6 +337,10 @@ impl Rewrite for SimpleReplacement {
                     *new_inp_node,
                     *rep_inp_port,
                 );
+                input_remap
+                    .entry((*rem_inp_node, *rem_inp_port))
+                    .or_default()
+                    .push((*new_inp_node, *rep_inp_port));
             }
         }
         // 3.3. For each q = self.nu_out[p] such that the predecessor of q is not an Input port, add an
@@ -154,7 +370,6 @@ impl Rewrite for SimpleReplacement {
                 let (rem_inp_pred_node, rem_inp_pred_port) = h
                     .single_linked_output(*rem_inp_node, *rem_inp_port)
                     .unwrap();
-                h.disconnect(*rem_inp_node, *rem_inp_port);
                 h.disconnect(*rem_out_node, *rem_out_port);
                 h.connect(
                     rem_inp_pred_node,
@@ -162,21 +377,134 @@ impl Rewrite for SimpleReplacement {
                     *rem_out_node,
                     *rem_out_port,
                 );
+                input_remap
+                    .entry((*rem_inp_node, *rem_inp_port))
+                    .or_default()
+                    .push((*rem_out_node, *rem_out_port));
+            }
+        }
+        // 3.5. Transfer metadata from removed nodes to their mapped replacements.
+        for (&node, rep_node) in &self.metadata_map {
+            let Some(old_meta) = h.take_node_metadata(node) else {
+                continue;
+            };
+            let new_node = index_map[rep_node];
+            let mut meta = h.take_node_metadata(new_node).unwrap_or_default();
+            for (key, value) in old_meta {
+                meta.entry(key).or_insert(value);
             }
+            h.overwrite_node_metadata(new_node, Some(meta));
         }
-        // 3.5. Remove all nodes in self.removal and edges between them.
+        // 3.6. Remove all nodes in self.removal and edges between them.
         for &node in self.subgraph.nodes() {
             h.remove_node(node);
         }
-        Ok(())
+        input_remap
     }
 
-    #[inline]
-    fn invalidation_set(&self) -> impl Iterator<Item = Node> {
-        let subcirc = self.subgraph.nodes().iter().copied();
-        let out_neighs = self.nu_out.keys().map(|key| key.0);
-        subcirc.chain(out_neighs)
+    /// Redirect the outputs of the replacement that targeted ports of
+    /// subgraphs removed by other replacements.
+    ///
+    /// A wire may be redirected into the subgraph of a replacement applied
+    /// later, so `input_remap` is followed until it reaches a port it does
+    /// not remap.
+    fn remap_outputs(&mut self, input_remap: &InputRemap) {
+        fn resolve(
+            target: (Node, IncomingPort),
+            input_remap: &InputRemap,
+            resolved: &mut Vec<(Node, IncomingPort)>,
+        ) {
+            match input_remap.get(&target) {
+                Some(new_targets) => {
+                    for &t in new_targets {
+                        resolve(t, input_remap, resolved);
+                    }
+                }
+                None => resolved.push(target),
+            }
+        }
+        let mut resolved = Vec::new();
+        for (target, rep_port) in mem::take(&mut self.nu_out) {
+            resolve(target, input_remap, &mut resolved);
+            self.nu_out
+                .extend(resolved.drain(..).map(|t| (t, rep_port)));
+        }
+    }
+}
+
+/// Applies a batch of [`SimpleReplacement`]s with pairwise disjoint
+/// subgraphs. See [`HugrMut::apply_rewrites`].
+pub(crate) fn apply_batch(
+    h: &mut impl HugrMut,
+    rewrites: Vec<SimpleReplacement>,
+) -> Result<(), BatchRewriteError> {
+    let mut owners: HashMap<Node, usize> = HashMap::new();
+    for (j, rw) in rewrites.iter().enumerate() {
+        for &node in rw.subgraph.nodes() {
+            if let Some(i) = owners.insert(node, j) {
+                return Err(BatchRewriteError::Overlap { i, j });
+            }
+        }
     }
+    for (index, rw) in rewrites.iter().enumerate() {
+        rw.verify(h)
+            .map_err(|error| BatchRewriteError::InvalidRewrite { index, error })?;
+    }
+    // A wire from one subgraph into another must be rewired by the first
+    // replacement, or the second one would find its input disconnected.
+    for (j, rw) in rewrites.iter().enumerate() {
+        for &(node, port) in rw.subgraph.incoming_ports().iter().flatten() {
+            let (src, _) = h.single_linked_output(node, port).unwrap();
+            if let Some(&i) = owners.get(&src) {
+                if !rewrites[i].nu_out.contains_key(&(node, port)) {
+                    return Err(BatchRewriteError::UnmappedWire { i, j });
+                }
+            }
+        }
+    }
+
+    // The subgraphs are disjoint, so applying a replacement leaves the nodes
+    // of the others untouched. Only the wires leaving a subgraph into another
+    // one need to be redirected to their new targets.
+    let mut input_remap = InputRemap::new();
+    for mut rw in rewrites {
+        rw.remap_outputs(&input_remap);
+        input_remap.extend(rw.apply_unchecked(h));
+    }
+    Ok(())
+}
+
+/// Error from applying a batch of [`SimpleReplacement`]s with
+/// [`HugrMut::apply_rewrites`].
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BatchRewriteError {
+    /// The subgraphs of two replacements share a node.
+    #[error("The subgraphs of replacements {i} and {j} overlap.")]
+    Overlap {
+        /// The index of the first replacement.
+        i: usize,
+        /// The index of the second replacement.
+        j: usize,
+    },
+    /// A wire from the subgraph of a replacement into the subgraph of
+    /// another one is not in the `nu_out` map of the first.
+    #[error("Replacement {i} does not rewire an edge into the subgraph of replacement {j}.")]
+    UnmappedWire {
+        /// The index of the replacement the wire leaves.
+        i: usize,
+        /// The index of the replacement the wire enters.
+        j: usize,
+    },
+    /// A replacement is invalid.
+    #[error("Replacement {index} is invalid: {error}")]
+    InvalidRewrite {
+        /// The index of the replacement.
+        index: usize,
+        /// The error of the replacement.
+        #[source]
+        error: SimpleReplacementError,
+    },
 }
 
 /// Error from a [`SimpleReplacement`] operation.
@@ -192,12 +520,16 @@ pub enum SimpleReplacementError {
     /// Node in replacement graph is invalid.
     #[error("A node in the replacement graph is invalid.")]
     InvalidReplacementNode(),
+    /// A boundary edge of the subgraph is missing.
+    #[error("The boundary edge at port {1} of node {0} is missing.")]
+    InvalidBoundaryPort(Node, IncomingPort),
 }
 
 #[cfg(test)]
 pub(in crate::hugr::rewrite) mod test {
     use itertools::Itertools;
     use rstest::{fixture, rstest};
+    use serde_json::json;
     use std::collections::{HashMap, HashSet};
 
     use crate::builder::{
@@ -206,9 +538,11 @@ pub(in crate::hugr::rewrite) mod test {
     };
     use crate::extension::prelude::BOOL_T;
     use crate::extension::{EMPTY_REG, PRELUDE_REGISTRY};
+    use crate::hugr::views::sibling_subgraph::InvalidReplacement;
     use crate::hugr::views::{HugrView, SiblingSubgraph};
     use crate::hugr::{Hugr, HugrMut, Rewrite};
     use crate::ops::dataflow::DataflowOpTrait;
+    use crate::ops::handle::NodeHandle;
     use crate::ops::OpTag;
     use crate::ops::OpTrait;
     use crate::std_extensions::logic::test::and_op;
@@ -217,7 +551,7 @@ pub(in crate::hugr::rewrite) mod test {
     use crate::utils::test_quantum_extension::{cx_gate, h_gate};
     use crate::{IncomingPort, Node};
 
-    use super::SimpleReplacement;
+    use super::{BatchRewriteError, SimpleReplacement, SimpleReplacementError};
 
     const QB: Type = crate::extension::prelude::QB_T;
 
@@ -371,12 +705,12 @@ pub(in crate::hugr::rewrite) mod test {
         nu_out.insert((h_outp_node, h_port_2), n_port_2);
         nu_out.insert((h_outp_node, h_port_3), n_port_3);
         // 4. Define the replacement
-        let r = SimpleReplacement {
-            subgraph: SiblingSubgraph::try_from_nodes(s, &h).unwrap(),
-            replacement: n,
+        let r = SimpleReplacement::new(
+            SiblingSubgraph::try_from_nodes(s, &h).unwrap(),
+            n,
             nu_inp,
             nu_out,
-        };
+        );
         assert_eq!(
             HashSet::<_>::from_iter(r.invalidation_set()),
             HashSet::<_>::from_iter([h_node_cx, h_node_h0, h_node_h1, h_outp_node]),
@@ -448,12 +782,12 @@ pub(in crate::hugr::rewrite) mod test {
         nu_out.insert((h_node_h0, h_port_2), n_port_0);
         nu_out.insert((h_node_h1, h_port_3), n_port_1);
         // 4. Define the replacement
-        let r = SimpleReplacement {
-            subgraph: SiblingSubgraph::try_from_nodes(s, &h).unwrap(),
-            replacement: n,
+        let r = SimpleReplacement::new(
+            SiblingSubgraph::try_from_nodes(s, &h).unwrap(),
+            n,
             nu_inp,
             nu_out,
-        };
+        );
         h.apply_rewrite(r).unwrap();
         // Expect [DFG] to be replaced with:
         // ┌───┐┌───┐
@@ -520,6 +854,310 @@ pub(in crate::hugr::rewrite) mod test {
         assert_eq!(h.edge_count(), orig.edge_count());
     }
 
+    #[test]
+    fn test_replace_with_mapping() {
+        let mut builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB, QB])).unwrap();
+        let cx = builder
+            .add_dataflow_op(cx_gate(), builder.input_wires())
+            .unwrap();
+        let [input, output] = builder.io();
+        let mut h = builder
+            .finish_prelude_hugr_with_outputs(cx.outputs())
+            .unwrap();
+        let subgraph = SiblingSubgraph::from_node(cx.node(), &h).unwrap();
+
+        let mut builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB, QB])).unwrap();
+        let [q0, q1] = builder.input_wires_arr();
+        let h0 = builder.add_dataflow_op(h_gate(), [q0]).unwrap();
+        let h1 = builder.add_dataflow_op(h_gate(), [q1]).unwrap();
+        let repl = builder
+            .finish_prelude_hugr_with_outputs(h0.outputs().chain(h1.outputs()))
+            .unwrap();
+
+        let (rep_inputs, rep_outputs) = subgraph.replacement_io_ports(&h, &repl).unwrap();
+        let nu_inp: HashMap<_, _> = rep_inputs
+            .iter()
+            .zip(subgraph.incoming_ports())
+            .map(|(&(n, p), targets)| (repl.linked_inputs(n, p).next().unwrap(), targets[0]))
+            .collect();
+        // Swap the outputs of the replacement.
+        let nu_out: HashMap<_, _> = subgraph
+            .outgoing_ports()
+            .iter()
+            .zip(rep_outputs.iter().rev())
+            .map(|(&(n, p), &(_, rep_p))| (h.linked_inputs(n, p).next().unwrap(), rep_p))
+            .collect();
+
+        let bad_nu_out = HashMap::from([((cx.node(), IncomingPort::from(0)), rep_outputs[0].1)]);
+        assert_eq!(
+            SimpleReplacement::new_with_mapping(
+                subgraph.clone(),
+                repl.clone(),
+                nu_inp.clone(),
+                bad_nu_out,
+                &h
+            )
+            .unwrap_err(),
+            InvalidReplacement::InvalidHostPort(cx.node(), IncomingPort::from(0).into())
+        );
+
+        // Every boundary port must be mapped.
+        let mut partial_nu_inp = nu_inp.clone();
+        let (&(rep_n, rep_p), _) = partial_nu_inp.iter().next().unwrap();
+        partial_nu_inp.remove(&(rep_n, rep_p));
+        assert_eq!(
+            SimpleReplacement::new_with_mapping(
+                subgraph.clone(),
+                repl.clone(),
+                partial_nu_inp,
+                nu_out.clone(),
+                &h
+            )
+            .unwrap_err(),
+            InvalidReplacement::InvalidReplacementPort(rep_n, rep_p.into())
+        );
+        let mut partial_nu_out = nu_out.clone();
+        partial_nu_out.remove(&(output, IncomingPort::from(1)));
+        assert_eq!(
+            SimpleReplacement::new_with_mapping(
+                subgraph.clone(),
+                repl.clone(),
+                nu_inp.clone(),
+                partial_nu_out,
+                &h
+            )
+            .unwrap_err(),
+            InvalidReplacement::InvalidHostPort(output, IncomingPort::from(1).into())
+        );
+
+        let rw = SimpleReplacement::new_with_mapping(subgraph, repl, nu_inp, nu_out, &h).unwrap();
+        h.apply_rewrite(rw).unwrap();
+        assert_eq!(h.update_validate(&PRELUDE_REGISTRY), Ok(()));
+
+        // The first input now reaches the second output.
+        let in0 = h.node_outputs(input).next().unwrap();
+        let (h_node, _) = h.linked_inputs(input, in0).exactly_one().ok().unwrap();
+        let h_out = h.node_outputs(h_node).next().unwrap();
+        let (out_node, out_port) = h.linked_inputs(h_node, h_out).exactly_one().ok().unwrap();
+        assert_eq!(out_node, output);
+        assert_eq!(out_port, IncomingPort::from(1));
+    }
+
+    #[test]
+    fn test_into_parts() {
+        let mut builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB, QB])).unwrap();
+        let cx = builder
+            .add_dataflow_op(cx_gate(), builder.input_wires())
+            .unwrap();
+        let h = builder
+            .finish_prelude_hugr_with_outputs(cx.outputs())
+            .unwrap();
+        let subgraph = SiblingSubgraph::from_node(cx.node(), &h).unwrap();
+
+        let mut builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB, QB])).unwrap();
+        let [q0, q1] = builder.input_wires_arr();
+        let h0 = builder.add_dataflow_op(h_gate(), [q0]).unwrap();
+        let h1 = builder.add_dataflow_op(h_gate(), [q1]).unwrap();
+        let repl = builder
+            .finish_prelude_hugr_with_outputs(h0.outputs().chain(h1.outputs()))
+            .unwrap();
+        let rw = subgraph.create_simple_replacement(&h, repl).unwrap();
+        let (nu_inp, nu_out) = (rw.nu_inp().clone(), rw.nu_out().clone());
+        assert_eq!(nu_inp.len(), 2);
+        assert_eq!(nu_out.len(), 2);
+
+        // Replaying the reassembled rewrite gives the same result.
+        let mut expected = h.clone();
+        expected.apply_rewrite(rw.clone()).unwrap();
+        let (subgraph, repl, parts_inp, parts_out) = rw.into_parts();
+        assert_eq!(subgraph.nodes(), [cx.node()]);
+        assert_eq!((&parts_inp, &parts_out), (&nu_inp, &nu_out));
+        let mut replayed = h;
+        replayed
+            .apply_rewrite(SimpleReplacement::new(subgraph, repl, parts_inp, parts_out))
+            .unwrap();
+        assert_eq!(replayed.update_validate(&PRELUDE_REGISTRY), Ok(()));
+        assert_eq!(replayed.node_count(), expected.node_count());
+        assert_eq!(
+            replayed
+                .nodes()
+                .map(|n| replayed.get_optype(n))
+                .collect_vec(),
+            expected
+                .nodes()
+                .map(|n| expected.get_optype(n))
+                .collect_vec()
+        );
+    }
+
+    #[test]
+    fn test_replace_metadata() {
+        let mut builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB, QB])).unwrap();
+        let cx = builder
+            .add_dataflow_op(cx_gate(), builder.input_wires())
+            .unwrap();
+        let mut h = builder
+            .finish_prelude_hugr_with_outputs(cx.outputs())
+            .unwrap();
+        h.set_metadata(cx.node(), "source", "line 3");
+        let subgraph = SiblingSubgraph::from_node(cx.node(), &h).unwrap();
+
+        let mut builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB, QB])).unwrap();
+        let [q0, q1] = builder.input_wires_arr();
+        let h0 = builder.add_dataflow_op(h_gate(), [q0]).unwrap();
+        let h1 = builder.add_dataflow_op(h_gate(), [q1]).unwrap();
+        builder.set_child_metadata(h0.node(), "name", "h0");
+        let repl = builder
+            .finish_prelude_hugr_with_outputs(h0.outputs().chain(h1.outputs()))
+            .unwrap();
+
+        let rw = subgraph
+            .create_simple_replacement(&h, repl.clone())
+            .unwrap()
+            .with_metadata_map(HashMap::from([(cx.node(), h1.node())]));
+        assert_eq!(
+            h.clone()
+                .apply_rewrite(rw.clone().with_metadata_map(HashMap::from([(
+                    cx.node(),
+                    repl.get_io(repl.root()).unwrap()[0]
+                )]))),
+            Err(SimpleReplacementError::InvalidReplacementNode())
+        );
+        h.apply_rewrite(rw).unwrap();
+        assert_eq!(h.update_validate(&PRELUDE_REGISTRY), Ok(()));
+
+        let meta = |key| {
+            h.nodes()
+                .filter_map(|n| h.get_metadata(n, key))
+                .cloned()
+                .collect_vec()
+        };
+        assert_eq!(meta("name"), vec![json!("h0")]);
+        assert_eq!(meta("source"), vec![json!("line 3")]);
+    }
+
+    #[test]
+    fn test_apply_rewrites() {
+        // A chain of four H gates on a single qubit.
+        let mut builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB])).unwrap();
+        let mut q = builder.input_wires().exactly_one().unwrap();
+        let mut gates = Vec::new();
+        for _ in 0..4 {
+            let h = builder.add_dataflow_op(h_gate(), [q]).unwrap();
+            q = h.out_wire(0);
+            gates.push(h.node());
+        }
+        let h = builder.finish_prelude_hugr_with_outputs([q]).unwrap();
+
+        let identity = {
+            let builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB])).unwrap();
+            let inputs = builder.input_wires();
+            builder.finish_prelude_hugr_with_outputs(inputs).unwrap()
+        };
+        let single_h = {
+            let mut builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB])).unwrap();
+            let h = builder
+                .add_dataflow_op(h_gate(), builder.input_wires())
+                .unwrap();
+            builder
+                .finish_prelude_hugr_with_outputs(h.outputs())
+                .unwrap()
+        };
+        let replace = |nodes: &[Node], replacement: &Hugr| {
+            SiblingSubgraph::try_from_nodes(nodes.to_vec(), &h)
+                .unwrap()
+                .create_simple_replacement(&h, replacement.clone())
+                .unwrap()
+        };
+        let first = replace(&gates[..2], &identity);
+        let last = replace(&gates[2..], &single_h);
+        let count_h = |h: &Hugr| {
+            h.nodes()
+                .filter(|&n| *h.get_optype(n) == h_gate().into())
+                .count()
+        };
+
+        // The output of each subgraph feeds the other, in either order.
+        for rewrites in [
+            vec![first.clone(), last.clone()],
+            vec![last.clone(), first.clone()],
+        ] {
+            let mut h = h.clone();
+            h.apply_rewrites(rewrites).unwrap();
+            h.update_validate(&PRELUDE_REGISTRY).unwrap();
+            assert_eq!(count_h(&h), 1);
+        }
+
+        let middle = replace(&gates[1..3], &identity);
+        let mut overlapping = h.clone();
+        assert_eq!(
+            overlapping.apply_rewrites(vec![first.clone(), last.clone(), middle]),
+            Err(BatchRewriteError::Overlap { i: 0, j: 2 })
+        );
+        assert_eq!(overlapping, h);
+
+        // A replacement dropping the wire into another subgraph is rejected.
+        let (subgraph, replacement, nu_inp, mut nu_out) = first.into_parts();
+        nu_out.remove(&(gates[2], IncomingPort::from(0)));
+        let dropping = SimpleReplacement::new(subgraph, replacement, nu_inp, nu_out);
+        let mut unmapped = h.clone();
+        assert_eq!(
+            unmapped.apply_rewrites(vec![last, dropping]),
+            Err(BatchRewriteError::UnmappedWire { i: 1, j: 0 })
+        );
+        assert_eq!(unmapped, h);
+    }
+
+    #[test]
+    fn test_verify_stale_replacement() {
+        // A chain of three H gates on a single qubit.
+        let mut builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB])).unwrap();
+        let mut q = builder.input_wires().exactly_one().unwrap();
+        let mut gates = Vec::new();
+        for _ in 0..3 {
+            let h = builder.add_dataflow_op(h_gate(), [q]).unwrap();
+            q = h.out_wire(0);
+            gates.push(h.node());
+        }
+        let h = builder.finish_prelude_hugr_with_outputs([q]).unwrap();
+        let identity = {
+            let builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB])).unwrap();
+            let inputs = builder.input_wires();
+            builder.finish_prelude_hugr_with_outputs(inputs).unwrap()
+        };
+        let replace = |nodes: &[Node]| {
+            SiblingSubgraph::try_from_nodes(nodes.to_vec(), &h)
+                .unwrap()
+                .create_simple_replacement(&h, identity.clone())
+                .unwrap()
+        };
+        let first = replace(&gates[..2]);
+        let overlapping = replace(&gates[1..]);
+        let last = replace(&gates[2..]);
+        assert_eq!(first.verify(&h), Ok(()));
+        assert_eq!(overlapping.verify(&h), Ok(()));
+
+        // Applying the first replacement removes a node of the second one,
+        // while the third is still applicable.
+        let mut h = h;
+        h.apply_rewrite(first).unwrap();
+        assert_eq!(
+            overlapping.verify(&h),
+            Err(SimpleReplacementError::InvalidRemovedNode())
+        );
+        assert_eq!(last.verify(&h), Ok(()));
+
+        // Removing a boundary edge invalidates the replacement.
+        h.disconnect(gates[2], IncomingPort::from(0));
+        assert_eq!(
+            last.verify(&h),
+            Err(SimpleReplacementError::InvalidBoundaryPort(
+                gates[2],
+                IncomingPort::from(0)
+            ))
+        );
+    }
+
     #[test]
     fn test_replace_after_copy() {
         let one_bit = type_row![BOOL_T];
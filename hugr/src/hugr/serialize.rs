@@ -5,8 +5,8 @@ use std::collections::HashMap;
 use thiserror::Error;
 
 use crate::core::NodeIndex;
-use crate::extension::ExtensionSet;
-use crate::hugr::{Hugr, NodeType};
+use crate::extension::{ExtensionRegistry, ExtensionSet};
+use crate::hugr::{Hugr, NodeType, ValidationError};
 use crate::ops::OpType;
 use crate::{Node, PortIndex};
 use portgraph::hierarchy::AttachError;
@@ -130,6 +130,64 @@ pub enum HUGRSerializationError {
     /// First node in node list must be the HUGR root.
     #[error("The first node in the node list has parent {0:?}, should be itself (index 0)")]
     FirstNodeNotRoot(Node),
+    /// The node list is empty.
+    #[error("The node list is empty, there must be at least a root node.")]
+    NoRootNode,
+    /// A node's parent is not an earlier node in the list.
+    #[error("The parent {parent:?} of node {node:?} is not an earlier node in the graph.")]
+    UnknownParent {
+        /// The node with the invalid parent.
+        node: Node,
+        /// The parent of the node.
+        parent: Node,
+    },
+    /// Edges with a port offset that the node does not have.
+    #[error("Node {node:?} has no {dir:?} port with offset {offset}.")]
+    InvalidPortOffset {
+        /// The node that the edge connects to.
+        node: Node,
+        /// The direction of the port.
+        dir: Direction,
+        /// The offset of the port.
+        offset: usize,
+    },
+    /// More metadata entries than nodes.
+    #[error("Metadata entry {node:?} does not correspond to a node in the graph.")]
+    UnknownMetadataNode {
+        /// The index of the metadata entry.
+        node: Node,
+    },
+    /// The input could not be deserialized.
+    #[error("Failed to deserialize the HUGR: {0}")]
+    Deserialize(String),
+    /// The serialization format version is not supported.
+    #[error("Unsupported HUGR serialization format version.")]
+    UnsupportedVersion,
+    /// The deserialized HUGR does not validate.
+    #[error("The deserialized HUGR is invalid: {0}")]
+    InvalidHugr(#[from] ValidationError),
+}
+
+impl Hugr {
+    /// Load a HUGR from its JSON serialization and validate it against
+    /// `extension_registry`.
+    ///
+    /// Indices and port offsets are checked as the HUGR is built, so malformed
+    /// or untrusted input produces an error rather than a panic. The result is
+    /// only returned if it passes [`Hugr::validate`].
+    pub fn load_validated(
+        bytes: &[u8],
+        extension_registry: &ExtensionRegistry,
+    ) -> Result<Hugr, HUGRSerializationError> {
+        let shg: Versioned<SerHugrV1> = serde_json::from_slice(bytes)
+            .map_err(|e| HUGRSerializationError::Deserialize(e.to_string()))?;
+        let Versioned::V1(shg) = shg else {
+            return Err(HUGRSerializationError::UnsupportedVersion);
+        };
+        let hugr: Hugr = shg.try_into()?;
+        hugr.validate(extension_registry)?;
+        Ok(hugr)
+    }
 }
 
 impl Serialize for Hugr {
@@ -244,7 +302,7 @@ impl TryFrom<SerHugrV1> for Hugr {
             parent: root_parent,
             input_extensions,
             op: root_type,
-        } = nodes.next().unwrap();
+        } = nodes.next().ok_or(HUGRSerializationError::NoRootNode)?;
         if root_parent.index() != 0 {
             return Err(HUGRSerializationError::FirstNodeNotRoot(root_parent));
         }
@@ -257,6 +315,12 @@ impl TryFrom<SerHugrV1> for Hugr {
         );
 
         for node_ser in nodes {
+            if !hugr.graph.contains_node(node_ser.parent.pg_index()) {
+                return Err(HUGRSerializationError::UnknownParent {
+                    node: portgraph::NodeIndex::new(hugr.node_count()).into(),
+                    parent: node_ser.parent,
+                });
+            }
             hugr.add_node_with_parent(
                 node_ser.parent,
                 NodeType::new(node_ser.op, node_ser.input_extensions),
@@ -265,6 +329,9 @@ impl TryFrom<SerHugrV1> for Hugr {
 
         for (node, metadata) in metadata.into_iter().enumerate() {
             let node = portgraph::NodeIndex::new(node);
+            if !hugr.graph.contains_node(node) {
+                return Err(HUGRSerializationError::UnknownMetadataNode { node: node.into() });
+            }
             hugr.metadata[node] = metadata.as_object().cloned();
         }
 
@@ -286,6 +353,9 @@ impl TryFrom<SerHugrV1> for Hugr {
                         .index()
                 }
             };
+            if offset >= hugr.graph.num_ports(node.pg_index(), dir) {
+                return Err(HUGRSerializationError::InvalidPortOffset { node, dir, offset });
+            }
             Ok(offset)
         };
         for [(src, from_offset), (dst, to_offset)] in edges {
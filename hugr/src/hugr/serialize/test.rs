@@ -17,6 +17,7 @@ use crate::std_extensions::logic::NotOp;
 use crate::types::type_param::{TypeArg, TypeParam};
 use crate::types::{FunctionType, PolyFuncType, SumType, Type, TypeBound};
 use crate::{type_row, OutgoingPort};
+use cool_asserts::assert_matches;
 use itertools::Itertools;
 use jsonschema::{Draft, JSONSchema};
 use lazy_static::lazy_static;
@@ -260,6 +261,89 @@ fn dfg_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// A small DFG with value and order edges, used to test loading corrupted data.
+fn load_test_hugr() -> Hugr {
+    let mut dfg = DFGBuilder::new(FunctionType::new_endo(type_row![BOOL_T])).unwrap();
+    let [w] = dfg.input_wires_arr();
+    let noop1 = dfg.add_dataflow_op(Noop { ty: BOOL_T }, [w]).unwrap();
+    let noop2 = dfg
+        .add_dataflow_op(Noop { ty: BOOL_T }, noop1.outputs())
+        .unwrap();
+    dfg.set_order(&noop1, &noop2);
+    dfg.finish_hugr_with_outputs(noop2.outputs(), &EMPTY_REG)
+        .unwrap()
+}
+
+#[test]
+fn load_validated() {
+    let hugr = load_test_hugr();
+    let bytes = serde_json::to_vec(&hugr).unwrap();
+    assert_eq!(Hugr::load_validated(&bytes, &EMPTY_REG).unwrap(), hugr);
+
+    let load_value = |v: &serde_json::Value| {
+        Hugr::load_validated(&serde_json::to_vec(v).unwrap(), &EMPTY_REG).unwrap_err()
+    };
+    let ser = serde_json::to_value(&hugr).unwrap();
+
+    let mut v = ser.clone();
+    v["edges"][0][0][1] = 1000.into();
+    assert_matches!(
+        load_value(&v),
+        HUGRSerializationError::InvalidPortOffset { offset: 1000, .. }
+    );
+
+    let mut v = ser.clone();
+    v["nodes"][1]["parent"] = 50.into();
+    assert_matches!(load_value(&v), HUGRSerializationError::UnknownParent { .. });
+
+    let mut v = ser.clone();
+    v["edges"][0][1][0] = (1u64 << 40).into();
+    assert_matches!(load_value(&v), HUGRSerializationError::Deserialize(_));
+
+    let mut v = ser.clone();
+    v["nodes"] = serde_json::json!([]);
+    assert_matches!(load_value(&v), HUGRSerializationError::NoRootNode);
+
+    let mut v = ser.clone();
+    v["metadata"]
+        .as_array_mut()
+        .unwrap()
+        .push(serde_json::Value::Null);
+    assert_matches!(
+        load_value(&v),
+        HUGRSerializationError::UnknownMetadataNode { .. }
+    );
+
+    let mut v = ser.clone();
+    v["version"] = "v0".into();
+    assert_matches!(load_value(&v), HUGRSerializationError::UnsupportedVersion);
+
+    // A well-formed graph that does not validate.
+    let mut v = ser;
+    v["edges"].as_array_mut().unwrap().pop();
+    assert_matches!(load_value(&v), HUGRSerializationError::InvalidHugr(_));
+}
+
+#[test]
+fn load_validated_fuzz() {
+    let bytes = serde_json::to_vec(&load_test_hugr()).unwrap();
+
+    // Every strict prefix is malformed.
+    for len in 0..bytes.len() {
+        assert!(Hugr::load_validated(&bytes[..len], &EMPTY_REG).is_err());
+    }
+
+    // Corrupting a single byte must never panic. Any HUGR that loads has been
+    // validated.
+    for i in 0..bytes.len() {
+        for &b in b"0179-[]{},:\"x" {
+            let mut corrupted = bytes.clone();
+            corrupted[i] = b;
+            let _ = Hugr::load_validated(&corrupted, &EMPTY_REG);
+        }
+    }
+}
+
 #[test]
 fn opaque_ops() -> Result<(), Box<dyn std::error::Error>> {
     let tp: Vec<Type> = vec![BOOL_T; 1];
@@ -20,8 +20,8 @@ use crate::ops::custom::{resolve_opaque_op, CustomOp};
 use crate::ops::validate::{ChildrenEdgeData, ChildrenValidationError, EdgeValidationError};
 use crate::ops::{FuncDefn, OpTag, OpTrait, OpType, ValidateOp};
 use crate::types::type_param::TypeParam;
-use crate::types::EdgeKind;
-use crate::{Direction, Hugr, Node, Port};
+use crate::types::{first_type_var, first_type_var_in_arg, EdgeKind, Type};
+use crate::{Direction, Hugr, Node, OutgoingPort, Port};
 
 use super::views::{HierarchyView, HugrView, SiblingGraph};
 use super::NodeType;
@@ -226,7 +226,7 @@ impl<'a, 'b> ValidationContext<'a, 'b> {
         }
 
         // Avoid double checking connected port types.
-        if dir == Direction::Incoming {
+        if port.is_incoming() {
             return Ok(());
         }
 
@@ -603,6 +603,86 @@ impl<'a, 'b> ValidationContext<'a, 'b> {
     }
 }
 
+/// Checks that every linear value produced by a child of `parent` is consumed
+/// exactly once.
+///
+/// This is a fast, focused subset of [`Hugr::validate`] for dataflow regions:
+/// no other invariants are checked.
+pub fn check_linearity(hugr: &impl HugrView, parent: Node) -> Result<(), LinearityError> {
+    for node in hugr.children(parent) {
+        for (port, typ) in hugr.out_value_types(node) {
+            if typ.copyable() {
+                continue;
+            }
+            match hugr.linked_inputs(node, port).count() {
+                0 => return Err(LinearityError::Dropped { node, port, typ }),
+                1 => {}
+                _ => return Err(LinearityError::Copied { node, port, typ }),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Errors returned by [`check_linearity`].
+#[derive(Debug, Clone, PartialEq, Error)]
+#[allow(missing_docs)]
+#[non_exhaustive]
+pub enum LinearityError {
+    /// A linear value is never used.
+    #[error("The linear value of type {typ} at port {port} of node {node} is dropped.")]
+    Dropped {
+        node: Node,
+        port: OutgoingPort,
+        typ: Type,
+    },
+    /// A linear value is used more than once.
+    #[error("The linear value of type {typ} at port {port} of node {node} is copied.")]
+    Copied {
+        node: Node,
+        port: OutgoingPort,
+        typ: Type,
+    },
+}
+
+/// Checks that no node of the HUGR refers to a type variable, i.e. that the
+/// HUGR is fully monomorphic and ready to be lowered to a concrete backend.
+///
+/// The type arguments of custom operations and the value and constant types
+/// of every node are checked. The declared signatures of [`FuncDefn`] and
+/// [`FuncDecl`] nodes bind their own variables and are not considered, but
+/// any use of those variables within a function body is reported.
+///
+/// Returns the first offending node with a [`SignatureError::NotMonomorphic`].
+///
+/// [`FuncDecl`]: crate::ops::FuncDecl
+pub fn check_monomorphic(hugr: &impl HugrView) -> Result<(), (Node, SignatureError)> {
+    for node in hugr.nodes() {
+        let op = hugr.get_optype(node);
+        let args = match op {
+            OpType::CustomOp(op) => op.args(),
+            _ => &[],
+        };
+        let static_types = [op.static_input(), op.static_output()]
+            .into_iter()
+            .filter_map(|kind| match kind {
+                Some(EdgeKind::Const(ty)) => Some(ty),
+                _ => None,
+            });
+        let var = args.iter().find_map(first_type_var_in_arg).or_else(|| {
+            op.dataflow_signature()
+                .map(Type::new_function)
+                .into_iter()
+                .chain(static_types)
+                .find_map(|ty| first_type_var(&ty))
+        });
+        if let Some(idx) = var {
+            return Err((node, SignatureError::NotMonomorphic { idx }));
+        }
+    }
+    Ok(())
+}
+
 /// Errors that can occur while validating a Hugr.
 #[derive(Debug, Clone, PartialEq, Error)]
 #[allow(missing_docs)]
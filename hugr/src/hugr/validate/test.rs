@@ -424,7 +424,10 @@ fn invalid_types() {
     );
     assert_eq!(
         validate_to_sig_error(too_many_type_args),
-        SignatureError::TypeArgMismatch(TypeArgError::WrongNumberArgs(2, 1))
+        SignatureError::MissingTypeArgs {
+            expected: 1,
+            got: 2
+        }
     );
 }
 
@@ -905,3 +908,74 @@ mod extension_tests {
         );
     }
 }
+
+#[test]
+fn linearity() {
+    use crate::extension::prelude::QB_T;
+
+    // A qubit that is never used.
+    let mut h = closed_dfg_root_hugr(FunctionType::new(type_row![QB_T, QB_T], type_row![QB_T]));
+    let [input, output] = h.get_io(h.root()).unwrap();
+    h.connect(input, 0, output, 0);
+    assert_eq!(
+        check_linearity(&h, h.root()),
+        Err(LinearityError::Dropped {
+            node: input,
+            port: 1.into(),
+            typ: QB_T
+        })
+    );
+
+    // A qubit that is used twice.
+    let mut h = closed_dfg_root_hugr(FunctionType::new(type_row![QB_T], type_row![QB_T, QB_T]));
+    let [input, output] = h.get_io(h.root()).unwrap();
+    h.connect(input, 0, output, 0);
+    h.connect(input, 0, output, 1);
+    assert_eq!(
+        check_linearity(&h, h.root()),
+        Err(LinearityError::Copied {
+            node: input,
+            port: 0.into(),
+            typ: QB_T
+        })
+    );
+
+    // Copyable values may be dropped and copied freely.
+    let mut h = closed_dfg_root_hugr(FunctionType::new(
+        type_row![BOOL_T],
+        type_row![BOOL_T, BOOL_T],
+    ));
+    let [input, output] = h.get_io(h.root()).unwrap();
+    h.connect(input, 0, output, 0);
+    h.connect(input, 0, output, 1);
+    assert_eq!(check_linearity(&h, h.root()), Ok(()));
+}
+
+#[test]
+fn monomorphic() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::ops::handle::NodeHandle;
+
+    let (h, _) = make_simple_hugr(2);
+    assert_eq!(check_monomorphic(&h), Ok(()));
+
+    // A polymorphic function whose body copies a value of the variable type.
+    let var = Type::new_var_use(0, TypeBound::Copyable);
+    let mut def = FunctionBuilder::new(
+        "copy",
+        PolyFuncType::new(
+            [TypeBound::Copyable.into()],
+            FunctionType::new(vec![var.clone()], vec![var.clone(), var.clone()]),
+        ),
+    )?;
+    let copy = def.add_dataflow_op(crate::extension::prelude::copy_op(var)?, def.input_wires())?;
+    let mut h = def.finish_prelude_hugr_with_outputs(copy.outputs())?;
+    let free_var = SignatureError::NotMonomorphic { idx: 0 };
+    let [input, output] = h.get_io(h.root()).unwrap();
+    assert_eq!(check_monomorphic(&h), Err((input, free_var.clone())));
+
+    // The instantiation of the copy op is reported by itself too.
+    h.remove_node(input);
+    h.remove_node(output);
+    assert_eq!(check_monomorphic(&h), Err((copy.handle().node(), free_var)));
+    Ok(())
+}
@@ -10,6 +10,7 @@ pub mod sibling_subgraph;
 #[cfg(test)]
 mod tests;
 
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::iter::Map;
 
 pub use self::petgraph::PetgraphWrapper;
@@ -24,12 +25,14 @@ use itertools::{Itertools, MapInto};
 use portgraph::render::{DotFormat, MermaidFormat};
 use portgraph::{multiportgraph, LinkView, MultiPortGraph, PortView};
 
-use super::{Hugr, HugrError, NodeMetadata, NodeMetadataMap, NodeType, DEFAULT_NODETYPE};
+use super::{Hugr, HugrError, HugrMut, NodeMetadata, NodeMetadataMap, NodeType, DEFAULT_NODETYPE};
 use crate::ops::handle::NodeHandle;
-use crate::ops::{OpParent, OpTag, OpTrait, OpType};
+use crate::ops::{NamedOp, OpParent, OpTag, OpTrait, OpType};
 
+use crate::types::type_param::TypeParam;
+use crate::types::{visit_custom_types, visit_custom_types_in_arg};
+use crate::types::{CustomType, PolyFuncType, Type, TypeArg, TypeRow};
 use crate::types::{EdgeKind, FunctionType};
-use crate::types::{PolyFuncType, Type};
 use crate::{Direction, IncomingPort, Node, OutgoingPort, Port};
 
 use itertools::Either;
@@ -111,6 +114,17 @@ pub trait HugrView: sealed::HugrInternals {
             .map(Into::into)
     }
 
+    /// Iterates over the ancestors of a node, from its parent up to the root.
+    fn ancestors(&self, node: Node) -> impl Iterator<Item = Node> + '_ {
+        std::iter::successors(self.get_parent(node), |&n| self.get_parent(n))
+    }
+
+    /// The depth of a node in the hierarchy, i.e. the number of its
+    /// [ancestors](HugrView::ancestors). The root has depth zero.
+    fn depth(&self, node: Node) -> usize {
+        self.ancestors(node).count()
+    }
+
     /// Returns the operation type of a node.
     #[inline]
     fn get_optype(&self, node: Node) -> &OpType {
@@ -293,6 +307,19 @@ pub trait HugrView: sealed::HugrInternals {
     /// Return iterator over the direct children of node.
     fn children(&self, node: Node) -> Self::Children<'_>;
 
+    /// Number of direct children of node.
+    #[inline]
+    fn child_count(&self, node: Node) -> usize {
+        self.children(node).count()
+    }
+
+    /// Returns `true` if the region defined by `node` contains no nodes other
+    /// than its input and output nodes (if any).
+    fn region_is_empty(&self, node: Node) -> bool {
+        let io_count = if self.get_io(node).is_some() { 2 } else { 0 };
+        self.child_count(node) <= io_count
+    }
+
     /// Iterates over neighbour nodes in the given direction.
     /// May contain duplicates if the graph has multiple links between nodes.
     fn neighbours(&self, node: Node, dir: Direction) -> Self::Neighbours<'_>;
@@ -314,6 +341,172 @@ pub trait HugrView: sealed::HugrInternals {
     /// Iterates over the input and output neighbours of the `node` in sequence.
     fn all_neighbours(&self, node: Node) -> Self::Neighbours<'_>;
 
+    /// Iterates over the nodes whose operation tag is contained in `tag`.
+    ///
+    /// E.g. `OpTag::Function` yields both function definitions and
+    /// declarations.
+    fn nodes_with_tag(&self, tag: OpTag) -> impl Iterator<Item = Node> + '_ {
+        self.nodes()
+            .filter(move |&n| tag.is_superset(self.get_optype(n).tag()))
+    }
+
+    /// Iterates over the [`crate::ops::Const`] nodes in the HUGR, with the
+    /// value of each and its type.
+    fn constants(&self) -> impl Iterator<Item = (Node, &crate::ops::Value, Type)> + '_ {
+        self.nodes().filter_map(move |n| {
+            let c = self.get_optype(n).as_const()?;
+            Some((n, c.value(), c.const_type()))
+        })
+    }
+
+    /// Iterates over the function definitions at the top level of a module.
+    ///
+    /// Returns an empty iterator if the root is not a [`crate::ops::Module`].
+    fn exported_functions(&self) -> impl Iterator<Item = Node> + '_ {
+        let root = self.root();
+        let is_module = self.get_optype(root).is_module();
+        self.children(root)
+            .filter(move |&n| is_module && self.get_optype(n).is_func_defn())
+    }
+
+    /// Iterates over the [exported functions][HugrView::exported_functions]
+    /// that are not the target of any [`crate::ops::Call`] outside of their
+    /// own definition.
+    ///
+    /// These are the entrypoints of a module. Recursive calls do not count as
+    /// references, and neither do [`crate::ops::LoadFunction`]s.
+    fn unreferenced_functions(&self) -> impl Iterator<Item = Node> + '_ {
+        self.exported_functions().filter(move |&func| {
+            self.static_targets(func)
+                .into_iter()
+                .flatten()
+                .filter(|&(n, _)| self.get_optype(n).is_call())
+                .all(|(call, _)| {
+                    std::iter::successors(Some(call), |&n| self.get_parent(n)).any(|n| n == func)
+                })
+        })
+    }
+
+    /// Iterates over the value edges between children of `parent`, as
+    /// `(source, source port, target, target port)`.
+    ///
+    /// Each edge is yielded once, from its source. Edges entering or leaving
+    /// the region, and order and static edges, are not included.
+    fn region_edges(
+        &self,
+        parent: Node,
+    ) -> impl Iterator<Item = (Node, OutgoingPort, Node, IncomingPort)> + '_ {
+        self.children(parent)
+            .flat_map(move |src| self.out_value_types(src).map(move |(p, _)| (src, p)))
+            .flat_map(move |(src, src_port)| {
+                self.linked_inputs(src, src_port)
+                    .filter(move |&(tgt, _)| self.get_parent(tgt) == Some(parent))
+                    .map(move |(tgt, tgt_port)| (src, src_port, tgt, tgt_port))
+            })
+    }
+
+    /// Iterates over every container node in the HUGR together with its direct
+    /// children, in breadth-first order from the root.
+    ///
+    /// A parent is always yielded before any of its descendants. Nodes
+    /// without children are not yielded as parents.
+    fn regions(&self) -> impl Iterator<Item = (Node, Vec<Node>)> + '_ {
+        let mut queue = VecDeque::from([self.root()]);
+        std::iter::from_fn(move || loop {
+            let parent = queue.pop_front()?;
+            let children = self.children(parent).collect_vec();
+            if children.is_empty() {
+                continue;
+            }
+            queue.extend(children.iter().copied());
+            return Some((parent, children));
+        })
+    }
+
+    /// Returns `true` if the two HUGRs are equal up to the numbering of their
+    /// nodes.
+    ///
+    /// Rather than a full graph isomorphism check, the nodes are matched by
+    /// a breadth-first traversal of the hierarchy, visiting the children of
+    /// each node in order. This suffices when the numbering changes but the
+    /// order of children does not, e.g. after a serialization round-trip.
+    /// Matched nodes must have the same operation and the same links.
+    /// Metadata is not compared.
+    fn is_isomorphic_to(&self, other: &impl HugrView) -> bool {
+        if self.node_count() != other.node_count() {
+            return false;
+        }
+        let ours = hierarchy_order(self);
+        let theirs = hierarchy_order(other);
+        if ours.len() != theirs.len() {
+            return false;
+        }
+        let node_map: HashMap<Node, Node> = ours.iter().copied().zip(theirs).collect();
+        node_map.iter().all(|(&n, &m)| {
+            self.get_optype(n) == other.get_optype(m)
+                && self.get_parent(n).map(|p| node_map[&p]) == other.get_parent(m)
+                && self.num_inputs(n) == other.num_inputs(m)
+                && self.num_outputs(n) == other.num_outputs(m)
+                && self.node_outputs(n).all(|port| {
+                    let ours = self
+                        .linked_inputs(n, port)
+                        .map(|(t, p)| (node_map[&t], p))
+                        .sorted()
+                        .collect_vec();
+                    let theirs = other.linked_inputs(m, port).sorted().collect_vec();
+                    ours == theirs
+                })
+        })
+    }
+
+    /// Iterates over the custom types used anywhere in the HUGR, each
+    /// reported once.
+    ///
+    /// This covers the port types of every node (including static and
+    /// function edges), the type arguments of extension operations, and the
+    /// definitions of type aliases. Custom types nested inside other types or
+    /// type arguments are included.
+    fn custom_types(&self) -> impl Iterator<Item = CustomType> + '_ {
+        let mut found = CustomTypes::default();
+        for node in self.nodes() {
+            let op = self.get_optype(node);
+            for port in self.all_node_ports(node) {
+                match op.port_kind(port) {
+                    Some(EdgeKind::Value(ty)) | Some(EdgeKind::Const(ty)) => found.add_type(&ty),
+                    Some(EdgeKind::Function(poly)) => found.add_poly_func(&poly),
+                    _ => {}
+                }
+            }
+            match op {
+                OpType::CustomOp(custom) => custom.args().iter().for_each(|a| found.add_arg(a)),
+                OpType::AliasDefn(alias) => found.add_type(&alias.definition),
+                _ => {}
+            }
+        }
+        found.found.into_iter()
+    }
+
+    /// A stable name for the operation at `node`, for diagnostics.
+    ///
+    /// Builtin operations use their canonical name, while extension
+    /// operations (resolved or opaque) are named `"{extension}.{op}"`.
+    #[inline]
+    fn op_name(&self, node: Node) -> String {
+        self.get_optype(node).name().to_string()
+    }
+
+    /// The variants of the Sum with which a [`DataflowBlock`] selects its
+    /// successor, one row per outgoing control-flow edge.
+    ///
+    /// Returns `None` if the node is not a [`DataflowBlock`].
+    ///
+    /// [`DataflowBlock`]: crate::ops::DataflowBlock
+    fn block_predicate_variants(&self, block: Node) -> Option<&[TypeRow]> {
+        self.get_optype(block)
+            .as_dataflow_block()
+            .map(|b| b.sum_rows.as_slice())
+    }
+
     /// Get the input and output child nodes of a dataflow parent.
     /// If the node isn't a dataflow parent, then return None
     #[inline]
@@ -360,6 +553,58 @@ pub trait HugrView: sealed::HugrInternals {
         }
     }
 
+    /// Copies the region rooted at `root` into a new standalone [`Hugr`].
+    ///
+    /// The operation at `root` becomes the root of the new hugr, and all its
+    /// descendants are copied along with their metadata and the sibling order
+    /// of the hierarchy. Edges between copied nodes are preserved.
+    ///
+    /// Edges crossing the boundary of the region are dropped. This includes
+    /// any edges connected to `root` itself, as well as non-local edges coming
+    /// from nodes outside the region. The resulting hugr may therefore fail
+    /// validation if the region depended on its surroundings.
+    ///
+    /// # Panics
+    ///
+    /// If `root` is not a valid node.
+    fn extract_hugr(&self, root: Node) -> Hugr
+    where
+        Self: Sized,
+    {
+        assert!(self.valid_node(root), "Invalid node {root}");
+        let mut extracted = Hugr::new(self.get_nodetype(root).clone());
+        let mut node_map = HashMap::from([(root, extracted.root())]);
+        // The descendants of `root` in the order they were added, so edges
+        // are also added in a deterministic order.
+        let mut descendants = Vec::new();
+        let mut queue = VecDeque::from([root]);
+        while let Some(node) = queue.pop_front() {
+            let new_node = node_map[&node];
+            extracted.overwrite_node_metadata(new_node, self.get_node_metadata(node).cloned());
+            for child in self.children(node) {
+                let new_child =
+                    extracted.add_node_with_parent(new_node, self.get_nodetype(child).clone());
+                node_map.insert(child, new_child);
+                descendants.push((child, new_child));
+                queue.push_back(child);
+            }
+        }
+
+        for (node, new_node) in descendants {
+            for src_port in self.node_outputs(node) {
+                for (tgt, tgt_port) in self.linked_inputs(node, src_port) {
+                    if tgt == root {
+                        continue;
+                    }
+                    if let Some(&new_tgt) = node_map.get(&tgt) {
+                        extracted.connect(new_node, src_port, new_tgt, tgt_port);
+                    }
+                }
+            }
+        }
+        extracted
+    }
+
     /// Return a wrapper over the view that can be used in petgraph algorithms.
     #[inline]
     fn as_petgraph(&self) -> PetgraphWrapper<'_, Self>
@@ -439,6 +684,12 @@ pub trait HugrView: sealed::HugrInternals {
         self.get_optype(node).dataflow_signature()
     }
 
+    /// The type of a value port of a node, or `None` if the node has no
+    /// dataflow signature or the port is not a value port.
+    fn port_type(&self, node: Node, port: impl Into<Port>) -> Option<Type> {
+        self.signature(node)?.port_type(port).cloned()
+    }
+
     /// Iterator over all outgoing ports that have Value type, along
     /// with corresponding types.
     fn value_types(&self, node: Node, dir: Direction) -> impl Iterator<Item = (Port, Type)> {
@@ -501,6 +752,59 @@ fn check_tag<Required: NodeHandle>(hugr: &impl HugrView, node: Node) -> Result<(
     Ok(())
 }
 
+/// The nodes of a HUGR in breadth-first order of the hierarchy, starting
+/// from the root.
+fn hierarchy_order<H: HugrView + ?Sized>(h: &H) -> Vec<Node> {
+    std::iter::once(h.root())
+        .chain(h.regions().flat_map(|(_, children)| children))
+        .collect()
+}
+
+/// Accumulator for [`HugrView::custom_types`], deduplicating by equality and
+/// keeping the order in which the types were first found.
+#[derive(Default)]
+struct CustomTypes {
+    found: Vec<CustomType>,
+    seen: HashSet<CustomType>,
+}
+
+impl CustomTypes {
+    fn add_custom(&mut self, custom: &CustomType) {
+        if self.seen.insert(custom.clone()) {
+            self.found.push(custom.clone());
+        }
+    }
+
+    fn add_type(&mut self, ty: &Type) {
+        visit_custom_types(ty, &mut |custom| self.add_custom(custom));
+    }
+
+    fn add_poly_func(&mut self, poly: &PolyFuncType) {
+        poly.params().iter().for_each(|p| self.add_param(p));
+        let body = poly.body();
+        body.input()
+            .iter()
+            .chain(body.output().iter())
+            .for_each(|t| self.add_type(t));
+    }
+
+    fn add_arg(&mut self, arg: &TypeArg) {
+        visit_custom_types_in_arg(arg, &mut |custom| self.add_custom(custom));
+    }
+
+    fn add_param(&mut self, param: &TypeParam) {
+        match param {
+            TypeParam::Opaque { ty } => {
+                self.add_custom(ty);
+                ty.args().iter().for_each(|a| self.add_arg(a));
+            }
+            TypeParam::List { param } => self.add_param(param),
+            TypeParam::Tuple { params } => params.iter().for_each(|p| self.add_param(p)),
+            _ => {}
+        }
+    }
+}
+
 impl RootTagged for Hugr {
     type RootHandle = Node;
 }
@@ -613,6 +917,11 @@ impl<T: AsRef<Hugr>> HugrView for T {
         self.as_ref().hierarchy.children(node.pg_index()).map_into()
     }
 
+    #[inline]
+    fn child_count(&self, node: Node) -> usize {
+        self.as_ref().hierarchy.child_count(node.pg_index())
+    }
+
     #[inline]
     fn neighbours(&self, node: Node, dir: Direction) -> Self::Neighbours<'_> {
         self.as_ref()
@@ -4,7 +4,6 @@
 use portgraph::render::{EdgeStyle, NodeStyle, PortStyle};
 use portgraph::{LinkView, NodeIndex, PortIndex, PortView};
 
-use crate::ops::NamedOp;
 use crate::types::EdgeKind;
 use crate::HugrView;
 
@@ -40,11 +39,11 @@ pub(super) fn node_style<H: HugrView + ?Sized>(
             NodeStyle::Box(format!(
                 "({ni}) {name}",
                 ni = n.index(),
-                name = h.get_optype(n.into()).name()
+                name = h.op_name(n.into())
             ))
         })
     } else {
-        Box::new(move |n| NodeStyle::Box(h.get_optype(n.into()).name().to_string()))
+        Box::new(move |n| NodeStyle::Box(h.op_name(n.into())))
     }
 }
 
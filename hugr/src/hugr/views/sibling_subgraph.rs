@@ -9,20 +9,22 @@
 //! while the former provide views for subgraphs within a single level of the
 //! hierarchy.
 
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::mem;
+use std::sync::OnceLock;
 
 use itertools::Itertools;
 use portgraph::algorithms::ConvexChecker;
-use portgraph::{view::Subgraph, Direction, PortView};
+use portgraph::{view::Subgraph, Direction, LinkView, PortView};
 use thiserror::Error;
 
 use crate::builder::{Container, FunctionBuilder};
+use crate::extension::ExtensionSet;
 use crate::hugr::{HugrMut, HugrView, RootTagged};
 use crate::ops::dataflow::DataflowOpTrait;
 use crate::ops::handle::{ContainerHandle, DataflowOpID};
 use crate::ops::{OpTag, OpTrait};
-use crate::types::{FunctionType, Type};
+use crate::types::{EdgeKind, FunctionType, Type, TypeRow};
 use crate::{Hugr, IncomingPort, Node, OutgoingPort, Port, SimpleReplacement};
 
 /// A non-empty convex subgraph of a HUGR sibling graph.
@@ -55,6 +57,8 @@ use crate::{Hugr, IncomingPort, Node, OutgoingPort, Port, SimpleReplacement};
 pub struct SiblingSubgraph {
     /// The nodes of the induced subgraph.
     nodes: Vec<Node>,
+    /// The common parent of the nodes.
+    parent: Node,
     /// The input ports of the subgraph.
     ///
     /// Grouped by input parameter. Each port must be unique and belong to a
@@ -65,6 +69,13 @@ pub struct SiblingSubgraph {
     /// Repeated ports are allowed and correspond to copying the output. Every
     /// port must belong to a node in `nodes`.
     outputs: Vec<(Node, OutgoingPort)>,
+    /// If the outputs have been deduplicated, the index in `outputs` of each
+    /// of the original outputs.
+    ///
+    /// See [`SiblingSubgraph::dedup_outputs`].
+    dedup_map: Option<Vec<usize>>,
+    /// The position of each node in `nodes`, built on first use.
+    node_index: OnceLock<HashMap<Node, usize>>,
 }
 
 /// The type of the incoming boundary of [`SiblingSubgraph`].
@@ -85,32 +96,69 @@ impl SiblingSubgraph {
     /// nodes of the root node. If you wish to create a subgraph from another
     /// root, wrap the `region` argument in a [`super::SiblingGraph`].
     ///
+    /// The boundary is read from the signatures of the input and output
+    /// children, so any dataflow parent is supported. For a
+    /// [`crate::ops::DataflowBlock`] root, the first output of the subgraph is
+    /// the branch predicate; for a [`crate::ops::TailLoop`] it is the
+    /// continue/break sum.
+    ///
     /// Wires connecting the input and output nodes are ignored. Note that due
     /// to this the resulting subgraph's signature may not match the signature
     /// of the dataflow parent.
     ///
     /// This will return an [`InvalidSubgraph::EmptySubgraph`] error if the
-    /// subgraph is empty.
+    /// subgraph is empty, an [`InvalidSubgraph::MalformedDfg`] error if the
+    /// root does not have valid input and output children, and an
+    /// [`InvalidSubgraph::NonDataflowIO`] error if the input or output node
+    /// is connected by a non-dataflow edge.
     pub fn try_new_dataflow_subgraph<H, Root>(dfg_graph: &H) -> Result<Self, InvalidSubgraph>
     where
         H: Clone + RootTagged<RootHandle = Root>,
         Root: ContainerHandle<ChildrenHandle = DataflowOpID>,
     {
-        let parent = dfg_graph.root();
-        let nodes = dfg_graph.children(parent).skip(2).collect_vec();
-        let (inputs, outputs) = get_input_output_ports(dfg_graph);
-
-        validate_subgraph(dfg_graph, &nodes, &inputs, &outputs)?;
-
-        if nodes.is_empty() {
-            Err(InvalidSubgraph::EmptySubgraph)
-        } else {
-            Ok(Self {
-                nodes,
-                inputs,
-                outputs,
-            })
+        Self::from_dataflow_region(dfg_graph)
+    }
+
+    /// A sibling subgraph containing the whole sibling graph of the root of
+    /// `graph`.
+    ///
+    /// If the root has input and output children, the subgraph is given by the
+    /// nodes between them and its boundary by their wires, as in
+    /// [`SiblingSubgraph::try_new_dataflow_subgraph`]. Otherwise all the
+    /// children are included and the boundary is computed as in
+    /// [`SiblingSubgraph::try_from_nodes`]; a whole sibling graph is always
+    /// convex, so no convexity check is performed. If you wish to create a
+    /// subgraph from another root, wrap the argument in a
+    /// [`super::SiblingGraph`].
+    ///
+    /// This will return an [`InvalidSubgraph::EmptySubgraph`] error if the
+    /// subgraph is empty.
+    pub fn try_from_sibling_graph(graph: &impl HugrView) -> Result<Self, InvalidSubgraph> {
+        let root = graph.root();
+        if graph.get_io(root).is_some() {
+            return Self::from_dataflow_region(graph);
+        }
+        let nodes = graph.children(root).collect_vec();
+        let (inputs, outputs) = get_nodes_boundary(graph, &nodes);
+
+        let parent = validate_subgraph(graph, &nodes, &inputs, &outputs)?;
+
+        Ok(Self::new_unchecked(inputs, outputs, nodes, parent))
+    }
+
+    /// The nodes between the input and output children of the root, with the
+    /// boundary given by their wires.
+    fn from_dataflow_region(graph: &impl HugrView) -> Result<Self, InvalidSubgraph> {
+        let parent = graph.root();
+        if graph.region_is_empty(parent) {
+            return Err(InvalidSubgraph::EmptySubgraph);
         }
+        let nodes = graph.children(parent).skip(2).collect_vec();
+        let (inputs, outputs) = get_input_output_ports(graph)?;
+
+        let parent = validate_subgraph(graph, &nodes, &inputs, &outputs)?;
+
+        Ok(Self::new_unchecked(inputs, outputs, nodes, parent))
     }
 
     /// Create a new convex sibling subgraph from input and output boundaries.
@@ -186,17 +234,13 @@ impl SiblingSubgraph {
         let subpg =
             Subgraph::new_subgraph(pg.clone(), combine_in_out(&inputs, &outputs).map(to_pg));
         let nodes = subpg.nodes_iter().map_into().collect_vec();
-        validate_subgraph(hugr, &nodes, &inputs, &outputs)?;
+        let parent = validate_subgraph(hugr, &nodes, &inputs, &outputs)?;
 
         if !subpg.is_convex_with_checker(checker) {
             return Err(InvalidSubgraph::NotConvex);
         }
 
-        Ok(Self {
-            nodes,
-            inputs,
-            outputs,
-        })
+        Ok(Self::new_unchecked(inputs, outputs, nodes, parent))
     }
 
     /// Create a subgraph from a set of nodes.
@@ -236,36 +280,60 @@ impl SiblingSubgraph {
         checker: &impl ConvexChecker,
     ) -> Result<Self, InvalidSubgraph> {
         let nodes = nodes.into();
-        let nodes_set = nodes.iter().copied().collect::<HashSet<_>>();
-        let incoming_edges = nodes
-            .iter()
-            .flat_map(|&n| hugr.node_inputs(n).map(move |p| (n, p)));
-        let outgoing_edges = nodes
-            .iter()
-            .flat_map(|&n| hugr.node_outputs(n).map(move |p| (n, p)));
-        let inputs = incoming_edges
-            .filter(|&(n, p)| {
-                if !hugr.is_linked(n, p) {
-                    return false;
-                }
-                let (out_n, _) = hugr.single_linked_output(n, p).unwrap();
-                !nodes_set.contains(&out_n)
-            })
-            // Every incoming edge is its own input.
-            .map(|p| vec![p])
+        let (inputs, outputs) = get_nodes_boundary(hugr, &nodes);
+        Self::try_new_with_checker(inputs, outputs, hugr, checker)
+    }
+
+    /// Create a subgraph containing a single node.
+    ///
+    /// The incoming and outgoing boundaries are given by the linked input and
+    /// output ports of the node, in port order. Every incoming edge is its own
+    /// input.
+    ///
+    /// A single node is always convex, so no convexity check is performed.
+    pub fn from_node(node: Node, hugr: &impl HugrView) -> Result<Self, InvalidSubgraph> {
+        let nodes = vec![node];
+        let inputs = hugr
+            .node_inputs(node)
+            .filter(|&p| hugr.is_linked(node, p))
+            .map(|p| vec![(node, p)])
             .collect_vec();
-        let outputs = outgoing_edges
-            .filter(|&(n, p)| {
-                if !hugr.is_linked(n, p) {
-                    return false;
-                }
-                // TODO: what if there are multiple outgoing edges?
-                // See https://github.com/CQCL/hugr/issues/518
-                let (in_n, _) = hugr.linked_ports(n, p).next().unwrap();
-                !nodes_set.contains(&in_n)
-            })
+        let outputs = hugr
+            .node_outputs(node)
+            .filter(|&p| hugr.is_linked(node, p))
+            .map(|p| (node, p))
             .collect_vec();
-        Self::try_new_with_checker(inputs, outputs, hugr, checker)
+        let parent = validate_subgraph(hugr, &nodes, &inputs, &outputs)?;
+
+        Ok(Self::new_unchecked(inputs, outputs, nodes, parent))
+    }
+
+    /// Create a sibling subgraph from its boundaries and nodes, without any
+    /// checks.
+    ///
+    /// This skips both the validation of the boundary and the convexity check
+    /// performed by [`SiblingSubgraph::try_new`], which is useful when the
+    /// subgraph is known to be valid by construction, e.g. in a tight loop.
+    ///
+    /// The caller must ensure that `nodes` are the non-empty set of children
+    /// of `parent` between the `inputs` and `outputs` boundaries, and that the
+    /// subgraph they define is convex. Otherwise, methods of the subgraph may
+    /// panic or return incorrect results, and rewrites built from it may
+    /// produce invalid hugrs.
+    pub fn new_unchecked(
+        inputs: IncomingPorts,
+        outputs: OutgoingPorts,
+        nodes: Vec<Node>,
+        parent: Node,
+    ) -> Self {
+        Self {
+            nodes,
+            parent,
+            inputs,
+            outputs,
+            dedup_map: None,
+            node_index: OnceLock::new(),
+        }
     }
 
     /// An iterator over the nodes in the subgraph.
@@ -278,6 +346,42 @@ impl SiblingSubgraph {
         self.nodes.len()
     }
 
+    /// Whether `node` is in the subgraph.
+    pub fn contains(&self, node: Node) -> bool {
+        self.node_position(node).is_some()
+    }
+
+    /// The index of `node` in [`SiblingSubgraph::nodes`], if it is in the
+    /// subgraph.
+    ///
+    /// The lookup table is built on the first query, after which each query
+    /// takes constant time.
+    pub fn node_position(&self, node: Node) -> Option<usize> {
+        let index = self.node_index.get_or_init(|| {
+            self.nodes
+                .iter()
+                .enumerate()
+                .map(|(i, &n)| (n, i))
+                .collect()
+        });
+        index.get(&node).copied()
+    }
+
+    /// Whether the subgraph shares no nodes with `other`.
+    pub fn is_disjoint(&self, other: &SiblingSubgraph) -> bool {
+        !other.nodes.iter().any(|&n| self.contains(n))
+    }
+
+    /// The nodes of the subgraph that are also in `other`, in the order of
+    /// [`SiblingSubgraph::nodes`].
+    pub fn overlapping_nodes(&self, other: &SiblingSubgraph) -> Vec<Node> {
+        self.nodes
+            .iter()
+            .filter(|&&n| other.contains(n))
+            .copied()
+            .collect()
+    }
+
     /// Returns the computed [`IncomingPorts`] of the subgraph.
     pub fn incoming_ports(&self) -> &IncomingPorts {
         &self.inputs
@@ -288,101 +392,275 @@ impl SiblingSubgraph {
         &self.outputs
     }
 
-    /// The signature of the subgraph.
-    pub fn signature(&self, hugr: &impl HugrView) -> FunctionType {
+    /// Collapse repeated outputs into a single entry.
+    ///
+    /// Repeated outputs copy a copyable value to several outgoing boundary
+    /// ports. Deduplicating them keeps the boundary compact, e.g. for hashing
+    /// or printing, and does not change the replacements that can be built
+    /// from the subgraph: a replacement output connected to a deduplicated
+    /// boundary port is connected to all the targets of that port. The
+    /// [`SiblingSubgraph::signature`] is that of the deduplicated boundary.
+    ///
+    /// The original outputs are recorded and can be restored with
+    /// [`SiblingSubgraph::expand_outputs`].
+    pub fn dedup_outputs(&mut self) {
+        if self.dedup_map.is_some() {
+            // Already deduplicated.
+            return;
+        }
+        let mut unique = Vec::new();
+        let mut positions: HashMap<(Node, OutgoingPort), usize> = HashMap::new();
+        let dedup_map = self
+            .outputs
+            .iter()
+            .map(|&port| {
+                *positions.entry(port).or_insert_with(|| {
+                    unique.push(port);
+                    unique.len() - 1
+                })
+            })
+            .collect();
+        self.outputs = unique;
+        self.dedup_map = Some(dedup_map);
+    }
+
+    /// Restore the outputs removed by [`SiblingSubgraph::dedup_outputs`], in
+    /// their original order.
+    ///
+    /// Does nothing if the outputs have not been deduplicated.
+    pub fn expand_outputs(&mut self) {
+        if let Some(dedup_map) = self.dedup_map.take() {
+            self.outputs = dedup_map.into_iter().map(|i| self.outputs[i]).collect();
+        }
+    }
+
+    /// The number of original outputs represented by each entry of
+    /// [`SiblingSubgraph::outgoing_ports`].
+    ///
+    /// This is one for every output unless the outputs have been deduplicated
+    /// with [`SiblingSubgraph::dedup_outputs`].
+    pub fn output_multiplicities(&self) -> Vec<usize> {
+        let mut counts = vec![1; self.outputs.len()];
+        if let Some(dedup_map) = &self.dedup_map {
+            counts.fill(0);
+            for &i in dedup_map {
+                counts[i] += 1;
+            }
+        }
+        counts
+    }
+
+    /// Reorder the input boundary of the subgraph, such that the `i`-th
+    /// input becomes the `perm[i]`-th input of the current boundary.
+    ///
+    /// The [`SiblingSubgraph::signature`] is updated accordingly.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidSubgraphBoundary::InvalidPermutation`] if `perm` is
+    /// not a permutation of the input indices.
+    pub fn permute_inputs(&mut self, perm: &[usize]) -> Result<(), InvalidSubgraphBoundary> {
+        check_permutation(perm, self.inputs.len())?;
+        self.inputs = perm.iter().map(|&i| self.inputs[i].clone()).collect();
+        Ok(())
+    }
+
+    /// Reorder the output boundary of the subgraph, such that the `i`-th
+    /// output becomes the `perm[i]`-th output of the current boundary.
+    ///
+    /// The [`SiblingSubgraph::signature`] is updated accordingly. If the
+    /// outputs have been deduplicated, [`SiblingSubgraph::expand_outputs`]
+    /// still restores the original outputs in their original order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InvalidSubgraphBoundary::InvalidPermutation`] if `perm` is
+    /// not a permutation of the output indices.
+    pub fn permute_outputs(&mut self, perm: &[usize]) -> Result<(), InvalidSubgraphBoundary> {
+        check_permutation(perm, self.outputs.len())?;
+        self.outputs = perm.iter().map(|&i| self.outputs[i]).collect();
+        if let Some(dedup_map) = &mut self.dedup_map {
+            let mut new_index = vec![0; perm.len()];
+            for (new, &old) in perm.iter().enumerate() {
+                new_index[old] = new;
+            }
+            for i in dedup_map.iter_mut() {
+                *i = new_index[*i];
+            }
+        }
+        Ok(())
+    }
+
+    /// The types of the input and output boundary ports, in the order of the
+    /// [`SiblingSubgraph::signature`].
+    ///
+    /// Useful to find which boundary port does not match a replacement.
+    pub fn boundary_types(&self, hugr: &impl HugrView) -> (Vec<Type>, Vec<Type>) {
         let input = self
             .inputs
             .iter()
             .map(|part| {
                 let &(n, p) = part.iter().next().expect("is non-empty");
-                let sig = hugr.signature(n).expect("must have dataflow signature");
-                sig.port_type(p).cloned().expect("must be dataflow edge")
+                hugr.port_type(n, p).expect("must be dataflow edge")
             })
-            .collect_vec();
+            .collect();
         let output = self
             .outputs
             .iter()
-            .map(|&(n, p)| {
-                let sig = hugr.signature(n).expect("must have dataflow signature");
-                sig.port_type(p).cloned().expect("must be dataflow edge")
-            })
-            .collect_vec();
+            .map(|&(n, p)| hugr.port_type(n, p).expect("must be dataflow edge"))
+            .collect();
+        (input, output)
+    }
+
+    /// The signature of the subgraph.
+    pub fn signature(&self, hugr: &impl HugrView) -> FunctionType {
+        let (input, output) = self.boundary_types(hugr);
         FunctionType::new(input, output)
     }
 
+    /// Whether the signature of the subgraph matches `pattern`.
+    ///
+    /// Extension requirements are not compared.
+    pub fn signature_matches(&self, hugr: &impl HugrView, pattern: &SignaturePattern) -> bool {
+        pattern.matches(&self.signature(hugr))
+    }
+
+    /// The signature a replacement DFG for the subgraph must have, and the
+    /// extensions it may require.
+    ///
+    /// The extension set is the union of the extension requirements of the
+    /// nodes in the subgraph, and is also the extension delta of the returned
+    /// [`FunctionType`], so the latter can be passed directly to a
+    /// [`crate::builder::DFGBuilder`]. A replacement may require fewer
+    /// extensions, but not more.
+    pub fn required_replacement_signature(
+        &self,
+        hugr: &impl HugrView,
+    ) -> (FunctionType, ExtensionSet) {
+        let extensions = ExtensionSet::union_over(
+            self.nodes
+                .iter()
+                .filter_map(|&n| hugr.signature(n))
+                .map(|sig| sig.extension_reqs),
+        );
+        let signature = self
+            .signature(hugr)
+            .with_extension_delta(extensions.clone());
+        (signature, extensions)
+    }
+
     /// The parent of the sibling subgraph.
-    pub fn get_parent(&self, hugr: &impl HugrView) -> Node {
-        hugr.get_parent(self.nodes[0]).expect("invalid subgraph")
+    pub fn parent(&self) -> Node {
+        self.parent
     }
 
-    /// Construct a [`SimpleReplacement`] to replace `self` with `replacement`.
+    /// The parent of the sibling subgraph.
+    #[deprecated(since = "0.4.0", note = "Use SiblingSubgraph::parent instead")]
+    pub fn get_parent(&self, _hugr: &impl HugrView) -> Node {
+        self.parent
+    }
+
+    /// The dataflow boundary ports of a `replacement` for `self`.
+    ///
+    /// Returns the outgoing dataflow ports of the replacement's Input node and
+    /// the incoming dataflow ports of its Output node, in signature order.
+    /// These correspond to the incoming and outgoing boundaries of the
+    /// subgraph, respectively, and can be used to construct custom port
+    /// mappings for [`SimpleReplacement::new_with_mapping`].
     ///
     /// `replacement` must be a hugr with DFG root and its signature must
     /// match the signature of the subgraph.
     ///
-    /// May return one of the following five errors
+    /// May return one of the following errors
     ///  - [`InvalidReplacement::InvalidDataflowGraph`]: the replacement
     ///    graph is not a [`crate::ops::OpTag::DataflowParent`]-rooted graph,
     ///  - [`InvalidReplacement::InvalidDataflowParent`]: the replacement does
     ///    not have an input and output node,
     ///  - [`InvalidReplacement::InvalidSignature`]: the signature of the
-    ///    replacement DFG does not match the subgraph signature, or
-    ///  - [`InvalidReplacement::NonConvexSubgraph`]: the sibling subgraph is not
-    ///    convex.
-    ///
-    /// At the moment we do not support state order edges. If any are found in
-    /// the replacement graph, this will panic.
-    pub fn create_simple_replacement(
+    ///    replacement DFG does not match the subgraph signature, see
+    ///    [`SiblingSubgraph::required_replacement_signature`], or
+    ///  - [`InvalidReplacement::UnsupportedOrderEdge`]: the input or output
+    ///    node of the replacement has a state order edge, which is not
+    ///    supported yet.
+    #[allow(clippy::type_complexity)]
+    pub fn replacement_io_ports(
         &self,
         hugr: &impl HugrView,
-        replacement: Hugr,
-    ) -> Result<SimpleReplacement, InvalidReplacement> {
+        replacement: &Hugr,
+    ) -> Result<(Vec<(Node, OutgoingPort)>, Vec<(Node, IncomingPort)>), InvalidReplacement> {
+        self.replacement_boundary(hugr, replacement, true)
+    }
+
+    /// Checks the boundary of `replacement` as in
+    /// [`SiblingSubgraph::replacement_io_ports`], only comparing its output
+    /// row with the subgraph's when `match_outputs` is set.
+    #[allow(clippy::type_complexity)]
+    fn replacement_boundary(
+        &self,
+        hugr: &impl HugrView,
+        replacement: &Hugr,
+        match_outputs: bool,
+    ) -> Result<(Vec<(Node, OutgoingPort)>, Vec<(Node, IncomingPort)>), InvalidReplacement> {
         let rep_root = replacement.root();
         let dfg_optype = replacement.get_optype(rep_root);
-        if !OpTag::Dfg.is_superset(dfg_optype.tag()) {
+        if !dfg_optype.is_dfg() {
             return Err(InvalidReplacement::InvalidDataflowGraph);
         }
         let Some([rep_input, rep_output]) = replacement.get_io(rep_root) else {
             return Err(InvalidReplacement::InvalidDataflowParent);
         };
-        if dfg_optype.dataflow_signature() != Some(self.signature(hugr)) {
+        let (expected, extensions) = self.required_replacement_signature(hugr);
+        let rep_signature = dfg_optype.dataflow_signature().unwrap_or_default();
+        if rep_signature.input() != expected.input()
+            || (match_outputs && rep_signature.output() != expected.output())
+            || !rep_signature.extension_reqs.is_subset(&extensions)
+        {
             return Err(InvalidReplacement::InvalidSignature);
         }
 
-        // TODO: handle state order edges. For now panic if any are present.
+        // TODO: handle state order edges. For now return an error if any are present.
         // See https://github.com/CQCL/hugr/discussions/432
         let rep_inputs = replacement.node_outputs(rep_input).map(|p| (rep_input, p));
         let rep_outputs = replacement.node_inputs(rep_output).map(|p| (rep_output, p));
-        let (rep_inputs, in_order_ports): (Vec<_>, Vec<_>) = rep_inputs.partition(|&(n, p)| {
-            replacement
-                .signature(n)
-                .is_some_and(|s| s.port_type(p).is_some())
-        });
-        let (rep_outputs, out_order_ports): (Vec<_>, Vec<_>) = rep_outputs.partition(|&(n, p)| {
-            replacement
-                .signature(n)
-                .is_some_and(|s| s.port_type(p).is_some())
-        });
+        let (rep_inputs, in_order_ports): (Vec<_>, Vec<_>) =
+            rep_inputs.partition(|&(n, p)| replacement.port_type(n, p).is_some());
+        let (rep_outputs, out_order_ports): (Vec<_>, Vec<_>) =
+            rep_outputs.partition(|&(n, p)| replacement.port_type(n, p).is_some());
 
         if combine_in_out(&vec![out_order_ports], &in_order_ports)
-            .any(|(n, p)| is_order_edge(&replacement, n, p))
+            .any(|(n, p)| is_order_edge(replacement, n, p))
         {
-            unimplemented!("Found state order edges in replacement graph");
+            return Err(InvalidReplacement::UnsupportedOrderEdge);
         }
 
-        let nu_inp = rep_inputs
-            .into_iter()
-            .zip_eq(&self.inputs)
-            .flat_map(|((rep_source_n, rep_source_p), self_targets)| {
-                replacement
-                    .linked_inputs(rep_source_n, rep_source_p)
-                    .flat_map(move |rep_target| {
-                        self_targets
-                            .iter()
-                            .map(move |&self_target| (rep_target, self_target))
-                    })
-            })
-            .collect();
+        Ok((rep_inputs, rep_outputs))
+    }
+
+    /// Construct a [`SimpleReplacement`] to replace `self` with `replacement`.
+    ///
+    /// `replacement` must be a hugr with DFG root and its signature must
+    /// match the signature of the subgraph.
+    ///
+    /// May return one of the following five errors
+    ///  - [`InvalidReplacement::InvalidDataflowGraph`]: the replacement
+    ///    graph is not a [`crate::ops::OpTag::DataflowParent`]-rooted graph,
+    ///  - [`InvalidReplacement::InvalidDataflowParent`]: the replacement does
+    ///    not have an input and output node,
+    ///  - [`InvalidReplacement::InvalidSignature`]: the signature of the
+    ///    replacement DFG does not match the subgraph signature, see
+    ///    [`SiblingSubgraph::required_replacement_signature`],
+    ///  - [`InvalidReplacement::NonConvexSubgraph`]: the sibling subgraph is not
+    ///    convex, or
+    ///  - [`InvalidReplacement::UnsupportedOrderEdge`]: the input or output
+    ///    node of the replacement has a state order edge.
+    pub fn create_simple_replacement(
+        &self,
+        hugr: &impl HugrView,
+        replacement: Hugr,
+    ) -> Result<SimpleReplacement, InvalidReplacement> {
+        let (rep_inputs, rep_outputs) = self.replacement_io_ports(hugr, &replacement)?;
+
+        let nu_inp = self.input_port_mapping(&replacement, rep_inputs);
         let nu_out = self
             .outputs
             .iter()
@@ -401,6 +679,112 @@ impl SiblingSubgraph {
         ))
     }
 
+    /// Construct a [`SimpleReplacement`] to replace `self` with `replacement`,
+    /// where `fanout` explicitly maps each output port of the replacement's
+    /// Output node to the external targets it should feed.
+    ///
+    /// Unlike [`SiblingSubgraph::create_simple_replacement`], the replacement
+    /// outputs need not correspond to the subgraph outputs, so the
+    /// replacement may produce copies of a value itself. Every port linked
+    /// to an output of the subgraph must be fed by exactly one replacement
+    /// output of the same type, and only copyable outputs may feed several
+    /// targets or none.
+    ///
+    /// May return the same errors as
+    /// [`SiblingSubgraph::create_simple_replacement`] (except that only the
+    /// input row of the signature is compared), or the following, reported
+    /// for the lowest replacement port or the first target in boundary order:
+    ///  - [`InvalidReplacement::InvalidReplacementPort`]: a key of `fanout`
+    ///    is not a replacement output,
+    ///  - [`InvalidReplacement::InvalidHostPort`]: a target in `fanout` is not
+    ///    linked to a subgraph output or has a different type, or a subgraph
+    ///    target is fed zero or several times,
+    ///  - [`InvalidReplacement::NonCopyableFanout`]: a replacement output of
+    ///    non-copyable type does not feed exactly one target.
+    pub fn create_simple_replacement_with_fanout(
+        &self,
+        hugr: &impl HugrView,
+        replacement: Hugr,
+        fanout: HashMap<IncomingPort, Vec<(Node, IncomingPort)>>,
+    ) -> Result<SimpleReplacement, InvalidReplacement> {
+        let (rep_inputs, rep_outputs) = self.replacement_boundary(hugr, &replacement, false)?;
+        let nu_inp = self.input_port_mapping(&replacement, rep_inputs);
+
+        // The external targets of the subgraph's outputs, in boundary order,
+        // which must all be fed.
+        let targets: Vec<(Node, IncomingPort)> = self
+            .outputs
+            .iter()
+            .flat_map(|&(n, p)| hugr.linked_inputs(n, p))
+            .collect();
+        let mut unfed: HashSet<(Node, IncomingPort)> = targets.iter().copied().collect();
+        // Report the lowest invalid port, independently of the map's order.
+        if let Some(&port) = fanout
+            .keys()
+            .filter(|&&p| !rep_outputs.iter().any(|&(_, rep_p)| rep_p == p))
+            .min()
+        {
+            let rep_output = replacement.get_io(replacement.root()).unwrap()[1];
+            return Err(InvalidReplacement::InvalidReplacementPort(
+                rep_output,
+                port.into(),
+            ));
+        }
+        let mut nu_out = HashMap::new();
+        for (rep_out, rep_out_port) in rep_outputs {
+            let rep_type = replacement.port_type(rep_out, rep_out_port);
+            let targets = fanout.get(&rep_out_port).map_or(&[][..], Vec::as_slice);
+            if targets.len() != 1 && !rep_type.as_ref().is_some_and(Type::copyable) {
+                return Err(InvalidReplacement::NonCopyableFanout(rep_out_port));
+            }
+            for &(target, target_port) in targets {
+                let target_type = hugr.port_type(target, target_port);
+                if !unfed.remove(&(target, target_port)) || target_type != rep_type {
+                    return Err(InvalidReplacement::InvalidHostPort(
+                        target,
+                        target_port.into(),
+                    ));
+                }
+                nu_out.insert((target, target_port), rep_out_port);
+            }
+        }
+        if let Some(&(target, target_port)) = targets.iter().find(|t| unfed.contains(t)) {
+            return Err(InvalidReplacement::InvalidHostPort(
+                target,
+                target_port.into(),
+            ));
+        }
+
+        Ok(SimpleReplacement::new(
+            self.clone(),
+            replacement,
+            nu_inp,
+            nu_out,
+        ))
+    }
+
+    /// Maps the targets of the replacement's inputs to the subgraph's input
+    /// ports they replace.
+    fn input_port_mapping(
+        &self,
+        replacement: &Hugr,
+        rep_inputs: Vec<(Node, OutgoingPort)>,
+    ) -> HashMap<(Node, IncomingPort), (Node, IncomingPort)> {
+        rep_inputs
+            .into_iter()
+            .zip_eq(&self.inputs)
+            .flat_map(|((rep_source_n, rep_source_p), self_targets)| {
+                replacement
+                    .linked_inputs(rep_source_n, rep_source_p)
+                    .flat_map(move |rep_target| {
+                        self_targets
+                            .iter()
+                            .map(move |&self_target| (rep_target, self_target))
+                    })
+            })
+            .collect()
+    }
+
     /// Create a new Hugr containing only the subgraph.
     ///
     /// The new Hugr will contain a [FuncDefn][crate::ops::FuncDefn] root
@@ -429,6 +813,176 @@ impl SiblingSubgraph {
     }
 }
 
+/// A slot in a [`SignaturePattern`], matching a single type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypePattern {
+    /// Matches any type.
+    Any,
+    /// Matches only the given type.
+    Exactly(Type),
+}
+
+impl TypePattern {
+    /// Whether `ty` matches the pattern.
+    pub fn matches(&self, ty: &Type) -> bool {
+        match self {
+            TypePattern::Any => true,
+            TypePattern::Exactly(expected) => expected == ty,
+        }
+    }
+}
+
+impl From<Type> for TypePattern {
+    fn from(ty: Type) -> Self {
+        TypePattern::Exactly(ty)
+    }
+}
+
+/// A pattern over the input and output rows of a signature, with wildcards.
+///
+/// A row left as `None` matches a row of any length. Otherwise the row must
+/// have exactly one type per slot, each matching its [`TypePattern`].
+///
+/// The default pattern matches every signature.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SignaturePattern {
+    /// The pattern for the input row.
+    pub input: Option<Vec<TypePattern>>,
+    /// The pattern for the output row.
+    pub output: Option<Vec<TypePattern>>,
+}
+
+impl SignaturePattern {
+    /// A pattern matching the given input and output slots.
+    pub fn new(
+        input: impl IntoIterator<Item = TypePattern>,
+        output: impl IntoIterator<Item = TypePattern>,
+    ) -> Self {
+        Self {
+            input: Some(input.into_iter().collect()),
+            output: Some(output.into_iter().collect()),
+        }
+    }
+
+    /// A pattern matching the given input slots and any output row.
+    pub fn with_input(input: impl IntoIterator<Item = TypePattern>) -> Self {
+        Self {
+            input: Some(input.into_iter().collect()),
+            output: None,
+        }
+    }
+
+    /// A pattern matching any input row and the given output slots.
+    pub fn with_output(output: impl IntoIterator<Item = TypePattern>) -> Self {
+        Self {
+            input: None,
+            output: Some(output.into_iter().collect()),
+        }
+    }
+
+    /// Whether `signature` matches the pattern. Extension requirements are
+    /// not compared.
+    pub fn matches(&self, signature: &FunctionType) -> bool {
+        fn row_matches(pattern: &Option<Vec<TypePattern>>, row: &TypeRow) -> bool {
+            pattern.as_ref().map_or(true, |slots| {
+                slots.len() == row.len() && slots.iter().zip(row.iter()).all(|(s, t)| s.matches(t))
+            })
+        }
+        row_matches(&self.input, signature.input()) && row_matches(&self.output, signature.output())
+    }
+}
+
+/// Find the convex subgraphs of `hugr` with at most `max_nodes` nodes whose
+/// boundary has the same input and output types as `signature`.
+///
+/// The candidates are the sets of sibling dataflow nodes that are connected by
+/// value edges, excluding Input and Output nodes and nodes with static or
+/// order edges leaving the set. The boundary of each candidate is ordered as in
+/// [`SiblingSubgraph::try_from_nodes`], with the nodes in index order.
+/// Extension requirements are not compared.
+///
+/// The number of connected sets grows exponentially with their size, so
+/// `max_nodes` should be kept small. A single [`TopoConvexChecker`] is shared
+/// between all the convexity checks.
+pub fn find_replaceable_subgraphs(
+    hugr: &impl HugrView,
+    signature: &FunctionType,
+    max_nodes: usize,
+) -> Vec<SiblingSubgraph> {
+    if max_nodes == 0 {
+        return Vec::new();
+    }
+    let checker = TopoConvexChecker::new(hugr);
+    let is_candidate = |n: Node| {
+        let op = hugr.get_optype(n);
+        hugr.get_parent(n)
+            .is_some_and(|p| hugr.get_optype(p).is_dataflow_parent())
+            && !matches!(op.tag(), OpTag::Input | OpTag::Output)
+            && hugr.all_node_ports(n).all(|p| {
+                !hugr.is_linked(n, p)
+                    || matches!(
+                        op.port_kind(p),
+                        Some(EdgeKind::Value(_)) | Some(EdgeKind::StateOrder)
+                    )
+            })
+    };
+    let value_neighbours = |n: Node| {
+        hugr.all_node_ports(n)
+            .filter(move |&p| !is_order_edge(hugr, n, p))
+            .flat_map(move |p| hugr.linked_ports(n, p))
+            .map(|(m, _)| m)
+            .filter(|&m| is_candidate(m))
+    };
+
+    // Grow connected node sets one neighbour at a time.
+    let mut seen: HashSet<BTreeSet<Node>> = HashSet::new();
+    let mut layer: Vec<BTreeSet<Node>> = hugr
+        .nodes()
+        .filter(|&n| is_candidate(n))
+        .map(|n| BTreeSet::from([n]))
+        .collect();
+    let mut found = Vec::new();
+    while !layer.is_empty() {
+        let mut next_layer = Vec::new();
+        for nodes in layer {
+            let closed = nodes.iter().all(|&n| {
+                hugr.all_node_ports(n)
+                    .filter(|&p| is_order_edge(hugr, n, p))
+                    .flat_map(|p| hugr.linked_ports(n, p))
+                    .all(|(m, _)| nodes.contains(&m))
+            });
+            if closed {
+                let subgraph = SiblingSubgraph::try_from_nodes_with_checker(
+                    nodes.iter().copied().collect_vec(),
+                    hugr,
+                    &checker,
+                );
+                if let Ok(subgraph) = subgraph {
+                    let sig = subgraph.signature(hugr);
+                    if sig.input == signature.input && sig.output == signature.output {
+                        found.push(subgraph);
+                    }
+                }
+            }
+            if nodes.len() >= max_nodes {
+                continue;
+            }
+            for neighbour in nodes.iter().flat_map(|&n| value_neighbours(n)) {
+                if nodes.contains(&neighbour) {
+                    continue;
+                }
+                let mut grown = nodes.clone();
+                grown.insert(neighbour);
+                if seen.insert(grown.clone()) {
+                    next_layer.push(grown);
+                }
+            }
+        }
+        layer = next_layer;
+    }
+    found
+}
+
 fn combine_in_out<'a>(
     inputs: &'a IncomingPorts,
     outputs: &'a OutgoingPorts,
@@ -444,15 +998,87 @@ fn combine_in_out<'a>(
 ///
 /// This can be used when constructing multiple sibling subgraphs to speed up
 /// convexity checking.
-pub struct TopoConvexChecker<'g, Base: 'g + HugrView>(
-    portgraph::algorithms::TopoConvexChecker<Base::Portgraph<'g>>,
-);
+pub struct TopoConvexChecker<'g, Base: 'g + HugrView> {
+    checker: portgraph::algorithms::TopoConvexChecker<Base::Portgraph<'g>>,
+    base: &'g Base,
+    // The index of each node in a topological order of the graph, computed
+    // on the first call to `span_cost`. `checker` computes the same order but
+    // does not expose it, and `ConvexChecker` is defined in `portgraph`.
+    topsort_ind: OnceLock<HashMap<Node, usize>>,
+}
 
 impl<'g, Base: HugrView> TopoConvexChecker<'g, Base> {
     /// Create a new convexity checker.
     pub fn new(base: &'g Base) -> Self {
         let pg = base.portgraph();
-        Self(portgraph::algorithms::TopoConvexChecker::new(pg))
+        Self {
+            checker: portgraph::algorithms::TopoConvexChecker::new(pg),
+            base,
+            topsort_ind: OnceLock::new(),
+        }
+    }
+
+    /// The index of each node in a topological order of the graph, starting
+    /// from the nodes without predecessors.
+    fn topsort_ind(&self) -> &HashMap<Node, usize> {
+        self.topsort_ind.get_or_init(|| {
+            let pg = self.base.portgraph();
+            let inputs = pg
+                .nodes_iter()
+                .filter(|&n| pg.input_neighbours(n).next().is_none());
+            let topsort: portgraph::algorithms::TopoSort<_> =
+                portgraph::algorithms::toposort(pg.clone(), inputs, Direction::Outgoing);
+            topsort.enumerate().map(|(i, n)| (n.into(), i)).collect()
+        })
+    }
+
+    /// The number of nodes outside of `nodes` that lie in its convex hull.
+    ///
+    /// These are the nodes that are both in the causal future and in the
+    /// causal past of the set, and that would therefore have to be added to
+    /// `nodes` to obtain a convex subgraph. Returns `Some(0)` if `nodes` is
+    /// convex.
+    ///
+    /// Returns `None` if one of `nodes` has no position in the topological
+    /// order: it is not in the graph, or it can only be reached through a
+    /// cycle that is not reachable from a source node, such as basic blocks
+    /// of a CFG that only branch to each other.
+    ///
+    /// Only the nodes that lie between the first and last node of the set in
+    /// a topological order of the graph are traversed. The order is computed
+    /// on the first call.
+    pub fn span_cost(&self, nodes: &[Node]) -> Option<usize> {
+        let topsort_ind = self.topsort_ind();
+        let nodes: HashSet<Node> = nodes.iter().copied().collect();
+        let indices: Vec<usize> = nodes
+            .iter()
+            .map(|n| topsort_ind.get(n).copied())
+            .collect::<Option<_>>()?;
+        let Some((min_ind, max_ind)) = indices.into_iter().minmax().into_option() else {
+            return Some(0);
+        };
+        let in_range = |n: &Node| {
+            topsort_ind
+                .get(n)
+                .is_some_and(|i| (min_ind..=max_ind).contains(i))
+        };
+
+        // Nodes outside the set reachable from it in the given direction.
+        let reachable = |dir: Direction| {
+            let mut visited = HashSet::new();
+            let mut stack: Vec<Node> = nodes.iter().copied().collect();
+            while let Some(node) = stack.pop() {
+                for next in self.base.neighbours(node, dir).filter(in_range) {
+                    if !nodes.contains(&next) && visited.insert(next) {
+                        stack.push(next);
+                    }
+                }
+            }
+            visited
+        };
+        let future = reachable(Direction::Outgoing);
+        let past = reachable(Direction::Incoming);
+        Some(future.intersection(&past).count())
     }
 }
 
@@ -463,7 +1089,7 @@ impl<'g, Base: HugrView> ConvexChecker for TopoConvexChecker<'g, Base> {
         inputs: impl IntoIterator<Item = portgraph::PortIndex>,
         outputs: impl IntoIterator<Item = portgraph::PortIndex>,
     ) -> bool {
-        self.0.is_convex(nodes, inputs, outputs)
+        self.checker.is_convex(nodes, inputs, outputs)
     }
 }
 
@@ -472,13 +1098,10 @@ impl<'g, Base: HugrView> ConvexChecker for TopoConvexChecker<'g, Base> {
 /// If the array is empty or a port does not exist, returns `None`.
 fn get_edge_type<H: HugrView, P: Into<Port> + Copy>(hugr: &H, ports: &[(Node, P)]) -> Option<Type> {
     let &(n, p) = ports.first()?;
-    let edge_t = hugr.signature(n)?.port_type(p)?.clone();
+    let edge_t = hugr.port_type(n, p)?;
     ports
         .iter()
-        .all(|&(n, p)| {
-            hugr.signature(n)
-                .is_some_and(|s| s.port_type(p) == Some(&edge_t))
-        })
+        .all(|&(n, p)| hugr.port_type(n, p).as_ref() == Some(&edge_t))
         .then_some(edge_t)
 }
 
@@ -488,12 +1111,14 @@ fn get_edge_type<H: HugrView, P: Into<Port> + Copy>(hugr: &H, ports: &[(Node, P)
 /// to nodes within the subgraph and are linked to at least one node outside of the subgraph.
 /// This does NOT check convexity proper, i.e. whether the set of nodes form a convex
 /// induced graph.
+///
+/// Returns the common parent of the nodes.
 fn validate_subgraph<H: HugrView>(
     hugr: &H,
     nodes: &[Node],
     inputs: &IncomingPorts,
     outputs: &OutgoingPorts,
-) -> Result<(), InvalidSubgraph> {
+) -> Result<Node, InvalidSubgraph> {
     // Copy of the nodes for fast lookup.
     let node_set = nodes.iter().copied().collect::<HashSet<_>>();
 
@@ -502,9 +1127,9 @@ fn validate_subgraph<H: HugrView>(
         return Err(InvalidSubgraph::EmptySubgraph);
     }
     // Check all nodes share parent
-    if !nodes.iter().map(|&n| hugr.get_parent(n)).all_equal() {
+    let Ok(Some(parent)) = nodes.iter().map(|&n| hugr.get_parent(n)).all_equal_value() else {
         return Err(InvalidSubgraph::NoSharedParent);
-    }
+    };
 
     // Check there are no linked "other" ports
     if combine_in_out(inputs, outputs).any(|(n, p)| is_order_edge(hugr, n, p)) {
@@ -567,27 +1192,69 @@ fn validate_subgraph<H: HugrView>(
         Err(InvalidSubgraphBoundary::MismatchedTypes(i))?;
     };
 
-    Ok(())
+    Ok(parent)
+}
+
+/// The boundary of the subgraph induced by `nodes`.
+///
+/// Every linked incoming port whose source is outside `nodes` is its own
+/// input, and every linked outgoing port with a target outside `nodes` is an
+/// output, in node and then port order.
+fn get_nodes_boundary<H: HugrView>(hugr: &H, nodes: &[Node]) -> (IncomingPorts, OutgoingPorts) {
+    let nodes_set = nodes.iter().copied().collect::<HashSet<_>>();
+    let incoming_edges = nodes
+        .iter()
+        .flat_map(|&n| hugr.node_inputs(n).map(move |p| (n, p)));
+    let outgoing_edges = nodes
+        .iter()
+        .flat_map(|&n| hugr.node_outputs(n).map(move |p| (n, p)));
+    let inputs = incoming_edges
+        .filter(|&(n, p)| {
+            if !hugr.is_linked(n, p) {
+                return false;
+            }
+            let (out_n, _) = hugr.single_linked_output(n, p).unwrap();
+            !nodes_set.contains(&out_n)
+        })
+        // Every incoming edge is its own input.
+        .map(|p| vec![p])
+        .collect_vec();
+    let outputs = outgoing_edges
+        .filter(|&(n, p)| {
+            if !hugr.is_linked(n, p) {
+                return false;
+            }
+            // TODO: what if there are multiple outgoing edges?
+            // See https://github.com/CQCL/hugr/issues/518
+            let (in_n, _) = hugr.linked_ports(n, p).next().unwrap();
+            !nodes_set.contains(&in_n)
+        })
+        .collect_vec();
+    (inputs, outputs)
 }
 
-fn get_input_output_ports<H: HugrView>(hugr: &H) -> (IncomingPorts, OutgoingPorts) {
-    let [inp, out] = hugr.get_io(hugr.root()).expect("invalid DFG");
+fn get_input_output_ports<H: HugrView>(
+    hugr: &H,
+) -> Result<(IncomingPorts, OutgoingPorts), InvalidSubgraph> {
+    let [inp, out] = hugr
+        .get_io(hugr.root())
+        .ok_or(InvalidSubgraph::MalformedDfg)?;
     if has_other_edge(hugr, inp, Direction::Outgoing) {
-        unimplemented!("Non-dataflow output not supported at input node")
+        return Err(InvalidSubgraph::NonDataflowIO(inp));
     }
     let dfg_inputs = hugr
         .get_optype(inp)
         .as_input()
-        .unwrap()
+        .ok_or(InvalidSubgraph::MalformedDfg)?
         .signature()
         .output_ports();
     if has_other_edge(hugr, out, Direction::Incoming) {
-        unimplemented!("Non-dataflow input not supported at output node")
+        return Err(InvalidSubgraph::NonDataflowIO(out));
     }
     let dfg_outputs = hugr
         .get_optype(out)
         .as_output()
-        .unwrap()
+        .ok_or(InvalidSubgraph::MalformedDfg)?
         .signature()
         .input_ports();
 
@@ -608,7 +1275,7 @@ fn get_input_output_ports<H: HugrView>(hugr: &H) -> (IncomingPorts, OutgoingPort
         .into_iter()
         .filter_map(|p| hugr.linked_outputs(out, p).find(|&(n, _)| n != inp))
         .collect();
-    (inputs, outputs)
+    Ok((inputs, outputs))
 }
 
 /// Whether a port is linked to a state order edge.
@@ -639,6 +1306,43 @@ pub enum InvalidReplacement {
     /// SiblingSubgraph is not convex.
     #[error("SiblingSubgraph is not convex.")]
     NonConvexSubgraph,
+    /// A port of the replacement graph is missing from the port mapping, or
+    /// mapped although it is not on the replacement boundary.
+    #[error(
+        "Port mapping is inconsistent with the replacement boundary at (node {0:?}, port {1:?})."
+    )]
+    InvalidReplacementPort(Node, Port),
+    /// A port of the host hugr is missing from the port mapping, or mapped
+    /// although it is not on the subgraph boundary.
+    #[error(
+        "Port mapping is inconsistent with the subgraph boundary at (node {0:?}, port {1:?})."
+    )]
+    InvalidHostPort(Node, Port),
+    /// State order edges at the boundary of the replacement are not supported.
+    #[error("State order edges at the boundary of the replacement graph are not supported.")]
+    UnsupportedOrderEdge,
+    /// A replacement output of non-copyable type does not feed exactly one
+    /// target.
+    #[error(
+        "Replacement output {0:?} has a non-copyable type but does not feed exactly one target."
+    )]
+    NonCopyableFanout(IncomingPort),
+}
+
+/// Check that `perm` is a permutation of `0..len`.
+fn check_permutation(perm: &[usize], len: usize) -> Result<(), InvalidSubgraphBoundary> {
+    let mut seen = vec![false; len];
+    let valid = perm.len() == len
+        && perm
+            .iter()
+            .all(|&i| i < len && !std::mem::replace(&mut seen[i], true));
+    if !valid {
+        return Err(InvalidSubgraphBoundary::InvalidPermutation {
+            perm: perm.to_vec(),
+            len,
+        });
+    }
+    Ok(())
 }
 
 /// Errors that can occur while constructing a [`SiblingSubgraph`].
@@ -657,6 +1361,13 @@ pub enum InvalidSubgraph {
     /// An invalid boundary port was found.
     #[error("Invalid boundary port.")]
     InvalidBoundary(#[from] InvalidSubgraphBoundary),
+    /// The dataflow region does not have valid input and output children.
+    #[error("The dataflow region does not have valid input and output nodes.")]
+    MalformedDfg,
+    /// The input or output node of a dataflow region has a linked
+    /// non-dataflow port.
+    #[error("Non-dataflow edges are not supported at the input or output node {0:?}.")]
+    NonDataflowIO(Node),
 }
 
 /// Errors that can occur while constructing a [`SiblingSubgraph`].
@@ -678,6 +1389,10 @@ pub enum InvalidSubgraphBoundary {
     /// Different types in a partition of the input boundary.
     #[error("The partition {0} in the input boundary has ports with different types.")]
     MismatchedTypes(usize),
+    /// A boundary reordering is not a permutation of the boundary indices.
+    #[error("{perm:?} is not a permutation of the {len} boundary indices.")]
+    #[allow(missing_docs)]
+    InvalidPermutation { perm: Vec<usize>, len: usize },
 }
 
 #[cfg(test)]
@@ -687,48 +1402,25 @@ mod tests {
     use cool_asserts::assert_matches;
 
     use crate::extension::PRELUDE_REGISTRY;
-    use crate::utils::test_quantum_extension::cx_gate;
+    use crate::utils::test_quantum_extension::{self, cx_gate, h_gate};
     use crate::{
         builder::{
-            BuildError, DFGBuilder, Dataflow, DataflowHugr, DataflowSubContainer, HugrBuilder,
-            ModuleBuilder,
+            BuildError, CFGBuilder, DFGBuilder, Dataflow, DataflowHugr, DataflowSubContainer,
+            HugrBuilder, ModuleBuilder,
         },
         extension::{
             prelude::{BOOL_T, QB_T},
-            EMPTY_REG,
+            ExtensionSet, EMPTY_REG,
         },
         hugr::views::{HierarchyView, SiblingGraph},
-        ops::handle::{DfgID, FuncID, NodeHandle},
+        ops::handle::{BasicBlockID, DfgID, FuncID, NodeHandle},
+        ops::{Noop, Value},
         std_extensions::logic::{test::and_op, NotOp},
         type_row,
     };
 
     use super::*;
 
-    impl SiblingSubgraph {
-        /// A sibling subgraph from a HUGR.
-        ///
-        /// The subgraph is given by the sibling graph of the root. If you wish to
-        /// create a subgraph from another root, wrap the argument `region` in a
-        /// [`super::SiblingGraph`].
-        ///
-        /// This will return an [`InvalidSubgraph::EmptySubgraph`] error if the
-        /// subgraph is empty.
-        fn from_sibling_graph(sibling_graph: &impl HugrView) -> Result<Self, InvalidSubgraph> {
-            let root = sibling_graph.root();
-            let nodes = sibling_graph.children(root).collect_vec();
-            if nodes.is_empty() {
-                Err(InvalidSubgraph::EmptySubgraph)
-            } else {
-                Ok(Self {
-                    nodes,
-                    inputs: Vec::new(),
-                    outputs: Vec::new(),
-                })
-            }
-        }
-    }
-
     fn build_hugr() -> Result<(Hugr, Node), BuildError> {
         let mut mod_builder = ModuleBuilder::new();
         let func = mod_builder.declare(
@@ -782,17 +1474,31 @@ mod tests {
         Ok((hugr, func_id.node()))
     }
 
+    #[test]
+    fn region_edges() {
+        let (hugr, func_root) = build_hugr().unwrap();
+        let [inp, out] = hugr.get_io(func_root).unwrap();
+        let cx = hugr.output_neighbours(inp).next().unwrap();
+
+        let edges = hugr.region_edges(func_root).collect_vec();
+        assert_eq!(edges.len(), 5);
+        assert!(edges.iter().all_unique());
+        assert_eq!(edges.iter().filter(|&&(src, ..)| src == inp).count(), 3);
+        assert_eq!(edges.iter().filter(|&&(src, ..)| src == cx).count(), 2);
+        assert!(edges.iter().all(|&(_, _, tgt, _)| tgt == cx || tgt == out));
+
+        // The module root only contains the function, with no value edges.
+        assert_eq!(hugr.region_edges(hugr.root()).count(), 0);
+    }
+
     #[test]
     fn construct_subgraph() -> Result<(), InvalidSubgraph> {
         let (hugr, func_root) = build_hugr().unwrap();
         let sibling_graph: SiblingGraph<'_> = SiblingGraph::try_new(&hugr, func_root).unwrap();
-        let from_root = SiblingSubgraph::from_sibling_graph(&sibling_graph)?;
+        let from_root = SiblingSubgraph::try_from_sibling_graph(&sibling_graph)?;
         let region: SiblingGraph<'_> = SiblingGraph::try_new(&hugr, func_root).unwrap();
-        let from_region = SiblingSubgraph::from_sibling_graph(&region)?;
-        assert_eq!(
-            from_root.get_parent(&sibling_graph),
-            from_region.get_parent(&sibling_graph)
-        );
+        let from_region = SiblingSubgraph::try_from_sibling_graph(&region)?;
+        assert_eq!(from_root.parent(), from_region.parent());
         assert_eq!(
             from_root.signature(&sibling_graph),
             from_region.signature(&sibling_graph)
@@ -800,6 +1506,88 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn sibling_graph_signature() -> Result<(), InvalidSubgraph> {
+        let (hugr, func_root) = build_3not_hugr().unwrap();
+        let func: SiblingGraph<'_> = SiblingGraph::try_new(&hugr, func_root).unwrap();
+        let sub = SiblingSubgraph::try_from_sibling_graph(&func)?;
+        assert_eq!(sub.nodes().len(), 3);
+        assert_eq!(
+            sub.signature(&func),
+            func.get_function_type().unwrap().body().clone()
+        );
+
+        // The module root has no input and output nodes: the whole region is
+        // included, with an empty boundary.
+        let module = SiblingSubgraph::try_from_sibling_graph(&hugr)?;
+        assert_eq!(module.nodes(), [func_root]);
+        assert_eq!(module.signature(&hugr), FunctionType::new_endo(type_row![]));
+        Ok(())
+    }
+
+    #[test]
+    fn dedup_outputs() -> Result<(), InvalidSubgraph> {
+        let (hugr, func_root) = build_3not_hugr().unwrap();
+        let [inp, _] = hugr.get_io(func_root).unwrap();
+        let not1 = hugr.output_neighbours(inp).exactly_one().unwrap();
+        let out = (not1, OutgoingPort::from(0));
+        let mut sub = SiblingSubgraph::try_new(
+            vec![vec![(not1, IncomingPort::from(0))]],
+            vec![out, out, out],
+            &hugr,
+        )?;
+        assert_eq!(sub.output_multiplicities(), [1, 1, 1]);
+
+        sub.dedup_outputs();
+        assert_eq!(sub.outgoing_ports(), &[out]);
+        assert_eq!(sub.output_multiplicities(), [3]);
+        assert_eq!(
+            sub.signature(&hugr),
+            FunctionType::new_endo(type_row![BOOL_T])
+        );
+
+        sub.expand_outputs();
+        assert_eq!(sub.outgoing_ports(), &[out, out, out]);
+        assert_eq!(sub.output_multiplicities(), [1, 1, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn permute_boundary() -> Result<(), Box<dyn Error>> {
+        let mut dfg = DFGBuilder::new(FunctionType::new_endo(type_row![BOOL_T, QB_T]))?;
+        let [b, q] = dfg.input_wires_arr();
+        let b = dfg.add_dataflow_op(Noop::new(BOOL_T), [b])?;
+        let q = dfg.add_dataflow_op(Noop::new(QB_T), [q])?;
+        let hugr = dfg.finish_prelude_hugr_with_outputs(b.outputs().chain(q.outputs()))?;
+
+        let mut sub = SiblingSubgraph::try_from_nodes([b.node(), q.node()], &hugr)?;
+        assert_eq!(
+            sub.signature(&hugr),
+            FunctionType::new_endo(type_row![BOOL_T, QB_T])
+        );
+        sub.permute_inputs(&[1, 0])?;
+        assert_eq!(
+            sub.signature(&hugr),
+            FunctionType::new(type_row![QB_T, BOOL_T], type_row![BOOL_T, QB_T])
+        );
+        sub.permute_outputs(&[1, 0])?;
+        assert_eq!(
+            sub.signature(&hugr),
+            FunctionType::new_endo(type_row![QB_T, BOOL_T])
+        );
+
+        for perm in [&[0][..], &[0, 0], &[0, 2]] {
+            assert_eq!(
+                sub.permute_inputs(perm),
+                Err(InvalidSubgraphBoundary::InvalidPermutation {
+                    perm: perm.to_vec(),
+                    len: 2
+                })
+            );
+        }
+        Ok(())
+    }
+
     #[test]
     fn construct_simple_replacement() -> Result<(), InvalidSubgraph> {
         let (mut hugr, func_root) = build_hugr().unwrap();
@@ -823,6 +1611,291 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn non_dataflow_io() {
+        let (mut hugr, func_root) = build_hugr().unwrap();
+        let [inp, _] = hugr.get_io(func_root).unwrap();
+        let cx = hugr.output_neighbours(inp).next().unwrap();
+        hugr.add_other_edge(inp, cx);
+        let func: SiblingGraph<'_, FuncID<true>> = SiblingGraph::try_new(&hugr, func_root).unwrap();
+        assert_eq!(
+            SiblingSubgraph::try_new_dataflow_subgraph(&func).unwrap_err(),
+            InvalidSubgraph::NonDataflowIO(inp)
+        );
+    }
+
+    #[test]
+    fn extract_func_hugr() {
+        let (hugr, func_root) = build_hugr().unwrap();
+        let func = hugr.extract_hugr(func_root);
+
+        assert_eq!(func.get_optype(func.root()), hugr.get_optype(func_root));
+        assert_eq!(
+            func.get_function_type(),
+            Some(FunctionType::new_endo(type_row![QB_T, QB_T, QB_T]).into())
+        );
+        // Input, output and the CX gate.
+        assert_eq!(func.node_count(), 4);
+        assert_eq!(func.edge_count(), 5);
+        func.validate(&test_quantum_extension::REG).unwrap();
+    }
+
+    #[test]
+    fn find_replaceable() {
+        let (hugr, _) = build_3not_hugr().unwrap();
+        let not_sig = FunctionType::new_endo(type_row![BOOL_T]);
+        // Every contiguous chain of NOT gates.
+        assert_eq!(find_replaceable_subgraphs(&hugr, &not_sig, 3).len(), 6);
+        assert_eq!(find_replaceable_subgraphs(&hugr, &not_sig, 2).len(), 5);
+        assert_eq!(find_replaceable_subgraphs(&hugr, &not_sig, 0).len(), 0);
+
+        let (hugr, func_root) = build_hugr().unwrap();
+        let [inp, _] = hugr.get_io(func_root).unwrap();
+        let cx = hugr.output_neighbours(inp).next().unwrap();
+        let cx_sig = FunctionType::new_endo(type_row![QB_T, QB_T]);
+        let found = find_replaceable_subgraphs(&hugr, &cx_sig, 2);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].nodes(), [cx]);
+    }
+
+    #[test]
+    fn dataflow_block_subgraph() -> Result<(), Box<dyn Error>> {
+        let mut cfg = CFGBuilder::new(FunctionType::new_endo(type_row![BOOL_T]))?;
+        let mut entry = cfg.simple_entry_builder(type_row![BOOL_T], 1, ExtensionSet::new())?;
+        let [b] = entry.input_wires_arr();
+        let not = entry.add_dataflow_op(NotOp, [b])?;
+        let pred = entry.add_load_value(Value::unary_unit_sum());
+        let entry = entry.finish_with_outputs(pred, not.outputs())?;
+        let exit = cfg.exit_block();
+        cfg.branch(&entry, 0, &exit)?;
+        let hugr = cfg.finish_prelude_hugr()?;
+
+        let block: SiblingGraph<'_, BasicBlockID> = SiblingGraph::try_new(&hugr, entry.node())?;
+        let sub = SiblingSubgraph::try_new_dataflow_subgraph(&block)?;
+        // The constant, its load and the NOT gate.
+        assert_eq!(sub.nodes().len(), 3);
+        // The branch predicate is the first output.
+        assert_eq!(
+            sub.signature(&hugr),
+            FunctionType::new(type_row![BOOL_T], vec![Type::new_unit_sum(1), BOOL_T])
+        );
+        assert_eq!(sub.outgoing_ports()[0].0, pred.node());
+        Ok(())
+    }
+
+    #[test]
+    fn disjoint_subgraphs() {
+        let (hugr, func_root) = build_3not_hugr().unwrap();
+        let [inp, _] = hugr.get_io(func_root).unwrap();
+        let not1 = hugr.output_neighbours(inp).exactly_one().ok().unwrap();
+        let not2 = hugr.output_neighbours(not1).exactly_one().ok().unwrap();
+        let not3 = hugr.output_neighbours(not2).exactly_one().ok().unwrap();
+
+        let first = SiblingSubgraph::try_from_nodes([not1, not2], &hugr).unwrap();
+        let last = SiblingSubgraph::try_from_nodes([not2, not3], &hugr).unwrap();
+        let single = SiblingSubgraph::from_node(not3, &hugr).unwrap();
+
+        assert!(!first.is_disjoint(&last));
+        assert_eq!(first.overlapping_nodes(&last), [not2]);
+        assert_eq!(last.overlapping_nodes(&single), [not3]);
+        assert!(first.is_disjoint(&single));
+        assert!(single.is_disjoint(&first));
+        assert!(first.overlapping_nodes(&single).is_empty());
+    }
+
+    #[test]
+    fn unchecked_subgraph() {
+        let (hugr, func_root) = build_3not_hugr().unwrap();
+        let [inp, _] = hugr.get_io(func_root).unwrap();
+        let not1 = hugr.output_neighbours(inp).exactly_one().ok().unwrap();
+        let not2 = hugr.output_neighbours(not1).exactly_one().ok().unwrap();
+
+        let checked = SiblingSubgraph::try_from_nodes([not1, not2], &hugr).unwrap();
+        let unchecked = SiblingSubgraph::new_unchecked(
+            checked.incoming_ports().clone(),
+            checked.outgoing_ports().clone(),
+            checked.nodes().to_vec(),
+            func_root,
+        );
+        assert_eq!(unchecked.nodes(), checked.nodes());
+        assert_eq!(unchecked.signature(&hugr), checked.signature(&hugr));
+        assert_eq!(unchecked.parent(), func_root);
+    }
+
+    #[test]
+    fn subgraph_parent() {
+        let (hugr, func_root) = build_3not_hugr().unwrap();
+        let [inp, _] = hugr.get_io(func_root).unwrap();
+        let not1 = hugr.output_neighbours(inp).exactly_one().ok().unwrap();
+        assert_eq!(
+            SiblingSubgraph::from_node(not1, &hugr).unwrap().parent(),
+            func_root
+        );
+        // The root has no parent to be a subgraph of.
+        assert_matches!(
+            SiblingSubgraph::from_node(hugr.root(), &hugr),
+            Err(InvalidSubgraph::NoSharedParent)
+        );
+    }
+
+    #[test]
+    fn node_position() {
+        let (hugr, func_root) = build_3not_hugr().unwrap();
+        let [inp, out] = hugr.get_io(func_root).unwrap();
+        let not1 = hugr.output_neighbours(inp).exactly_one().ok().unwrap();
+        let not2 = hugr.output_neighbours(not1).exactly_one().ok().unwrap();
+        let not3 = hugr.output_neighbours(not2).exactly_one().ok().unwrap();
+
+        let sub = SiblingSubgraph::try_from_nodes([not2, not1], &hugr).unwrap();
+        assert!(sub.contains(not1));
+        assert!(!sub.contains(not3));
+        assert!(!sub.contains(out));
+        assert_eq!(sub.node_position(not3), None);
+        for (i, &n) in sub.nodes().iter().enumerate() {
+            assert_eq!(sub.clone().node_position(n), Some(i));
+        }
+    }
+
+    #[test]
+    fn span_cost() {
+        let (hugr, func_root) = build_3not_hugr().unwrap();
+        let [inp, _] = hugr.get_io(func_root).unwrap();
+        let not1 = hugr.output_neighbours(inp).exactly_one().ok().unwrap();
+        let not2 = hugr.output_neighbours(not1).exactly_one().ok().unwrap();
+        let not3 = hugr.output_neighbours(not2).exactly_one().ok().unwrap();
+
+        let checker = TopoConvexChecker::new(&hugr);
+        assert_eq!(checker.span_cost(&[]), Some(0));
+        assert_eq!(checker.span_cost(&[not1, not2]), Some(0));
+        assert_eq!(checker.span_cost(&[not1, not3]), Some(1));
+        assert_eq!(checker.span_cost(&[inp, not3]), Some(2));
+        assert_eq!(
+            checker.span_cost(&[not1, Node::from(portgraph::NodeIndex::new(1000))]),
+            None
+        );
+    }
+
+    #[test]
+    fn span_cost_cycle() -> Result<(), Box<dyn Error>> {
+        let block_sig = || FunctionType::new_endo(type_row![]);
+        let mut cfg = CFGBuilder::new(block_sig())?;
+        let mut entry = cfg.simple_entry_builder(type_row![], 1, ExtensionSet::new())?;
+        let pred = entry.add_load_value(Value::unary_unit_sum());
+        let entry = entry.finish_with_outputs(pred, [])?;
+        // Two blocks that are only reachable from each other.
+        let [b1, b2] = [(); 2].map(|_| {
+            let mut block = cfg.simple_block_builder(block_sig(), 1).unwrap();
+            let pred = block.add_load_value(Value::unary_unit_sum());
+            block.finish_with_outputs(pred, []).unwrap()
+        });
+        let exit = cfg.exit_block();
+        cfg.branch(&entry, 0, &exit)?;
+        cfg.branch(&b1, 0, &b2)?;
+        cfg.branch(&b2, 0, &b1)?;
+        let hugr = cfg.finish_prelude_hugr()?;
+
+        let checker = TopoConvexChecker::new(&hugr);
+        assert_eq!(checker.span_cost(&[entry.node(), exit.node()]), Some(0));
+        assert_eq!(checker.span_cost(&[b1.node()]), None);
+        assert_eq!(checker.span_cost(&[entry.node(), b2.node()]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn replacement_signature() -> Result<(), Box<dyn Error>> {
+        let xa = ExtensionSet::singleton(&"A".try_into().unwrap());
+        let sig = FunctionType::new_endo(type_row![BOOL_T]);
+        let sig_a = sig.clone().with_extension_delta(xa.clone());
+        let mut outer = DFGBuilder::new(sig_a.clone())?;
+        let inner = outer.dfg_builder(sig_a.clone(), None, outer.input_wires())?;
+        let inner_wires = inner.input_wires();
+        let inner = inner.finish_with_outputs(inner_wires)?;
+        let not = outer.add_dataflow_op(NotOp, inner.outputs())?;
+        let hugr = outer.finish_hugr_with_outputs(not.outputs(), &EMPTY_REG)?;
+
+        let sub = SiblingSubgraph::try_from_nodes([inner.node(), not.node()], &hugr)?;
+        assert_eq!(
+            sub.required_replacement_signature(&hugr),
+            (sig_a.clone(), xa.clone())
+        );
+        let only_not = SiblingSubgraph::from_node(not.node(), &hugr)?;
+        assert_eq!(
+            only_not.required_replacement_signature(&hugr),
+            (sig.clone(), ExtensionSet::new())
+        );
+
+        // A replacement built from the required signature, or requiring fewer
+        // extensions, is accepted. One requiring more extensions is not.
+        let identity = |sig| -> Result<Hugr, BuildError> {
+            let b = DFGBuilder::new(sig)?;
+            let wires = b.input_wires();
+            b.finish_hugr_with_outputs(wires, &EMPTY_REG)
+        };
+        let (required, _) = sub.required_replacement_signature(&hugr);
+        assert!(sub
+            .create_simple_replacement(&hugr, identity(required)?)
+            .is_ok());
+        assert!(sub.create_simple_replacement(&hugr, identity(sig)?).is_ok());
+        assert_matches!(
+            only_not.create_simple_replacement(&hugr, identity(sig_a)?),
+            Err(InvalidReplacement::InvalidSignature)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn replacement_with_order_edge() -> Result<(), Box<dyn Error>> {
+        let (hugr, func_root) = build_hugr()?;
+        let [inp, _] = hugr.get_io(func_root).unwrap();
+        let cx = hugr.output_neighbours(inp).next().unwrap();
+        let sub = SiblingSubgraph::from_node(cx, &hugr)?;
+
+        let mut builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB_T, QB_T]))?;
+        let [rep_inp, _] = builder.io();
+        let cx2 = builder.add_dataflow_op(cx_gate(), builder.input_wires())?;
+        builder.add_other_wire(rep_inp, cx2.node());
+        let replacement = builder.finish_prelude_hugr_with_outputs(cx2.outputs())?;
+
+        assert_eq!(
+            sub.create_simple_replacement(&hugr, replacement)
+                .unwrap_err(),
+            InvalidReplacement::UnsupportedOrderEdge
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn replace_single_node() -> Result<(), Box<dyn Error>> {
+        let (mut hugr, func_root) = build_hugr()?;
+        let [inp, _] = hugr.get_io(func_root).unwrap();
+        let cx = hugr.output_neighbours(inp).next().unwrap();
+        let sub = SiblingSubgraph::from_node(cx, &hugr)?;
+        assert_eq!(sub.nodes(), [cx]);
+        assert_eq!(
+            sub.signature(&hugr),
+            FunctionType::new_endo(type_row![QB_T, QB_T])
+        );
+
+        // CX decomposed as a reversed CX conjugated by Hadamards.
+        let decomposition = {
+            let mut builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB_T, QB_T]))?;
+            let [q0, q1] = builder.input_wires_arr();
+            let [q0] = builder.add_dataflow_op(h_gate(), [q0])?.outputs_arr();
+            let [q1] = builder.add_dataflow_op(h_gate(), [q1])?.outputs_arr();
+            let [q1, q0] = builder.add_dataflow_op(cx_gate(), [q1, q0])?.outputs_arr();
+            let [q0] = builder.add_dataflow_op(h_gate(), [q0])?.outputs_arr();
+            let [q1] = builder.add_dataflow_op(h_gate(), [q1])?.outputs_arr();
+            builder.finish_prelude_hugr_with_outputs([q0, q1])?
+        };
+
+        let rep = sub.create_simple_replacement(&hugr, decomposition)?;
+        hugr.apply_rewrite(rep)?;
+        assert_eq!(hugr.node_count(), 9); // Module + Def + In + 4 * H + CX + Out
+        hugr.update_validate(&test_quantum_extension::REG)?;
+
+        Ok(())
+    }
+
     #[test]
     fn test_signature() -> Result<(), InvalidSubgraph> {
         let (hugr, dfg) = build_hugr().unwrap();
@@ -834,6 +1907,10 @@ mod tests {
             sub.signature(&func),
             FunctionType::new_endo(type_row![QB_T, QB_T])
         );
+        assert_eq!(
+            sub.boundary_types(&func),
+            (vec![QB_T, QB_T], vec![QB_T, QB_T])
+        );
         Ok(())
     }
 
@@ -841,7 +1918,7 @@ mod tests {
     fn construct_simple_replacement_invalid_signature() -> Result<(), InvalidSubgraph> {
         let (hugr, dfg) = build_hugr().unwrap();
         let func: SiblingGraph<'_> = SiblingGraph::try_new(&hugr, dfg).unwrap();
-        let sub = SiblingSubgraph::from_sibling_graph(&func)?;
+        let sub = SiblingSubgraph::try_from_sibling_graph(&func)?;
 
         let empty_dfg = {
             let builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB_T])).unwrap();
@@ -976,6 +2053,116 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn replacement_with_fanout() -> Result<(), Box<dyn Error>> {
+        // A single NOT whose output is copied to both outputs.
+        let two_bits = type_row![BOOL_T, BOOL_T];
+        let mut builder = DFGBuilder::new(FunctionType::new(type_row![BOOL_T], two_bits.clone()))?;
+        let [b] = builder.input_wires_arr();
+        let [b] = builder.add_dataflow_op(NotOp, [b])?.outputs_arr();
+        let mut hugr = builder.finish_prelude_hugr_with_outputs([b, b])?;
+        let [_, out] = hugr.get_io(hugr.root()).unwrap();
+        let not = hugr.input_neighbours(out).next().unwrap();
+        let sub = SiblingSubgraph::from_node(not, &hugr)?;
+
+        // The replacement computes each copy with its own NOT.
+        let two_nots = {
+            let mut builder = DFGBuilder::new(FunctionType::new(type_row![BOOL_T], two_bits))?;
+            let [b] = builder.input_wires_arr();
+            let [b0] = builder.add_dataflow_op(NotOp, [b])?.outputs_arr();
+            let [b1] = builder.add_dataflow_op(NotOp, [b])?.outputs_arr();
+            builder.finish_prelude_hugr_with_outputs([b0, b1])?
+        };
+
+        // A target fed twice is rejected.
+        let fanout = HashMap::from_iter([
+            (0.into(), vec![(out, 0.into()), (out, 1.into())]),
+            (1.into(), vec![(out, 1.into())]),
+        ]);
+        assert_eq!(
+            sub.create_simple_replacement_with_fanout(&hugr, two_nots.clone(), fanout)
+                .err(),
+            Some(InvalidReplacement::InvalidHostPort(
+                out,
+                IncomingPort::from(1).into()
+            ))
+        );
+
+        // Errors are reported for the first target in boundary order, and the
+        // lowest invalid replacement port.
+        assert_eq!(
+            sub.create_simple_replacement_with_fanout(&hugr, two_nots.clone(), HashMap::new())
+                .err(),
+            Some(InvalidReplacement::InvalidHostPort(
+                out,
+                IncomingPort::from(0).into()
+            ))
+        );
+        let fanout = HashMap::from_iter([(7.into(), vec![]), (5.into(), vec![])]);
+        let rep_output = two_nots.get_io(two_nots.root()).unwrap()[1];
+        assert_eq!(
+            sub.create_simple_replacement_with_fanout(&hugr, two_nots.clone(), fanout)
+                .err(),
+            Some(InvalidReplacement::InvalidReplacementPort(
+                rep_output,
+                IncomingPort::from(5).into()
+            ))
+        );
+
+        let fanout = HashMap::from_iter([
+            (0.into(), vec![(out, 0.into())]),
+            (1.into(), vec![(out, 1.into())]),
+        ]);
+        let rep = sub.create_simple_replacement_with_fanout(&hugr, two_nots, fanout)?;
+        hugr.apply_rewrite(rep)?;
+        hugr.update_validate(&PRELUDE_REGISTRY)?;
+        assert_eq!(hugr.input_neighbours(out).count(), 2);
+
+        // A linear output must feed exactly one target.
+        let (hugr, func_root) = build_hugr()?;
+        let [inp, _] = hugr.get_io(func_root).unwrap();
+        let cx = hugr.output_neighbours(inp).next().unwrap();
+        let sub = SiblingSubgraph::from_node(cx, &hugr)?;
+        let identity = {
+            let builder = DFGBuilder::new(FunctionType::new_endo(type_row![QB_T, QB_T]))?;
+            let inputs = builder.input_wires();
+            builder.finish_prelude_hugr_with_outputs(inputs)?
+        };
+        let fanout = HashMap::from_iter([(0.into(), hugr.linked_inputs(cx, 0).collect_vec())]);
+        assert_eq!(
+            sub.create_simple_replacement_with_fanout(&hugr, identity, fanout)
+                .err(),
+            Some(InvalidReplacement::NonCopyableFanout(1.into()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn signature_pattern() -> Result<(), Box<dyn Error>> {
+        let (hugr, func_root) = build_hugr()?;
+        let [inp, _] = hugr.get_io(func_root).unwrap();
+        let cx = hugr.output_neighbours(inp).next().unwrap();
+        let sub = SiblingSubgraph::from_node(cx, &hugr)?;
+
+        let qb = || TypePattern::Exactly(QB_T);
+        assert!(sub.signature_matches(&hugr, &SignaturePattern::default()));
+        assert!(sub.signature_matches(
+            &hugr,
+            &SignaturePattern::with_input([qb(), TypePattern::Any])
+        ));
+        assert!(sub.signature_matches(
+            &hugr,
+            &SignaturePattern::new([TypePattern::Any, qb()], [qb(), qb()])
+        ));
+        // Wrong type, or wrong number of slots.
+        assert!(!sub.signature_matches(
+            &hugr,
+            &SignaturePattern::with_input([BOOL_T.into(), TypePattern::Any])
+        ));
+        assert!(!sub.signature_matches(&hugr, &SignaturePattern::with_output([qb()])));
+        Ok(())
+    }
+
     #[test]
     fn edge_both_output_and_copy() {
         // https://github.com/CQCL/hugr/issues/518
@@ -1,12 +1,16 @@
+use itertools::Itertools;
 use portgraph::PortOffset;
 use rstest::{fixture, rstest};
 
 use crate::{
-    builder::{BuildError, BuildHandle, Container, DFGBuilder, Dataflow, DataflowHugr},
-    extension::prelude::QB_T,
+    builder::{
+        BuildError, BuildHandle, Container, DFGBuilder, Dataflow, DataflowHugr,
+        DataflowSubContainer, HugrBuilder, ModuleBuilder,
+    },
+    extension::{prelude::QB_T, PRELUDE_REGISTRY},
     ops::{
-        handle::{DataflowOpID, NodeHandle},
-        Value,
+        handle::{DataflowOpID, FuncID, NodeHandle},
+        OpTag, Value,
     },
     type_row,
     types::FunctionType,
@@ -90,6 +94,19 @@ fn mermaid_string(#[case] test_name: &str, #[case] h: Hugr) {
     insta::assert_snapshot!(test_name, h.mermaid_string());
 }
 
+#[rstest]
+fn port_type(sample_hugr: (Hugr, BuildHandle<DataflowOpID>, BuildHandle<DataflowOpID>)) {
+    use crate::{IncomingPort, OutgoingPort};
+    let (h, n1, _) = sample_hugr;
+
+    assert_eq!(h.port_type(n1.node(), IncomingPort::from(1)), Some(QB_T));
+    assert_eq!(h.port_type(n1.node(), OutgoingPort::from(0)), Some(QB_T));
+    // Order edge ports have no value type.
+    assert_eq!(h.port_type(n1.node(), OutgoingPort::from(2)), None);
+    let [input, _] = h.get_io(h.root()).unwrap();
+    assert_eq!(h.port_type(input, IncomingPort::from(0)), None);
+}
+
 #[rstest]
 fn all_ports(sample_hugr: (Hugr, BuildHandle<DataflowOpID>, BuildHandle<DataflowOpID>)) {
     use itertools::Itertools;
@@ -221,3 +238,207 @@ fn test_dataflow_ports_only() {
         ]
     )
 }
+
+#[test]
+fn nodes_with_tag() {
+    let mut module = ModuleBuilder::new();
+    let sig = FunctionType::new_endo(type_row![QB_T]);
+    for name in ["f", "g", "h"] {
+        let f = module.define_function(name, sig.clone().into()).unwrap();
+        let inputs = f.input_wires();
+        f.finish_with_outputs(inputs).unwrap();
+    }
+    module.declare("ext", sig.into()).unwrap();
+    let h = module.finish_prelude_hugr().unwrap();
+
+    assert_eq!(h.nodes_with_tag(OpTag::FuncDefn).count(), 3);
+    assert_eq!(h.nodes_with_tag(OpTag::Function).count(), 4);
+    assert_eq!(h.nodes_with_tag(OpTag::Input).count(), 3);
+    assert_eq!(h.nodes_with_tag(OpTag::Any).count(), h.node_count());
+}
+
+#[test]
+fn unreferenced_functions() {
+    let mut module = ModuleBuilder::new();
+    let sig = FunctionType::new_endo(type_row![QB_T]);
+    let mut define = |name, callees: &[FuncID<true>], recursive: bool| {
+        let mut f = module.define_function(name, sig.clone().into()).unwrap();
+        let mut q = f.input_wires().exactly_one().unwrap();
+        let callees = callees
+            .iter()
+            .copied()
+            .chain(recursive.then(|| f.container_node().into()));
+        for callee in callees {
+            q = f
+                .call(&callee, &[], [q], &PRELUDE_REGISTRY)
+                .unwrap()
+                .out_wire(0);
+        }
+        FuncID::from(f.finish_with_outputs([q]).unwrap().node())
+    };
+    let leaf = define("leaf", &[], true);
+    let helper = define("helper", &[leaf], false);
+    let main = define("main", &[helper, leaf], false);
+    let unused = define("unused", &[], true);
+    module.declare("ext", sig.into()).unwrap();
+    let h = module.finish_prelude_hugr().unwrap();
+
+    assert_eq!(
+        h.exported_functions().collect_vec(),
+        [leaf.node(), helper.node(), main.node(), unused.node()]
+    );
+    assert_eq!(
+        h.unreferenced_functions().collect_vec(),
+        [main.node(), unused.node()]
+    );
+
+    // Only a module exports functions.
+    let main_view = h.extract_hugr(main.node());
+    assert_eq!(main_view.exported_functions().count(), 0);
+    // Extraction is deterministic.
+    assert_eq!(h.extract_hugr(main.node()), main_view);
+}
+
+#[test]
+fn custom_types() {
+    use crate::extension::{prelude::USIZE_T, ExtensionRegistry, PRELUDE};
+    use crate::std_extensions::collections::{self, list_type};
+
+    let list_t = list_type(USIZE_T);
+    let dfg = DFGBuilder::new(FunctionType::new(
+        vec![QB_T, list_t.clone()],
+        vec![QB_T, list_t],
+    ))
+    .unwrap();
+    let [q, l] = dfg.input_wires_arr();
+    let reg = ExtensionRegistry::try_new([PRELUDE.to_owned(), collections::EXTENSION.to_owned()])
+        .unwrap();
+    let h = dfg.finish_hugr_with_outputs([q, l], &reg).unwrap();
+
+    // The element type inside the list is found, and each type appears once.
+    let names = h
+        .custom_types()
+        .map(|t| t.name().to_string())
+        .sorted()
+        .collect_vec();
+    assert_eq!(names, ["List", "qubit", "usize"]);
+}
+
+#[test]
+fn op_name() {
+    use crate::extension::prelude::BOOL_T;
+    use crate::std_extensions::logic::{test::and_op, LOGIC_REG};
+
+    let (h, n1, _) = sample_hugr();
+    assert_eq!(h.op_name(n1.node()), "test.quantum.CX");
+    assert_eq!(h.op_name(h.root()), "DFG");
+
+    let mut dfg = DFGBuilder::new(FunctionType::new(
+        type_row![BOOL_T, BOOL_T],
+        type_row![BOOL_T],
+    ))
+    .unwrap();
+    let and = dfg.add_dataflow_op(and_op(), dfg.input_wires()).unwrap();
+    let h = dfg
+        .finish_hugr_with_outputs(and.outputs(), &LOGIC_REG)
+        .unwrap();
+    assert_eq!(h.op_name(and.node()), "logic.And");
+}
+
+#[test]
+fn regions() -> Result<(), BuildError> {
+    let mut module = ModuleBuilder::new();
+    let mut f = module.define_function("main", FunctionType::new_endo(type_row![QB_T]).into())?;
+    let [q] = f.input_wires_arr();
+    let mut outer = f.dfg_builder(FunctionType::new_endo(type_row![QB_T]), None, [q])?;
+    let [q] = outer.input_wires_arr();
+    let inner = outer.dfg_builder(FunctionType::new_endo(type_row![QB_T]), None, [q])?;
+    let [q] = inner.input_wires_arr();
+    let inner = inner.finish_with_outputs([q])?;
+    let outer = outer.finish_with_outputs(inner.outputs())?;
+    let f = f.finish_with_outputs(outer.outputs())?;
+    let h = module.finish_prelude_hugr()?;
+
+    let regions = h.regions().collect_vec();
+    let parents = regions.iter().map(|(p, _)| *p).collect_vec();
+    assert_eq!(parents, [h.root(), f.node(), outer.node(), inner.node()]);
+    for (parent, children) in &regions {
+        assert_eq!(children, &h.children(*parent).collect_vec());
+    }
+    Ok(())
+}
+
+#[test]
+fn region_is_empty() -> Result<(), BuildError> {
+    let mut module = ModuleBuilder::new();
+    let mut f = module.define_function("main", FunctionType::new_endo(type_row![QB_T]).into())?;
+    let [q] = f.input_wires_arr();
+    let empty = f.dfg_builder(FunctionType::new_endo(type_row![QB_T]), None, [q])?;
+    let [q] = empty.input_wires_arr();
+    let empty = empty.finish_with_outputs([q])?;
+    let f = f.finish_with_outputs(empty.outputs())?;
+    let h = module.finish_prelude_hugr()?;
+
+    assert_eq!(h.child_count(h.root()), 1);
+    assert_eq!(h.child_count(f.node()), 3);
+    assert_eq!(h.child_count(empty.node()), 2);
+    assert!(!h.region_is_empty(h.root()));
+    assert!(!h.region_is_empty(f.node()));
+    assert!(h.region_is_empty(empty.node()));
+    Ok(())
+}
+
+#[test]
+fn isomorphic_after_round_trip() -> Result<(), BuildError> {
+    use crate::hugr::HugrMut;
+    use crate::ops::Noop;
+
+    // Nested regions are built depth-first, but serialized breadth-first.
+    let mut module = ModuleBuilder::new();
+    let mut f = module.define_function("main", FunctionType::new_endo(type_row![QB_T]).into())?;
+    let [q] = f.input_wires_arr();
+    let inner = f.dfg_builder(FunctionType::new_endo(type_row![QB_T]), None, [q])?;
+    let [q] = inner.input_wires_arr();
+    let inner = inner.finish_with_outputs([q])?;
+    let noop = f.add_dataflow_op(Noop { ty: QB_T }, inner.outputs())?;
+    f.finish_with_outputs(noop.outputs())?;
+    let h = module.finish_prelude_hugr()?;
+
+    let json = serde_json::to_string(&h).unwrap();
+    let round_trip: Hugr = serde_json::from_str(&json).unwrap();
+    // The nodes have been renumbered.
+    assert!(h
+        .nodes()
+        .any(|n| h.get_optype(n) != round_trip.get_optype(n)));
+    assert!(h.is_isomorphic_to(&round_trip));
+    assert!(round_trip.is_isomorphic_to(&h));
+
+    let (other, _, _) = sample_hugr();
+    assert!(!h.is_isomorphic_to(&other));
+    let mut disconnected = h.clone();
+    disconnected.disconnect(inner.node(), crate::OutgoingPort::from(0));
+    assert!(!h.is_isomorphic_to(&disconnected));
+    Ok(())
+}
+
+#[test]
+fn ancestors() -> Result<(), BuildError> {
+    let mut module = ModuleBuilder::new();
+    let mut f = module.define_function("main", FunctionType::new_endo(type_row![QB_T]).into())?;
+    let [q] = f.input_wires_arr();
+    let inner = f.dfg_builder(FunctionType::new_endo(type_row![QB_T]), None, [q])?;
+    let [inner_input, _] = inner.io();
+    let [q] = inner.input_wires_arr();
+    let inner = inner.finish_with_outputs([q])?;
+    let f = f.finish_with_outputs(inner.outputs())?;
+    let h = module.finish_prelude_hugr()?;
+
+    assert_eq!(
+        h.ancestors(inner_input).collect_vec(),
+        [inner.node(), f.node(), h.root()]
+    );
+    assert_eq!(h.depth(inner_input), 3);
+    assert_eq!(h.ancestors(h.root()).next(), None);
+    assert_eq!(h.depth(h.root()), 0);
+    Ok(())
+}
@@ -145,6 +145,8 @@ pub mod hugr;
 pub mod macros;
 pub mod ops;
 pub mod std_extensions;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 pub mod types;
 mod utils;
 
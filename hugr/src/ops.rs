@@ -9,8 +9,8 @@ pub mod leaf;
 pub mod module;
 pub mod tag;
 pub mod validate;
-use crate::extension::ExtensionSet;
-use crate::types::{EdgeKind, FunctionType};
+use crate::extension::{ExtensionSet, OpCapabilities};
+use crate::types::{EdgeKind, FunctionType, TypeRow};
 use crate::{Direction, OutgoingPort, Port};
 use crate::{IncomingPort, PortIndex};
 use paste::paste;
@@ -290,6 +290,41 @@ impl OpType {
     pub fn is_container(&self) -> bool {
         self.validity_flags().allowed_children != OpTag::None
     }
+
+    /// Checks whether the children of the operation form a dataflow graph.
+    #[inline]
+    pub fn is_dataflow_parent(&self) -> bool {
+        OpTag::DataflowParent.is_superset(self.tag())
+    }
+
+    /// Checks whether the operation is a [`Conditional`] branching on a
+    /// [boolean], i.e. an if/else with two cases whose variants carry no
+    /// values.
+    ///
+    /// [boolean]: crate::extension::prelude::BOOL_T
+    pub fn is_boolean_conditional(&self) -> bool {
+        self.as_conditional()
+            .is_some_and(|c| c.sum_rows.len() == 2 && c.sum_rows.iter().all(TypeRow::is_empty))
+    }
+
+    /// The [`OpCapabilities`] of the operation.
+    ///
+    /// Only resolved extension operations have capabilities, given by their
+    /// definition; all other operations have none.
+    pub fn capabilities(&self) -> OpCapabilities {
+        self.as_custom_op()
+            .and_then(CustomOp::as_extension_op)
+            .map_or(OpCapabilities::NONE, |op| op.def().capabilities())
+    }
+
+    /// Checks whether the operation is a dataflow operation, i.e. a node of a
+    /// dataflow graph that is not a definition such as a [`FuncDefn`] or a
+    /// [`Const`].
+    #[inline]
+    pub fn is_dataflow_op(&self) -> bool {
+        let tag = self.tag();
+        OpTag::DataflowChild.is_superset(tag) && !OpTag::ScopedDefn.is_superset(tag)
+    }
 }
 
 /// Macro used by operations that want their
@@ -464,3 +499,51 @@ macro_rules! impl_validate_op {
 }
 
 use impl_validate_op;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::extension::prelude::USIZE_T;
+    use crate::type_row;
+    use crate::types::FunctionType;
+
+    #[test]
+    fn op_predicates() {
+        let sig = FunctionType::new_endo(type_row![USIZE_T]);
+        let dfg: OpType = DFG {
+            signature: sig.clone(),
+        }
+        .into();
+        let func: OpType = FuncDefn {
+            name: "f".into(),
+            signature: sig.clone().into(),
+        }
+        .into();
+        let cfg: OpType = CFG { signature: sig }.into();
+        let noop: OpType = Noop { ty: USIZE_T }.into();
+        let module: OpType = Module.into();
+
+        assert!(dfg.is_container() && dfg.is_dataflow_parent() && dfg.is_dataflow_op());
+        assert!(func.is_container() && func.is_dataflow_parent() && !func.is_dataflow_op());
+        assert!(cfg.is_container() && !cfg.is_dataflow_parent() && cfg.is_dataflow_op());
+        assert!(!noop.is_container() && !noop.is_dataflow_parent() && noop.is_dataflow_op());
+        assert!(module.is_container() && !module.is_dataflow_parent() && !module.is_dataflow_op());
+    }
+
+    #[test]
+    fn boolean_conditional() {
+        let conditional = |sum_rows| -> OpType {
+            Conditional {
+                sum_rows,
+                other_inputs: type_row![USIZE_T],
+                outputs: type_row![USIZE_T],
+                extension_delta: Default::default(),
+            }
+            .into()
+        };
+        assert!(conditional(vec![type_row![]; 2]).is_boolean_conditional());
+        assert!(!conditional(vec![type_row![]; 3]).is_boolean_conditional());
+        assert!(!conditional(vec![type_row![], type_row![USIZE_T]]).is_boolean_conditional());
+        assert!(!OpType::from(Noop { ty: USIZE_T }).is_boolean_conditional());
+    }
+}
@@ -5,7 +5,7 @@ mod custom;
 use super::{NamedOp, OpName, OpTrait, StaticTag};
 use super::{OpTag, OpType};
 use crate::extension::ExtensionSet;
-use crate::types::{CustomType, EdgeKind, FunctionType, SumType, SumTypeError, Type};
+use crate::types::{CustomType, EdgeKind, FunctionType, SumType, SumTypeError, Type, TypeEnum};
 use crate::{Hugr, HugrView};
 
 use itertools::Itertools;
@@ -151,6 +151,9 @@ pub enum ConstTypeError {
     /// Error when checking a custom value.
     #[error("Error when checking custom type: {0:?}")]
     CustomCheckFail(#[from] CustomCheckFailure),
+    /// The type has no sensible default value.
+    #[error("Type {0} has no default value.")]
+    NoDefaultValue(Type),
 }
 
 /// Hugrs (even functions) inside Consts must be monomorphic
@@ -250,6 +253,19 @@ impl Value {
         }
     }
 
+    /// If this is a boolean constant (see [`Value::from_bool`]), returns its
+    /// value.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Sum {
+                tag,
+                values,
+                sum_type,
+            } if values.is_empty() && sum_type == &SumType::new_unary(2) => Some(*tag == 1),
+            _ => None,
+        }
+    }
+
     /// Returns a tuple constant of constant values.
     pub fn extension(custom_const: impl CustomConst) -> Self {
         Self::Extension {
@@ -286,6 +302,104 @@ impl Value {
         .into()
     }
 
+    /// Compare two values of type `typ`, ignoring differences in how they are
+    /// represented.
+    ///
+    /// Tuples and sums are compared by their tag and the values in the
+    /// selected variant, so a [`Value::Tuple`] is equal to a single-variant
+    /// [`Value::Sum`] with the same elements, and the [`SumType`] stored in a
+    /// sum value is ignored in favour of `typ`. Extension values are equal if
+    /// both have type `typ` and either side's [`CustomConst::equal_consts`]
+    /// says so. Function values
+    /// are compared as [`Hugr`]s.
+    ///
+    /// Returns `false` if the values are not both valid instances of `typ`.
+    pub fn semantically_eq(&self, other: &Value, typ: &Type) -> bool {
+        match (self, other) {
+            (Self::Extension { e: e1 }, Self::Extension { e: e2 }) => {
+                &e1.0.get_type() == typ
+                    && &e2.0.get_type() == typ
+                    && (e1.0.equal_consts(e2.0.as_ref()) || e2.0.equal_consts(e1.0.as_ref()))
+            }
+            (Self::Function { hugr: h1 }, Self::Function { hugr: h2 }) => h1 == h2,
+            _ => {
+                let TypeEnum::Sum(sum_type) = typ.as_type_enum() else {
+                    return false;
+                };
+                let (Some((tag, vs1)), Some((tag2, vs2))) = (self.sum_parts(), other.sum_parts())
+                else {
+                    return false;
+                };
+                let Some(row) = sum_type.get_variant(tag) else {
+                    return false;
+                };
+                tag == tag2
+                    && vs1.len() == row.len()
+                    && vs2.len() == row.len()
+                    && itertools::izip!(vs1, vs2, row.iter())
+                        .all(|(v1, v2, t)| v1.semantically_eq(v2, t))
+            }
+        }
+    }
+
+    /// A default value of type `typ`.
+    ///
+    /// Sums take their first variant, filled with default values, and tuples
+    /// are filled with default values. The prelude `usize` and the standard
+    /// integer and float types default to zero, and lists to the empty list.
+    ///
+    /// Returns [`ConstTypeError::NoDefaultValue`] for types with no sensible
+    /// default, such as functions, type variables, empty sums and other
+    /// extension types.
+    pub fn default_for(typ: &Type) -> Result<Self, ConstTypeError> {
+        let no_default = || ConstTypeError::NoDefaultValue(typ.clone());
+        match typ.as_type_enum() {
+            TypeEnum::Sum(sum_type) => {
+                let row = sum_type.get_variant(0).ok_or_else(no_default)?;
+                let values: Vec<_> = row.iter().map(Self::default_for).try_collect()?;
+                if sum_type.num_variants() == 1 {
+                    Ok(Self::tuple(values))
+                } else {
+                    Self::sum(0, values, sum_type.clone())
+                }
+            }
+            TypeEnum::Extension(custom) => extension_default(custom)?.ok_or_else(no_default),
+            _ => Err(no_default()),
+        }
+    }
+
+    /// Folds `f` over this value and the values nested in it, in post-order:
+    /// the elements of a tuple or sum are visited before the value itself.
+    ///
+    /// Extension values (including lists) and functions are not traversed.
+    pub fn fold<T>(&self, init: T, f: impl Fn(T, &Value) -> T) -> T {
+        self.fold_ref(init, &f)
+    }
+
+    fn fold_ref<T>(&self, init: T, f: &impl Fn(T, &Value) -> T) -> T {
+        let children = self.sum_parts().map_or(&[][..], |(_, vs)| vs);
+        let acc = children.iter().fold(init, |acc, v| v.fold_ref(acc, f));
+        f(acc, self)
+    }
+
+    /// The number of values with no nested values in this value, see
+    /// [`Value::fold`].
+    pub fn leaf_count(&self) -> usize {
+        self.fold(0, |n, v| match v.sum_parts() {
+            Some((_, vs)) if !vs.is_empty() => n,
+            _ => n + 1,
+        })
+    }
+
+    /// The tag and variant values of a tuple or sum value.
+    fn sum_parts(&self) -> Option<(usize, &[Value])> {
+        match self {
+            Self::Tuple { vs } => Some((0, vs)),
+            Self::Sum { tag, values, .. } => Some((*tag, values)),
+            _ => None,
+        }
+    }
+
     /// The extensions required by a [`Value`]
     pub fn extension_reqs(&self) -> ExtensionSet {
         match self {
@@ -343,6 +457,33 @@ pub type ValueName = SmolStr;
 /// Slice of a [`ValueName`] constant value identifier.
 pub type ValueNameRef = str;
 
+/// The default value of the extension types known to [`Value::default_for`].
+fn extension_default(custom: &CustomType) -> Result<Option<Value>, ConstTypeError> {
+    use crate::extension::prelude::{ConstUsize, USIZE_CUSTOM_T};
+    use crate::std_extensions::arithmetic::{float_types, int_types};
+    use crate::std_extensions::collections::{self, ListValue};
+    use crate::types::type_param::TypeArg;
+
+    let ext = custom.extension();
+    Ok(Some(match custom.args() {
+        [] if custom == &USIZE_CUSTOM_T => ConstUsize::new(0).into(),
+        [] if custom == &float_types::FLOAT64_CUSTOM_TYPE => float_types::ConstF64::new(0.0).into(),
+        [TypeArg::BoundedNat { n }]
+            if ext == &int_types::EXTENSION_ID && custom.name() == &int_types::INT_TYPE_ID =>
+        {
+            let log_width = u8::try_from(*n).unwrap_or(u8::MAX);
+            int_types::ConstInt::new_u(log_width, 0)?.into()
+        }
+        [TypeArg::Type { ty }]
+            if ext == &collections::EXTENSION_NAME
+                && custom.name() == &collections::LIST_TYPENAME =>
+        {
+            ListValue::new_empty(ty.clone()).into()
+        }
+        _ => return Ok(None),
+    }))
+}
+
 #[cfg(test)]
 mod test {
     use super::Value;
@@ -351,7 +492,7 @@ mod test {
         builder::{BuildError, DFGBuilder, Dataflow, DataflowHugr},
         extension::{
             prelude::{ConstUsize, USIZE_CUSTOM_T, USIZE_T},
-            ExtensionId, ExtensionRegistry, PRELUDE,
+            ExtensionId, ExtensionRegistry,
         },
         std_extensions::arithmetic::float_types::{self, ConstF64, FLOAT64_TYPE},
         type_row,
@@ -398,7 +539,7 @@ mod test {
     }
 
     fn test_registry() -> ExtensionRegistry {
-        ExtensionRegistry::try_new([PRELUDE.to_owned(), float_types::EXTENSION.to_owned()]).unwrap()
+        ExtensionRegistry::with_prelude([float_types::EXTENSION.to_owned()]).unwrap()
     }
 
     /// Constructs a DFG hugr defining a sum constant, and returning the loaded value.
@@ -527,6 +668,85 @@ mod test {
         assert_eq!(const_tuple.get_custom_value::<ConstF64>(), None);
     }
 
+    #[test]
+    fn leaf_count() {
+        let nested = Value::tuple([
+            Value::tuple([ConstUsize::new(1).into(), ConstF64::new(2.0).into()]),
+            Value::true_val(),
+        ]);
+        assert_eq!(nested.leaf_count(), 3);
+        assert_eq!(Value::unit().leaf_count(), 1);
+
+        // Post-order: the elements are visited before the tuples containing them.
+        let counts = nested.fold(Vec::new(), |mut counts, v| {
+            counts.push(v.leaf_count());
+            counts
+        });
+        assert_eq!(counts, [1, 1, 2, 1, 3]);
+    }
+
+    #[test]
+    fn semantic_equality() {
+        use crate::std_extensions::arithmetic::int_types::{ConstInt, INT_TYPES};
+
+        // The same integer, built as unsigned and as signed.
+        let int_t = INT_TYPES[5].clone();
+        let u: Value = ConstInt::new_u(5, 42).unwrap().into();
+        let s: Value = ConstInt::new_s(5, 42).unwrap().into();
+        assert!(u.semantically_eq(&s, &int_t));
+        let neg: Value = ConstInt::new_s(5, -42).unwrap().into();
+        assert!(!u.semantically_eq(&neg, &int_t));
+        assert!(!u.semantically_eq(&s, &INT_TYPES[4]));
+        assert!(!u.semantically_eq(&s, &USIZE_T));
+
+        // A tuple and the equivalent single-variant sum.
+        let row = type_row![USIZE_T, USIZE_T];
+        let tuple_t = Type::new_tuple(row.clone());
+        let elems = || [ConstUsize::new(1).into(), ConstUsize::new(2).into()];
+        let tuple = Value::tuple(elems());
+        let sum = Value::sum(0, elems(), SumType::new([row])).unwrap();
+        assert_ne!(tuple, sum);
+        assert!(tuple.semantically_eq(&sum, &tuple_t));
+        assert!(sum.semantically_eq(&tuple, &tuple_t));
+        let other = Value::tuple([ConstUsize::new(2).into(), ConstUsize::new(1).into()]);
+        assert!(!tuple.semantically_eq(&other, &tuple_t));
+
+        // The same unit sum, with different representations of its type.
+        let general = Value::Sum {
+            tag: 1,
+            values: vec![],
+            sum_type: SumType::General {
+                rows: vec![type_row![], type_row![]],
+            },
+        };
+        assert_ne!(general, Value::true_val());
+        assert!(general.semantically_eq(&Value::true_val(), &Type::new_unit_sum(2)));
+        assert!(!general.semantically_eq(&Value::false_val(), &Type::new_unit_sum(2)));
+    }
+
+    #[rstest]
+    #[case(Type::new_unit_sum(2))]
+    #[case(Type::new_tuple(type_row![USIZE_T, FLOAT64_TYPE]))]
+    #[case(Type::new_sum([type_row![USIZE_T], type_row![]]))]
+    #[case(crate::std_extensions::arithmetic::int_types::INT_TYPES[4].clone())]
+    #[case(crate::std_extensions::collections::list_type(USIZE_T))]
+    fn default_value(#[case] typ: Type) {
+        let value = Value::default_for(&typ).unwrap();
+        assert_eq!(value.const_type(), typ);
+    }
+
+    #[rstest]
+    #[case(crate::extension::prelude::QB_T)]
+    #[case(Type::new_function(FunctionType::new_endo(type_row![USIZE_T])))]
+    #[case(Type::new_sum(Vec::<TypeRow>::new()))]
+    #[case(Type::new_var_use(0, TypeBound::Copyable))]
+    fn no_default_value(#[case] typ: Type) {
+        assert_eq!(
+            Value::default_for(&typ),
+            Err(ConstTypeError::NoDefaultValue(typ))
+        );
+    }
+
     #[test]
     fn test_yaml_const() {
         let ex_id: ExtensionId = "my_extension".try_into().unwrap();
@@ -1,13 +1,15 @@
 //! Extensible operations.
 
 use std::sync::Arc;
+
+use itertools::Itertools;
 use thiserror::Error;
 
 use crate::extension::{ConstFoldResult, ExtensionId, ExtensionRegistry, OpDef, SignatureError};
 use crate::hugr::hugrmut::sealed::HugrMutInternals;
 use crate::hugr::{HugrView, NodeType};
-use crate::types::EdgeKind;
 use crate::types::{type_param::TypeArg, FunctionType};
+use crate::types::{EdgeKind, Substitution};
 use crate::{ops, Hugr, IncomingPort, Node};
 
 use super::dataflow::DataflowOpTrait;
@@ -197,6 +199,28 @@ impl ExtensionOp {
         self.def.as_ref()
     }
 
+    /// Substitute the type variables used in the arguments of this operation,
+    /// giving the values of the variables in scope in order, and recompute
+    /// the signature.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SignatureError`] if the substituted arguments are not valid
+    /// for the definition.
+    ///
+    /// # Panics
+    ///
+    /// If the arguments use a variable beyond the end of `vars`.
+    pub fn substitute(
+        &self,
+        vars: &[TypeArg],
+        exts: &ExtensionRegistry,
+    ) -> Result<Self, SignatureError> {
+        let subst = Substitution::new(vars, exts);
+        let args = self.args.iter().map(|a| a.substitute(&subst)).collect_vec();
+        Self::new(self.def.clone(), args, exts)
+    }
+
     /// Attempt to evaluate this operation. See [`OpDef::constant_fold`].
     pub fn constant_fold(&self, consts: &[(IncomingPort, ops::Value)]) -> ConstFoldResult {
         self.def().constant_fold(self.args(), consts)
@@ -296,6 +320,30 @@ impl OpaqueOp {
     }
 }
 
+impl OpaqueOp {
+    /// Look up the [`OpDef`] of this operation in `registry` and instantiate
+    /// it with the stored type arguments.
+    ///
+    /// # Errors
+    ///
+    /// If the extension or operation are not in the registry, or if the
+    /// stored arguments do not match the parameters of the definition
+    /// ([`SignatureError::TypeArgMismatch`]). The stored signature is not
+    /// compared against the computed one, see [`resolve_opaque_op`].
+    pub fn resolve(&self, registry: &ExtensionRegistry) -> Result<ExtensionOp, SignatureError> {
+        let ext = registry
+            .get(&self.extension)
+            .ok_or_else(|| SignatureError::ExtensionNotFound(self.extension.clone()))?;
+        let def = ext
+            .get_op(&self.op_name)
+            .ok_or_else(|| SignatureError::ExtensionOpNotFound {
+                exn: self.extension.clone(),
+                op: self.op_name.clone(),
+            })?;
+        ExtensionOp::new(def.clone(), self.args.clone(), registry)
+    }
+}
+
 impl OpaqueOp {
     /// Unique name of the operation.
     pub fn name(&self) -> &OpName {
@@ -369,28 +417,29 @@ pub fn resolve_opaque_op(
     opaque: &OpaqueOp,
     extension_registry: &ExtensionRegistry,
 ) -> Result<Option<ExtensionOp>, CustomOpError> {
-    if let Some(r) = extension_registry.get(&opaque.extension) {
-        // Fail if the Extension was found but did not have the expected operation
-        let Some(def) = r.get_op(&opaque.op_name) else {
-            return Err(CustomOpError::OpNotFoundInExtension(
-                opaque.op_name.clone(),
-                r.name().clone(),
-            ));
-        };
-        let ext_op =
-            ExtensionOp::new(def.clone(), opaque.args.clone(), extension_registry).unwrap();
-        if opaque.signature != ext_op.signature {
-            return Err(CustomOpError::SignatureMismatch {
-                extension: opaque.extension.clone(),
-                op: def.name().clone(),
-                computed: ext_op.signature.clone(),
-                stored: opaque.signature.clone(),
-            });
-        };
-        Ok(Some(ext_op))
-    } else {
-        Ok(None)
+    if extension_registry.get(&opaque.extension).is_none() {
+        return Ok(None);
     }
+    let ext_op = opaque.resolve(extension_registry).map_err(|e| match e {
+        // The Extension was found but did not have the expected operation
+        SignatureError::ExtensionOpNotFound { exn, op } => {
+            CustomOpError::OpNotFoundInExtension(op, exn)
+        }
+        error => CustomOpError::InvalidArgs {
+            extension: opaque.extension.clone(),
+            op: opaque.op_name.clone(),
+            error,
+        },
+    })?;
+    if opaque.signature != ext_op.signature {
+        return Err(CustomOpError::SignatureMismatch {
+            extension: opaque.extension.clone(),
+            op: ext_op.def().name().clone(),
+            computed: ext_op.signature.clone(),
+            stored: opaque.signature.clone(),
+        });
+    };
+    Ok(Some(ext_op))
 }
 
 /// Errors that arise after loading a Hugr containing opaque ops (serialized just as their names)
@@ -410,15 +459,119 @@ pub enum CustomOpError {
         stored: FunctionType,
         computed: FunctionType,
     },
+    /// Extension and OpDef found, but the stored type arguments are invalid
+    #[error("Invalid type arguments for {op} in extension {extension}: {error}")]
+    #[allow(missing_docs)]
+    InvalidArgs {
+        extension: ExtensionId,
+        op: OpName,
+        #[source]
+        error: SignatureError,
+    },
 }
 
 #[cfg(test)]
 mod test {
 
-    use crate::extension::prelude::{QB_T, USIZE_T};
+    use cool_asserts::assert_matches;
+
+    use crate::extension::prelude::{BOOL_T, QB_T, USIZE_T};
+    use crate::type_row;
 
     use super::*;
 
+    #[test]
+    fn resolve_opaque() {
+        use crate::extension::PRELUDE_REGISTRY;
+        use crate::std_extensions::logic::{self, NotOp, LOGIC_REG};
+
+        let not_op = |args| {
+            OpaqueOp::new(
+                logic::EXTENSION_ID,
+                NotOp.name(),
+                "".into(),
+                args,
+                FunctionType::new_endo(type_row![BOOL_T]),
+            )
+        };
+        let resolved = not_op(vec![]).resolve(&LOGIC_REG).unwrap();
+        assert_eq!(resolved.def().name(), &NotOp.name());
+
+        // Corrupt serialized arguments are caught on resolution.
+        let corrupt = not_op(vec![TypeArg::BoundedNat { n: 2 }]);
+        assert_eq!(
+            corrupt.resolve(&LOGIC_REG).unwrap_err(),
+            SignatureError::MissingTypeArgs {
+                expected: 0,
+                got: 1
+            }
+        );
+        assert_matches!(
+            resolve_opaque_op(
+                Node::from(portgraph::NodeIndex::new(0)),
+                &corrupt,
+                &LOGIC_REG
+            ),
+            Err(CustomOpError::InvalidArgs { .. })
+        );
+
+        assert_eq!(
+            not_op(vec![]).resolve(&PRELUDE_REGISTRY).unwrap_err(),
+            SignatureError::ExtensionNotFound(logic::EXTENSION_ID)
+        );
+        let missing = OpaqueOp::new(
+            logic::EXTENSION_ID,
+            "Missing",
+            "".into(),
+            vec![],
+            FunctionType::new_endo(type_row![]),
+        );
+        assert_eq!(
+            missing.resolve(&LOGIC_REG).unwrap_err(),
+            SignatureError::ExtensionOpNotFound {
+                exn: logic::EXTENSION_ID,
+                op: "Missing".into()
+            }
+        );
+    }
+
+    #[test]
+    fn substitute_nat_var() -> Result<(), SignatureError> {
+        use crate::extension::prelude::array_type;
+        use crate::extension::{ExtensionId, PRELUDE};
+        use crate::types::{type_param::TypeParam, PolyFuncType};
+        use crate::Extension;
+
+        const EXT_ID: ExtensionId = ExtensionId::new_unchecked("MyExt");
+        let size = TypeParam::max_nat();
+        let mut e = Extension::new(EXT_ID);
+        e.add_op(
+            "len".into(),
+            "".into(),
+            PolyFuncType::new(
+                [size.clone()],
+                FunctionType::new(
+                    vec![array_type(TypeArg::new_var_use(0, size.clone()), BOOL_T)],
+                    vec![USIZE_T],
+                ),
+            ),
+        )
+        .unwrap();
+        let reg = ExtensionRegistry::try_new([PRELUDE.to_owned(), e]).unwrap();
+        let def = reg.get(&EXT_ID).unwrap().get_op("len").unwrap();
+
+        // An op in a scope with a nat variable.
+        let generic = ExtensionOp::new(def.clone(), [TypeArg::new_var_use(0, size)], &reg)?;
+        let concrete = generic.substitute(&[TypeArg::BoundedNat { n: 3 }], &reg)?;
+        let three = TypeArg::BoundedNat { n: 3 };
+        assert_eq!(
+            concrete,
+            ExtensionOp::new(def.clone(), [three.clone()], &reg)?
+        );
+        assert_eq!(concrete.signature().input[0], array_type(three, BOOL_T));
+        Ok(())
+    }
+
     #[test]
     fn new_opaque_op() {
         let sig = FunctionType::new_endo(vec![QB_T]);
@@ -116,7 +116,7 @@ impl OpTrait for AliasDefn {
 }
 
 /// A type alias declaration. Resolved at link time.
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct AliasDecl {
     /// Alias name
     pub name: SmolStr,
@@ -10,6 +10,7 @@ use itertools::Itertools;
 use portgraph::{NodeIndex, PortOffset};
 use thiserror::Error;
 
+use crate::extension::ExtensionSet;
 use crate::types::TypeRow;
 
 use super::dataflow::DataflowParent;
@@ -89,6 +90,8 @@ impl ValidateOp for super::Conditional {
 
         // Each child must have its variant's row and the rest of `inputs` as input,
         // and matching output
+        let first_child = children[0].0;
+        let mut case_deltas = ExtensionSet::new();
         for (i, (child, optype)) in children.into_iter().enumerate() {
             let case_op = optype
                 .as_case()
@@ -100,6 +103,16 @@ impl ValidateOp for super::Conditional {
                     optype: optype.clone(),
                 });
             }
+            case_deltas = case_deltas.union(sig.extension_reqs.clone());
+        }
+
+        // The Conditional's extension delta must include those of its cases.
+        if !case_deltas.is_subset(&self.extension_delta) {
+            return Err(ChildrenValidationError::ConditionalExtensionDelta {
+                child: first_child,
+                expected: self.extension_delta.clone(),
+                actual: case_deltas,
+            });
         }
 
         Ok(())
@@ -191,6 +204,13 @@ pub enum ChildrenValidationError {
         expected_count: usize,
         actual_sum_rows: Vec<TypeRow>,
     },
+    /// The cases of a conditional require extensions not in the container's extension delta.
+    #[error("The cases of a conditional container require extensions {actual}, but the container only declares {expected}")]
+    ConditionalExtensionDelta {
+        child: NodeIndex,
+        expected: ExtensionSet,
+        actual: ExtensionSet,
+    },
 }
 
 impl ChildrenValidationError {
@@ -202,6 +222,7 @@ impl ChildrenValidationError {
             ChildrenValidationError::ConditionalCaseSignature { child, .. } => *child,
             ChildrenValidationError::IOSignatureMismatch { child, .. } => *child,
             ChildrenValidationError::InvalidConditionalSum { child, .. } => *child,
+            ChildrenValidationError::ConditionalExtensionDelta { child, .. } => *child,
         }
     }
 }
@@ -349,7 +370,9 @@ fn validate_cfg_edge(edge: ChildrenEdgeData) -> Result<(), EdgeValidationError>
 #[cfg(test)]
 mod test {
     use crate::extension::prelude::USIZE_T;
+    use crate::extension::ExtensionId;
     use crate::ops::dataflow::IOTrait;
+    use crate::types::FunctionType;
     use crate::{ops, type_row};
     use cool_asserts::assert_matches;
 
@@ -398,6 +421,36 @@ mod test {
         );
     }
 
+    #[test]
+    fn conditional_extension_delta() {
+        let [a, b]: [ExtensionId; 2] = ["A", "B"].map(|e| e.try_into().unwrap());
+        let case = |delta: ExtensionSet| -> OpType {
+            ops::Case {
+                signature: FunctionType::new_endo(type_row![]).with_extension_delta(delta),
+            }
+            .into()
+        };
+        let conditional = |delta| ops::Conditional {
+            sum_rows: vec![type_row![]; 2],
+            other_inputs: type_row![],
+            outputs: type_row![],
+            extension_delta: delta,
+        };
+        let cases = [case(ExtensionSet::singleton(&a)), case(ExtensionSet::new())];
+        let children = vec![(0, &cases[0]), (1, &cases[1])];
+
+        let exact = conditional(ExtensionSet::singleton(&a));
+        assert_eq!(exact.validate_op_children(make_iter(&children)), Ok(()));
+        // Declaring a strict superset of the cases' deltas is allowed.
+        let superset = conditional(ExtensionSet::from_iter([a.clone(), b.clone()]));
+        assert_eq!(superset.validate_op_children(make_iter(&children)), Ok(()));
+        let missing = conditional(ExtensionSet::singleton(&b));
+        assert_matches!(
+            missing.validate_op_children(make_iter(&children)),
+            Err(ChildrenValidationError::ConditionalExtensionDelta { .. })
+        );
+    }
+
     fn make_iter<'a>(
         children: &'a [(usize, &OpType)],
     ) -> impl DoubleEndedIterator<Item = (NodeIndex, &'a OpType)> {
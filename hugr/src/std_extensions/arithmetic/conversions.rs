@@ -36,7 +36,7 @@ pub enum ConvertOpDef {
 
 impl MakeOpDef for ConvertOpDef {
     fn from_def(op_def: &OpDef) -> Result<Self, OpLoadError> {
-        crate::extension::simple_op::try_from_name(op_def.name())
+        crate::extension::simple_op::try_from_name(op_def.name(), op_def.extension())
     }
 
     fn signature(&self) -> SignatureFunc {
@@ -9,7 +9,7 @@ use crate::{
     ops::constant::CustomConst,
     types::{
         type_param::{TypeArg, TypeArgError, TypeParam},
-        ConstTypeError, CustomType, Type, TypeBound,
+        ConstTypeError, CustomCheckFailure, CustomType, Type, TypeBound, TypeEnum,
     },
     Extension,
 };
@@ -40,6 +40,29 @@ lazy_static! {
         .unwrap();
 }
 
+/// Integer type of the given log width, or an error if the width is not
+/// supported.
+///
+/// This is a runtime alternative to indexing [`static@INT_TYPES`].
+pub fn int_type_of_log_width(log_width: u8) -> Result<Type, TypeArgError> {
+    get_log_width(&type_arg(log_width)).map(|w| INT_TYPES[w as usize].clone())
+}
+
+/// Returns the log width of `ty` if it is an integer type of concrete width,
+/// and `None` otherwise.
+pub fn int_type_log_width(ty: &Type) -> Option<u8> {
+    let TypeEnum::Extension(custom) = ty.as_type_enum() else {
+        return None;
+    };
+    if custom.extension() != &EXTENSION_ID || custom.name() != &INT_TYPE_ID {
+        return None;
+    }
+    match custom.args() {
+        [arg] => get_log_width(arg).ok(),
+        _ => None,
+    }
+}
+
 const fn is_valid_log_width(n: u8) -> bool {
     n < LOG_WIDTH_BOUND
 }
@@ -95,7 +118,7 @@ impl ConstInt {
                 crate::types::CustomCheckFailure::Message("Invalid integer width.".to_owned()),
             ));
         }
-        if (log_width <= 5) && (value >= (1u64 << (1u8 << log_width))) {
+        if value > max_unsigned(log_width) {
             return Err(ConstTypeError::CustomCheckFail(
                 crate::types::CustomCheckFailure::Message(
                     "Invalid unsigned integer value.".to_owned(),
@@ -112,8 +135,8 @@ impl ConstInt {
                 crate::types::CustomCheckFailure::Message("Invalid integer width.".to_owned()),
             ));
         }
-        let width = 1u8 << log_width;
-        if (log_width <= 5) && (value >= (1i64 << (width - 1)) || value < -(1i64 << (width - 1))) {
+        let max = max_signed(log_width);
+        if value > max || value < -max - 1 {
             return Err(ConstTypeError::CustomCheckFail(
                 crate::types::CustomCheckFailure::Message(
                     "Invalid signed integer value.".to_owned(),
@@ -123,11 +146,7 @@ impl ConstInt {
 
         Ok(Self {
             log_width,
-            value: (if value >= 0 || log_width == LOG_WIDTH_MAX {
-                value
-            } else {
-                value + (1i64 << width)
-            }) as u64,
+            value: value as u64 & max_unsigned(log_width),
         })
     }
 
@@ -142,20 +161,29 @@ impl ConstInt {
     }
 
     /// Returns the value of the constant as a signed integer
+    ///
+    /// A constant with an invalid width, e.g. one deserialized without being
+    /// validated, is read at the maximum width.
     pub fn value_s(&self) -> i64 {
-        if self.log_width == LOG_WIDTH_MAX {
-            self.value as i64
-        } else {
-            let width = 1u8 << self.log_width;
-            if ((self.value << 1) >> width) == 0 {
-                self.value as i64
-            } else {
-                self.value as i64 - (1i64 << width)
-            }
-        }
+        // Sign-extend from the top bit of the constant's width.
+        let shift = 64 - (1u32 << self.log_width.min(LOG_WIDTH_MAX));
+        ((self.value << shift) as i64) >> shift
     }
 }
 
+/// The largest unsigned value representable with the given (valid) log width.
+///
+/// Computed by shifting [`u64::MAX`] down, so no intermediate value can
+/// overflow even at the maximum width.
+const fn max_unsigned(log_width: u8) -> u64 {
+    u64::MAX >> (64 - (1u32 << log_width))
+}
+
+/// The largest signed value representable with the given (valid) log width.
+const fn max_signed(log_width: u8) -> i64 {
+    (max_unsigned(log_width) >> 1) as i64
+}
+
 #[typetag::serde]
 impl CustomConst for ConstInt {
     fn name(&self) -> ValueName {
@@ -172,6 +200,24 @@ impl CustomConst for ConstInt {
     fn get_type(&self) -> Type {
         int_type(type_arg(self.log_width))
     }
+
+    fn validate(&self) -> Result<(), CustomCheckFailure> {
+        // Signed values are stored in two's complement within the width, so
+        // the unsigned bound covers both interpretations.
+        if !is_valid_log_width(self.log_width) {
+            return Err(CustomCheckFailure::Message(
+                "Invalid integer width.".to_owned(),
+            ));
+        }
+        if self.value > max_unsigned(self.log_width) {
+            return Err(CustomCheckFailure::Message(format!(
+                "Value {} does not fit in {} bits.",
+                self.value,
+                1u32 << self.log_width
+            )));
+        }
+        Ok(())
+    }
 }
 
 /// Extension for basic integer types.
@@ -231,6 +277,46 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_int_type_widths() {
+        for log_width in 0..LOG_WIDTH_BOUND {
+            let ty = int_type_of_log_width(log_width).unwrap();
+            assert_eq!(ty, INT_TYPES[log_width as usize]);
+            assert_eq!(int_type_log_width(&ty), Some(log_width));
+        }
+        assert_matches!(
+            int_type_of_log_width(LOG_WIDTH_BOUND),
+            Err(TypeArgError::TypeMismatch { .. })
+        );
+        assert_eq!(int_type_log_width(&int_tv(0)), None);
+        assert_eq!(
+            int_type_log_width(&crate::extension::prelude::USIZE_T),
+            None
+        );
+    }
+
+    #[test]
+    fn test_int_const_bounds() {
+        for log_width in 0..LOG_WIDTH_BOUND {
+            let max_u = max_unsigned(log_width);
+            let max_s = max_signed(log_width);
+            assert_eq!(ConstInt::new_u(log_width, max_u).unwrap().value_u(), max_u);
+            assert_eq!(ConstInt::new_s(log_width, max_s).unwrap().value_s(), max_s);
+            let min = ConstInt::new_s(log_width, -max_s - 1).unwrap();
+            assert_eq!(min.value_s(), -max_s - 1);
+            assert_eq!(min.value_u(), max_s as u64 + 1);
+            if log_width < LOG_WIDTH_MAX {
+                ConstInt::new_u(log_width, max_u + 1).unwrap_err();
+                ConstInt::new_s(log_width, max_s + 1).unwrap_err();
+                ConstInt::new_s(log_width, -max_s - 2).unwrap_err();
+            }
+        }
+        assert_eq!(max_unsigned(LOG_WIDTH_MAX), u64::MAX);
+        assert_eq!(max_signed(LOG_WIDTH_MAX), i64::MAX);
+        assert_eq!(max_unsigned(0), 1);
+        assert_eq!(max_signed(0), 0);
+    }
+
     #[test]
     fn test_int_consts() {
         let const_u32_7 = ConstInt::new_u(5, 7);
@@ -269,7 +355,39 @@ mod test {
         assert!(const_i32_2.validate().is_ok());
         assert_eq!(const_i32_2.name(), "u32(4294967294)");
 
+        // Signed boundary values round-trip through the unsigned storage.
+        for (log_width, min, max) in [(0, -1, 0), (3, -128, 127), (6, i64::MIN, i64::MAX)] {
+            for v in [min, -1, 0, max] {
+                let c = ConstInt::new_s(log_width, v).unwrap();
+                assert_eq!(c.value_s(), v);
+                assert!(c.validate().is_ok());
+            }
+        }
+        assert_eq!(ConstInt::new_s(3, -1).unwrap().value_u(), 255);
+        ConstInt::new_s(3, -129).unwrap_err();
+
+        // Constants constructed without the checks (e.g. deserialized) fail
+        // validation.
+        let too_big = ConstInt {
+            log_width: 3,
+            value: 256,
+        };
+        assert_matches!(too_big.validate(), Err(CustomCheckFailure::Message(_)));
+        let bad_width = ConstInt {
+            log_width: LOG_WIDTH_BOUND,
+            value: 0,
+        };
+        assert_matches!(bad_width.validate(), Err(CustomCheckFailure::Message(_)));
+        for log_width in [LOG_WIDTH_BOUND, u8::MAX] {
+            let bad_width = ConstInt {
+                log_width,
+                value: u64::MAX,
+            };
+            assert_eq!(bad_width.value_s(), -1);
+        }
+
         ConstInt::new_s(50, -2).unwrap_err();
+        ConstInt::new_u(LOG_WIDTH_BOUND, 0).unwrap_err();
         ConstInt::new_u(50, 2).unwrap_err();
     }
 }
@@ -251,7 +251,12 @@ impl MakeExtensionOp for ListOpInst {
             // can't use const SmolStr in pattern
             _ if name == &POP_NAME => ListOp::Pop,
             _ if name == &PUSH_NAME => ListOp::Push,
-            _ => return Err(OpLoadError::NotMember(name.to_string())),
+            _ => {
+                return Err(OpLoadError::NotMember(
+                    name.to_string(),
+                    ext_op.def().extension().clone(),
+                ))
+            }
         };
 
         Ok(Self {
@@ -293,10 +298,7 @@ impl ListOpInst {
 mod test {
     use crate::ops::OpTrait;
     use crate::{
-        extension::{
-            prelude::{ConstUsize, QB_T, USIZE_T},
-            PRELUDE,
-        },
+        extension::prelude::{ConstUsize, QB_T, USIZE_T},
         std_extensions::arithmetic::float_types::{self, ConstF64, FLOAT64_TYPE},
         types::TypeRow,
     };
@@ -335,9 +337,7 @@ mod test {
 
     #[test]
     fn test_list_ops() {
-        let reg =
-            ExtensionRegistry::try_new([PRELUDE.to_owned(), float_types::EXTENSION.to_owned()])
-                .unwrap();
+        let reg = ExtensionRegistry::with_prelude([float_types::EXTENSION.to_owned()]).unwrap();
         let pop_op = ListOp::Pop.with_type(QB_T);
         let pop_ext = pop_op.clone().to_extension_op(&reg).unwrap();
         assert_eq!(ListOpInst::from_extension_op(&pop_ext).unwrap(), pop_op);
@@ -6,6 +6,7 @@ use crate::ops::constant::ValueName;
 use crate::ops::OpName;
 use crate::{
     algorithm::const_fold::sorted_consts,
+    builder::{BuildError, Dataflow},
     extension::{
         prelude::BOOL_T,
         simple_op::{try_from_name, MakeExtensionOp, MakeOpDef, MakeRegisteredOp, OpLoadError},
@@ -17,7 +18,7 @@ use crate::{
         type_param::{TypeArg, TypeParam},
         FunctionType,
     },
-    Extension, IncomingPort,
+    Extension, HugrView, IncomingPort, Wire,
 };
 use lazy_static::lazy_static;
 /// Name of extension false value.
@@ -48,7 +49,7 @@ impl MakeOpDef for NaryLogic {
     }
 
     fn from_def(op_def: &OpDef) -> Result<Self, OpLoadError> {
-        try_from_name(op_def.name())
+        try_from_name(op_def.name(), op_def.extension())
     }
 
     fn post_opdef(&self, def: &mut OpDef) {
@@ -98,7 +99,7 @@ impl MakeExtensionOp for ConcreteLogicOp {
 }
 
 /// Not operation.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct NotOp;
 impl NamedOp for NotOp {
     fn name(&self) -> OpName {
@@ -110,7 +111,10 @@ impl MakeOpDef for NotOp {
         if op_def.name() == &NotOp.name() {
             Ok(NotOp)
         } else {
-            Err(OpLoadError::NotMember(op_def.name().to_string()))
+            Err(OpLoadError::NotMember(
+                op_def.name().to_string(),
+                op_def.extension().clone(),
+            ))
         }
     }
 
@@ -127,7 +131,8 @@ pub const EXTENSION_ID: ExtensionId = ExtensionId::new_unchecked("logic");
 fn logic_op_sig() -> impl SignatureFromArgs {
     struct LogicOpCustom;
 
-    const MAX: &[TypeParam; 1] = &[TypeParam::max_nat()];
+    // Logic operations require at least one input.
+    const MIN_ONE: &[TypeParam; 1] = &[TypeParam::bounded_nat_range(1, None)];
     impl SignatureFromArgs for LogicOpCustom {
         fn compute_signature(
             &self,
@@ -142,7 +147,7 @@ fn logic_op_sig() -> impl SignatureFromArgs {
         }
 
         fn static_params(&self) -> &[TypeParam] {
-            MAX
+            MIN_ONE
         }
     }
     LogicOpCustom
@@ -190,39 +195,143 @@ impl MakeRegisteredOp for NotOp {
     }
 }
 
+/// Builder helpers for logic operations that simplify literal inputs while
+/// building, rather than relying on a later constant folding pass.
+pub trait LogicOpBuilder: Dataflow {
+    /// Add a logical "and" of `inputs`, omitting literal `TRUE` inputs.
+    ///
+    /// If any input is a literal `FALSE`, that wire is returned and no
+    /// operation is added. If a single input remains it is returned directly.
+    fn add_and_simplified(
+        &mut self,
+        inputs: impl IntoIterator<Item = Wire>,
+    ) -> Result<Wire, BuildError> {
+        add_nary_simplified(self, NaryLogic::And, inputs)
+    }
+
+    /// Add a logical "or" of `inputs`, omitting literal `FALSE` inputs.
+    ///
+    /// If any input is a literal `TRUE`, that wire is returned and no
+    /// operation is added. If a single input remains it is returned directly.
+    fn add_or_simplified(
+        &mut self,
+        inputs: impl IntoIterator<Item = Wire>,
+    ) -> Result<Wire, BuildError> {
+        add_nary_simplified(self, NaryLogic::Or, inputs)
+    }
+
+    /// Add a logical "and" of all `inputs`, returning the output wire.
+    ///
+    /// The operation is instantiated with the number of inputs. Returns
+    /// [`BuildError::NoInputs`] if `inputs` is empty.
+    fn add_nary_and(&mut self, inputs: impl IntoIterator<Item = Wire>) -> Result<Wire, BuildError> {
+        add_nary(self, NaryLogic::And, inputs.into_iter().collect())
+    }
+
+    /// Add a logical "or" of all `inputs`, returning the output wire.
+    ///
+    /// The operation is instantiated with the number of inputs. Returns
+    /// [`BuildError::NoInputs`] if `inputs` is empty.
+    fn add_nary_or(&mut self, inputs: impl IntoIterator<Item = Wire>) -> Result<Wire, BuildError> {
+        add_nary(self, NaryLogic::Or, inputs.into_iter().collect())
+    }
+}
+
+impl<D: Dataflow> LogicOpBuilder for D {}
+
+fn add_nary_simplified<D: Dataflow + ?Sized>(
+    builder: &mut D,
+    op: NaryLogic,
+    inputs: impl IntoIterator<Item = Wire>,
+) -> Result<Wire, BuildError> {
+    // The value that can be dropped from the inputs, and its negation which
+    // determines the result.
+    let identity = match op {
+        NaryLogic::And => true,
+        NaryLogic::Or => false,
+    };
+    let mut remaining = Vec::new();
+    for wire in inputs {
+        match bool_literal(builder.hugr(), wire) {
+            Some(b) if b == identity => {}
+            Some(_) => return Ok(wire),
+            None => remaining.push(wire),
+        }
+    }
+    match remaining.as_slice() {
+        [] => Ok(builder.add_load_value(ops::Value::from_bool(identity))),
+        [wire] => Ok(*wire),
+        _ => Ok(builder
+            .add_dataflow_op(op.with_n_inputs(remaining.len() as u64), remaining)?
+            .out_wire(0)),
+    }
+}
+
+fn add_nary<D: Dataflow + ?Sized>(
+    builder: &mut D,
+    op: NaryLogic,
+    inputs: Vec<Wire>,
+) -> Result<Wire, BuildError> {
+    // The operations take at least one input.
+    if inputs.is_empty() {
+        let name: &'static str = op.into();
+        return Err(BuildError::NoInputs { op: name.into() });
+    }
+    Ok(builder
+        .add_dataflow_op(op.with_n_inputs(inputs.len() as u64), inputs)?
+        .out_wire(0))
+}
+
+/// If `wire` is the output of a [`ops::LoadConstant`] of a boolean constant,
+/// return its value.
+fn bool_literal(hugr: &impl HugrView, wire: Wire) -> Option<bool> {
+    let load = hugr.get_optype(wire.node()).as_load_constant()?;
+    let (const_node, _) = hugr.single_linked_output(wire.node(), load.constant_port())?;
+    hugr.get_optype(const_node).as_const()?.value().as_bool()
+}
+
 fn read_inputs(consts: &[(IncomingPort, ops::Value)]) -> Option<Vec<bool>> {
-    let true_val = ops::Value::true_val();
-    let false_val = ops::Value::false_val();
-    let inps: Option<Vec<bool>> = sorted_consts(consts)
+    sorted_consts(consts)
         .into_iter()
-        .map(|c| {
-            if c == &true_val {
-                Some(true)
-            } else if c == &false_val {
-                Some(false)
-            } else {
-                None
-            }
-        })
-        .collect();
-    let inps = inps?;
-    Some(inps)
+        .map(ops::Value::as_bool)
+        .collect()
 }
 
 #[cfg(test)]
 pub(crate) mod test {
-    use super::{extension, ConcreteLogicOp, NaryLogic, NotOp, FALSE_NAME, TRUE_NAME};
+    use super::{
+        extension, ConcreteLogicOp, LogicOpBuilder, NaryLogic, NotOp, EXTENSION_ID, FALSE_NAME,
+        LOGIC_REG, TRUE_NAME,
+    };
     use crate::{
+        builder::{BuildError, Container, DFGBuilder, Dataflow, DataflowHugr},
         extension::{
-            prelude::BOOL_T,
-            simple_op::{MakeExtensionOp, MakeOpDef, MakeRegisteredOp},
+            prelude::{BOOL_T, PRELUDE_ID},
+            simple_op::{MakeExtensionOp, MakeOpDef, MakeRegisteredOp, OpLoadError},
+            PRELUDE_REGISTRY,
         },
-        ops::NamedOp,
-        Extension,
+        ops::{NamedOp, Value},
+        type_row,
+        types::FunctionType,
+        Extension, HugrView,
     };
 
+    use cool_asserts::assert_matches;
     use strum::IntoEnumIterator;
 
+    #[test]
+    fn load_from_wrong_def() {
+        let r: Extension = extension();
+        let not_def = r.get_op(&NotOp.name()).unwrap();
+        let err = NaryLogic::from_def(not_def).unwrap_err();
+        assert_eq!(err, OpLoadError::NotMember("Not".into(), r.name().clone()));
+        assert!(err.to_string().contains("logic"));
+        assert_eq!(
+            NotOp::from_def(r.get_op("And").unwrap()).unwrap_err(),
+            OpLoadError::NotMember("And".into(), r.name().clone())
+        );
+    }
+
     #[test]
     fn test_logic_extension() {
         let r: Extension = extension();
@@ -237,6 +346,17 @@ pub(crate) mod test {
         }
     }
 
+    #[test]
+    fn test_capabilities() {
+        let r: Extension = extension();
+        for op in NaryLogic::iter() {
+            let def = r.get_op(&op.name()).unwrap();
+            assert!(def.has_const_folder());
+            assert!(!def.has_lower_funcs());
+        }
+        assert!(!r.get_op(&NotOp.name()).unwrap().has_const_folder());
+    }
+
     #[test]
     fn test_conversions() {
         for def in [NaryLogic::And, NaryLogic::Or] {
@@ -248,6 +368,30 @@ pub(crate) mod test {
         NotOp::from_extension_op(&NotOp.to_extension_op().unwrap()).unwrap();
     }
 
+    #[test]
+    fn test_roundtrip() {
+        for def in [NaryLogic::And, NaryLogic::Or] {
+            for n in 1..4 {
+                def.with_n_inputs(n)
+                    .check_roundtrip(&EXTENSION_ID, &LOGIC_REG)
+                    .unwrap();
+            }
+        }
+        NotOp.check_roundtrip(&EXTENSION_ID, &LOGIC_REG).unwrap();
+
+        // The op is not defined in the prelude.
+        assert_matches!(
+            NotOp.check_roundtrip(&PRELUDE_ID, &PRELUDE_REGISTRY),
+            Err(OpLoadError::NotMember(..))
+        );
+    }
+
+    #[test]
+    fn test_nullary_rejected() {
+        assert!(NaryLogic::And.with_n_inputs(1).to_extension_op().is_some());
+        assert!(NaryLogic::And.with_n_inputs(0).to_extension_op().is_none());
+    }
+
     #[test]
     fn test_values() {
         let r: Extension = extension();
@@ -260,6 +404,72 @@ pub(crate) mod test {
         }
     }
 
+    #[test]
+    fn test_simplified_builders() {
+        let mut b = DFGBuilder::new(FunctionType::new(
+            type_row![BOOL_T, BOOL_T],
+            type_row![BOOL_T; 4],
+        ))
+        .unwrap();
+        let [x, y] = b.input_wires_arr();
+        let t = b.add_load_value(Value::true_val());
+        let f = b.add_load_value(Value::false_val());
+
+        // Identity elements are dropped.
+        assert_eq!(b.add_and_simplified([x, t]).unwrap(), x);
+        assert_eq!(b.add_or_simplified([f, x]).unwrap(), x);
+        // Absorbing elements short-circuit.
+        assert_eq!(b.add_and_simplified([x, f, y]).unwrap(), f);
+        assert_eq!(b.add_or_simplified([y, t]).unwrap(), t);
+
+        let and = b.add_and_simplified([x, t, y]).unwrap();
+        let and_op = b.hugr().get_optype(and.node());
+        assert_eq!(
+            ConcreteLogicOp::from_optype(and_op),
+            Some(NaryLogic::And.with_n_inputs(2))
+        );
+
+        let or = b.add_or_simplified([f, f]).unwrap();
+        let empty_and = b.add_and_simplified([]).unwrap();
+        let x_or_y = b.add_or_simplified([x, y]).unwrap();
+
+        b.finish_hugr_with_outputs([and, or, empty_and, x_or_y], &LOGIC_REG)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_nary_builders() {
+        let mut b = DFGBuilder::new(FunctionType::new(
+            type_row![BOOL_T; 3],
+            type_row![BOOL_T, BOOL_T],
+        ))
+        .unwrap();
+        let [x, y, z] = b.input_wires_arr();
+
+        let and = b.add_nary_and([x, y, z]).unwrap();
+        assert_eq!(
+            ConcreteLogicOp::from_optype(b.hugr().get_optype(and.node())),
+            Some(NaryLogic::And.with_n_inputs(3))
+        );
+        let or = b.add_nary_or([and]).unwrap();
+        assert_eq!(
+            ConcreteLogicOp::from_optype(b.hugr().get_optype(or.node())),
+            Some(NaryLogic::Or.with_n_inputs(1))
+        );
+        let nodes = b.hugr().node_count();
+        assert_eq!(
+            b.add_nary_and([]),
+            Err(BuildError::NoInputs { op: "And".into() })
+        );
+        assert_eq!(
+            b.add_nary_or([]),
+            Err(BuildError::NoInputs { op: "Or".into() })
+        );
+        assert_eq!(b.hugr().node_count(), nodes);
+
+        b.finish_hugr_with_outputs([and, or], &LOGIC_REG).unwrap();
+    }
+
     /// Generate a logic extension "and" operation over [`crate::prelude::BOOL_T`]
     pub(crate) fn and_op() -> ConcreteLogicOp {
         NaryLogic::And.with_n_inputs(2)
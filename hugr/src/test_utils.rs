@@ -0,0 +1,160 @@
+//! Helpers for testing code that produces HUGRs.
+//!
+//! Enabled by the `test-utils` feature.
+
+use itertools::Itertools;
+
+use crate::extension::ExtensionRegistry;
+use crate::hugr::HugrView;
+use crate::ops::custom::resolve_extension_ops;
+use crate::Hugr;
+
+/// Serializes and deserializes a HUGR, and asserts that the result is the
+/// same as the original up to a renumbering of the nodes.
+///
+/// Operations of both HUGRs are resolved against `registry` before they are
+/// compared, as extension operations are serialized as opaque operations.
+///
+/// Returns the deserialized HUGR.
+///
+/// # Panics
+///
+/// If the HUGR cannot be serialized or deserialized, or if the deserialized
+/// HUGR differs from the original. The panic message lists every difference
+/// found.
+pub fn assert_serde_roundtrip(hugr: &Hugr, registry: &ExtensionRegistry) -> Hugr {
+    let json =
+        serde_json::to_value(hugr).unwrap_or_else(|e| panic!("Failed to serialize the HUGR: {e}"));
+    let mut new_hugr: Hugr = serde_json::from_value(json)
+        .unwrap_or_else(|e| panic!("Failed to deserialize the HUGR: {e}"));
+    resolve_extension_ops(&mut new_hugr, registry)
+        .unwrap_or_else(|e| panic!("Failed to resolve the deserialized operations: {e}"));
+
+    // Serialization numbers the nodes in order.
+    let mut expected = hugr.clone();
+    expected.canonicalize_nodes(|_, _| {});
+    resolve_extension_ops(&mut expected, registry)
+        .unwrap_or_else(|e| panic!("Failed to resolve the original operations: {e}"));
+
+    let diff = hugr_diff(&expected, &new_hugr);
+    assert!(
+        diff.is_empty(),
+        "The HUGR changed in a serialization round-trip:\n  {}",
+        diff.join("\n  ")
+    );
+    new_hugr
+}
+
+/// Lists the differences between two HUGRs with the same node numbering.
+fn hugr_diff(expected: &Hugr, actual: &Hugr) -> Vec<String> {
+    let mut diff = Vec::new();
+    if expected.node_count() != actual.node_count() {
+        diff.push(format!(
+            "expected {} nodes, found {}",
+            expected.node_count(),
+            actual.node_count()
+        ));
+    }
+    if expected.root() != actual.root() {
+        diff.push(format!(
+            "expected root {}, found {}",
+            expected.root(),
+            actual.root()
+        ));
+    }
+    for node in expected.nodes() {
+        if !actual.contains_node(node) {
+            diff.push(format!("{node}: missing"));
+            continue;
+        }
+        let (old_op, new_op) = (expected.get_optype(node), actual.get_optype(node));
+        if old_op != new_op {
+            diff.push(format!("{node}: expected op {old_op:?}, found {new_op:?}"));
+        }
+        let (old_parent, new_parent) = (expected.get_parent(node), actual.get_parent(node));
+        if old_parent != new_parent {
+            diff.push(format!(
+                "{node}: expected parent {old_parent:?}, found {new_parent:?}"
+            ));
+        }
+        let old_children = expected.children(node).collect_vec();
+        let new_children = actual.children(node).collect_vec();
+        if old_children != new_children {
+            diff.push(format!(
+                "{node}: expected children {old_children:?}, found {new_children:?}"
+            ));
+        }
+        if expected.get_node_metadata(node) != actual.get_node_metadata(node) {
+            diff.push(format!("{node}: metadata differs"));
+        }
+        let (old_outputs, new_outputs) = (expected.num_outputs(node), actual.num_outputs(node));
+        if old_outputs != new_outputs {
+            diff.push(format!(
+                "{node}: expected {old_outputs} outputs, found {new_outputs}"
+            ));
+            continue;
+        }
+        for port in expected.node_outputs(node) {
+            let old_links = expected.linked_inputs(node, port).sorted().collect_vec();
+            let new_links = actual.linked_inputs(node, port).sorted().collect_vec();
+            if old_links != new_links {
+                diff.push(format!(
+                    "{node} {port}: expected links to {old_links:?}, found {new_links:?}"
+                ));
+            }
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::builder::{DFGBuilder, Dataflow, DataflowHugr};
+    use crate::extension::prelude::BOOL_T;
+    use crate::hugr::HugrMut;
+    use crate::std_extensions::logic::test::{and_op, or_op};
+    use crate::std_extensions::logic::LOGIC_REG;
+    use crate::type_row;
+    use crate::types::FunctionType;
+    use crate::OutgoingPort;
+
+    fn and_hugr() -> Hugr {
+        let mut builder = DFGBuilder::new(FunctionType::new(
+            type_row![BOOL_T, BOOL_T],
+            type_row![BOOL_T],
+        ))
+        .unwrap();
+        let [a, b] = builder.input_wires_arr();
+        let [ab] = builder
+            .add_dataflow_op(and_op(), [a, b])
+            .unwrap()
+            .outputs_arr();
+        builder.finish_hugr_with_outputs([ab], &LOGIC_REG).unwrap()
+    }
+
+    #[test]
+    fn serde_roundtrip() {
+        let hugr = and_hugr();
+        let new_hugr = assert_serde_roundtrip(&hugr, &LOGIC_REG);
+        assert!(hugr_diff(&hugr, &new_hugr).is_empty());
+    }
+
+    #[test]
+    fn diff() {
+        let hugr = and_hugr();
+        let mut other = hugr.clone();
+        let [input, _] = other.get_io(other.root()).unwrap();
+        let port = OutgoingPort::from(1);
+        other.disconnect(input, port);
+        let and = other.children(other.root()).nth(2).unwrap();
+        other
+            .map_ops(|n, _| (n == and).then(|| or_op().into()))
+            .unwrap();
+
+        let diff = hugr_diff(&hugr, &other);
+        assert_eq!(diff.len(), 2);
+        assert!(diff[0].starts_with(&format!("{input} {port}: expected links")));
+        assert!(diff[1].starts_with(&format!("{and}: expected op")));
+    }
+}
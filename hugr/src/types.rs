@@ -23,7 +23,7 @@ use itertools::FoldWhile::{Continue, Done};
 use itertools::{repeat_n, Itertools};
 use serde::{Deserialize, Serialize};
 
-use crate::extension::{ExtensionRegistry, SignatureError};
+use crate::extension::{ExtensionRegistry, ExtensionSet, SignatureError};
 use crate::ops::AliasDecl;
 use crate::type_row;
 
@@ -115,7 +115,7 @@ pub(crate) fn least_upper_bound(mut tags: impl Iterator<Item = TypeBound>) -> Ty
     .into_inner()
 }
 
-#[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Debug, Eq, Hash, Serialize, Deserialize)]
 #[serde(tag = "s")]
 #[non_exhaustive]
 /// Representation of a Sum type.
@@ -182,6 +182,11 @@ impl SumType {
             SumType::General { rows } => rows.len(),
         }
     }
+
+    /// Returns an iterator over the rows of the variants, in tag order.
+    pub fn variants(&self) -> impl Iterator<Item = &TypeRow> {
+        (0..self.num_variants()).map(|tag| self.get_variant(tag).unwrap())
+    }
 }
 
 impl From<SumType> for Type {
@@ -193,7 +198,7 @@ impl From<SumType> for Type {
     }
 }
 
-#[derive(Clone, PartialEq, Debug, Eq, derive_more::Display)]
+#[derive(Clone, PartialEq, Debug, Eq, Hash, derive_more::Display)]
 /// Core types
 pub enum TypeEnum {
     // TODO optimise with Box<CustomType> ?
@@ -233,7 +238,7 @@ impl TypeEnum {
 }
 
 #[derive(
-    Clone, PartialEq, Debug, Eq, derive_more::Display, serde::Serialize, serde::Deserialize,
+    Clone, PartialEq, Debug, Eq, Hash, derive_more::Display, serde::Serialize, serde::Deserialize,
 )]
 #[display(fmt = "{}", "_0")]
 #[serde(into = "serialize::SerSimpleType", from = "serialize::SerSimpleType")]
@@ -341,6 +346,43 @@ impl Type {
         &self.0
     }
 
+    /// If this is a sum type, returns it.
+    ///
+    /// Use [`SumType::num_variants`] and [`SumType::get_variant`] to inspect
+    /// the variants, e.g. to check the branches of a predicate.
+    pub fn as_sum(&self) -> Option<&SumType> {
+        match &self.0 {
+            TypeEnum::Sum(sum) => Some(sum),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this is a sum type with exactly `n_variants` variants,
+    /// as required of the predicate selecting a branch of a
+    /// [`Conditional`](crate::ops::Conditional), CFG block or
+    /// [`TailLoop`](crate::ops::TailLoop).
+    pub fn is_predicate(&self, n_variants: usize) -> bool {
+        self.as_sum()
+            .is_some_and(|sum| sum.num_variants() == n_variants)
+    }
+
+    /// If this is a function type, returns its signature.
+    pub fn as_function(&self) -> Option<&FunctionType> {
+        match &self.0 {
+            TypeEnum::Function(fun_ty) => Some(fun_ty),
+            _ => None,
+        }
+    }
+
+    /// If this is a function type, returns the extensions required to call a
+    /// function value of this type.
+    ///
+    /// Function types with extension requirements are built with
+    /// [`Type::new_function`] from a [`FunctionType::with_extension_delta`].
+    pub fn function_extensions(&self) -> Option<&ExtensionSet> {
+        self.as_function().map(|fun_ty| &fun_ty.extension_reqs)
+    }
+
     /// Report if the type is copyable - i.e.the least upper bound of the type
     /// is contained by the copyable bound.
     pub const fn copyable(&self) -> bool {
@@ -375,6 +417,8 @@ impl Type {
         }
     }
 
+    /// Applies a substitution to the type, also expanding any references to
+    /// type aliases of the extensions in the substitution's registry.
     pub(crate) fn substitute(&self, t: &Substitution) -> Self {
         match &self.0 {
             TypeEnum::Alias(_) | TypeEnum::Sum(SumType::Unit { .. }) => self.clone(),
@@ -384,7 +428,10 @@ impl Type {
                 };
                 ty
             }
-            TypeEnum::Extension(cty) => Type::new_extension(cty.substitute(t)),
+            TypeEnum::Extension(cty) => match cty.get_type_alias(t.extension_registry()) {
+                Some(def) => def.substitute(t),
+                None => Type::new_extension(cty.substitute(t)),
+            },
             TypeEnum::Function(bf) => Type::new_function(bf.substitute(t)),
             TypeEnum::Sum(SumType::General { rows }) => {
                 Type::new_sum(rows.iter().map(|x| subst_row(x, t)))
@@ -393,11 +440,87 @@ impl Type {
     }
 }
 
+/// Calls `f` on each [CustomType] used anywhere within a type, including in
+/// type arguments, in depth-first order.
+pub(crate) fn visit_custom_types<'a>(ty: &'a Type, f: &mut impl FnMut(&'a CustomType)) {
+    match &ty.0 {
+        TypeEnum::Extension(cty) => {
+            f(cty);
+            cty.args()
+                .iter()
+                .for_each(|a| visit_custom_types_in_arg(a, f))
+        }
+        TypeEnum::Function(ft) => ft
+            .input
+            .iter()
+            .chain(ft.output.iter())
+            .for_each(|t| visit_custom_types(t, f)),
+        TypeEnum::Sum(SumType::General { rows }) => rows
+            .iter()
+            .flat_map(TypeRow::iter)
+            .for_each(|t| visit_custom_types(t, f)),
+        TypeEnum::Alias(_) | TypeEnum::Sum(SumType::Unit { .. }) | TypeEnum::Variable(..) => (),
+    }
+}
+
+/// Calls `f` on each [CustomType] used anywhere within a type argument, see
+/// [visit_custom_types].
+pub(crate) fn visit_custom_types_in_arg<'a>(arg: &'a TypeArg, f: &mut impl FnMut(&'a CustomType)) {
+    match arg {
+        TypeArg::Type { ty } => visit_custom_types(ty, f),
+        TypeArg::Opaque { arg } => {
+            f(&arg.typ);
+            arg.typ
+                .args()
+                .iter()
+                .for_each(|a| visit_custom_types_in_arg(a, f))
+        }
+        TypeArg::Sequence { elems } => elems.iter().for_each(|a| visit_custom_types_in_arg(a, f)),
+        _ => (),
+    }
+}
+
+/// Returns the index of the first type variable used anywhere within a type,
+/// including in the extension requirements of function types.
+pub(crate) fn first_type_var(ty: &Type) -> Option<usize> {
+    match &ty.0 {
+        TypeEnum::Variable(idx, _) => Some(*idx),
+        TypeEnum::Extension(cty) => cty.args().iter().find_map(first_type_var_in_arg),
+        TypeEnum::Function(ft) => ft
+            .input
+            .iter()
+            .chain(ft.output.iter())
+            .find_map(first_type_var)
+            .or_else(|| ft.extension_reqs.type_vars().next()),
+        TypeEnum::Sum(SumType::General { rows }) => {
+            rows.iter().flat_map(TypeRow::iter).find_map(first_type_var)
+        }
+        TypeEnum::Sum(SumType::Unit { .. }) | TypeEnum::Alias(_) => None,
+    }
+}
+
+/// Returns the index of the first type variable used anywhere within a
+/// [TypeArg]. See [first_type_var].
+pub(crate) fn first_type_var_in_arg(arg: &TypeArg) -> Option<usize> {
+    match arg {
+        TypeArg::Type { ty } => first_type_var(ty),
+        TypeArg::Sequence { elems } => elems.iter().find_map(first_type_var_in_arg),
+        TypeArg::Extensions { es } => es.type_vars().next(),
+        TypeArg::Variable { v } => Some(v.index()),
+        // The type of an opaque argument cannot refer to variables.
+        TypeArg::BoundedNat { .. } | TypeArg::Opaque { .. } => None,
+    }
+}
+
 /// Details a replacement of type variables with a finite list of known values.
 /// (Variables out of the range of the list will result in a panic)
 pub(crate) struct Substitution<'a>(&'a [TypeArg], &'a ExtensionRegistry);
 
 impl<'a> Substitution<'a> {
+    pub(crate) fn new(args: &'a [TypeArg], extension_registry: &'a ExtensionRegistry) -> Self {
+        Self(args, extension_registry)
+    }
+
     pub(crate) fn apply_var(&self, idx: usize, decl: &TypeParam) -> TypeArg {
         let arg = self
             .0
@@ -413,12 +536,7 @@ impl<'a> Substitution<'a> {
 }
 
 fn subst_row(row: &TypeRow, tr: &Substitution) -> TypeRow {
-    let res = row
-        .iter()
-        .map(|ty| ty.substitute(tr))
-        .collect::<Vec<_>>()
-        .into();
-    res
+    row.iter().map(|ty| ty.substitute(tr)).collect()
 }
 
 pub(crate) fn check_typevar_decl(
@@ -449,7 +567,9 @@ pub(crate) fn check_typevar_decl(
 #[cfg(test)]
 pub(crate) mod test {
     use super::*;
-    use crate::extension::prelude::USIZE_T;
+    use crate::extension::prelude::{BOOL_T, QB_T, USIZE_T};
+    use crate::std_extensions::collections::list_type;
+    use rstest::rstest;
 
     #[test]
     fn construct() {
@@ -480,4 +600,51 @@ pub(crate) mod test {
         let pred_direct = SumType::Unit { size: 2 };
         assert_eq!(pred1, pred_direct.into())
     }
+
+    #[test]
+    fn sum_variants() {
+        let pred = Type::new_unit_sum(3);
+        let sum = pred.as_sum().unwrap();
+        assert_eq!(sum.num_variants(), 3);
+        assert_eq!(sum.get_variant(2), Some(&Type::EMPTY_TYPEROW));
+        assert_eq!(sum.get_variant(3), None);
+
+        let rows = [type_row![USIZE_T], type_row![USIZE_T, Type::UNIT]];
+        let general = Type::new_sum(rows.clone());
+        let sum = general.as_sum().unwrap();
+        assert_eq!(sum.num_variants(), 2);
+        assert_eq!(sum.variants().cloned().collect_vec(), rows);
+        assert_eq!(sum.get_variant(1), Some(&rows[1]));
+
+        assert_eq!(USIZE_T.as_sum(), None);
+    }
+
+    #[rstest]
+    #[case(Type::new_tuple(vec![USIZE_T, Type::new_tuple(vec![USIZE_T])]), true)]
+    #[case(Type::new_tuple(vec![USIZE_T, Type::new_tuple(vec![QB_T])]), false)]
+    #[case(Type::new_sum([vec![USIZE_T].into(), vec![Type::new_tuple(vec![QB_T])].into()]), false)]
+    #[case(Type::new_sum([type_row![], vec![Type::new_function(FunctionType::new_endo(vec![QB_T]))].into()]), true)]
+    #[case(list_type(Type::new_tuple(vec![USIZE_T, BOOL_T])), true)]
+    #[case(list_type(Type::new_tuple(vec![USIZE_T, QB_T])), false)]
+    #[case(Type::new_tuple(vec![list_type(QB_T)]), false)]
+    fn container_copyable(#[case] typ: Type, #[case] copyable: bool) {
+        assert_eq!(typ.copyable(), copyable);
+    }
+
+    #[test]
+    fn function_extensions() {
+        let ext = crate::extension::ExtensionId::new_unchecked("A");
+        let sig = FunctionType::new_endo(type_row![USIZE_T]).with_extension_delta(ext.clone());
+        let fun_t = Type::new_function(sig.clone());
+        assert_eq!(fun_t.as_function(), Some(&sig));
+        assert_eq!(
+            fun_t.function_extensions(),
+            Some(&ExtensionSet::singleton(&ext))
+        );
+
+        let pure_t = Type::new_function(FunctionType::new_endo(type_row![USIZE_T]));
+        assert_eq!(pure_t.function_extensions(), Some(&ExtensionSet::new()));
+        assert_eq!(USIZE_T.as_function(), None);
+        assert_eq!(USIZE_T.function_extensions(), None);
+    }
 }
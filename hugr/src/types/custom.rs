@@ -6,13 +6,13 @@ use std::fmt::{self, Display};
 use crate::extension::{ExtensionId, ExtensionRegistry, SignatureError, TypeDef};
 
 use super::{
-    type_param::{TypeArg, TypeParam},
+    type_param::{check_type_args, TypeArg, TypeParam},
     Substitution, TypeBound,
 };
 use super::{Type, TypeName};
 
 /// An opaque type element. Contains the unique identifier of its definition.
-#[derive(Debug, PartialEq, Eq, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CustomType {
     extension: ExtensionId,
     /// Unique identifier of the opaque type.
@@ -68,12 +68,36 @@ impl CustomType {
         self.args
             .iter()
             .try_for_each(|a| a.validate(extension_registry, var_decls))?;
+        // A reference to a type alias takes no arguments, and has the bound of
+        // the alias definition.
+        if let Some(definition) = self.get_type_alias(extension_registry) {
+            check_type_args(&self.args, &[])?;
+            let expected = definition.least_upper_bound();
+            if self.bound != expected {
+                return Err(SignatureError::WrongBound {
+                    actual: self.bound,
+                    expected,
+                });
+            }
+            return Ok(());
+        }
         // And check they fit into the TypeParams declared by the TypeDef
         let def = self.get_type_def(extension_registry)?;
         def.check_custom(self)
     }
 
-    fn get_type_def<'a>(
+    /// Returns the definition of the type alias this type refers to, if its
+    /// extension in the registry has a type alias with its name.
+    pub(crate) fn get_type_alias<'a>(
+        &self,
+        extension_registry: &'a ExtensionRegistry,
+    ) -> Option<&'a Type> {
+        extension_registry
+            .get(&self.extension)?
+            .get_type_alias(&self.id)
+    }
+
+    pub(crate) fn get_type_def<'a>(
         &self,
         extension_registry: &'a ExtensionRegistry,
     ) -> Result<&'a TypeDef, SignatureError> {
@@ -115,7 +115,7 @@ pub(crate) mod test {
 
     lazy_static! {
         static ref REGISTRY: ExtensionRegistry =
-            ExtensionRegistry::try_new([PRELUDE.to_owned(), EXTENSION.to_owned()]).unwrap();
+            ExtensionRegistry::with_prelude([EXTENSION.to_owned()]).unwrap();
     }
 
     impl PolyFuncType {
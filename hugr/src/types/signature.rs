@@ -10,7 +10,7 @@ use super::{subst_row, Substitution, Type, TypeRow};
 use crate::extension::{ExtensionRegistry, ExtensionSet, SignatureError};
 use crate::{Direction, IncomingPort, OutgoingPort, Port};
 
-#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 /// Describes the edges required to/from a node, and thus, also the type of a [Graph].
 /// This includes both the concept of "signature" in the spec,
 /// and also the target (value) of a call (static).
@@ -54,6 +54,27 @@ impl FunctionType {
 }
 
 impl FunctionType {
+    /// Returns a new signature with `f` applied to each input and output type,
+    /// keeping the extension requirements.
+    ///
+    /// See [`TypeRow::map`].
+    ///
+    /// ```
+    /// # use hugr::extension::prelude::{BOOL_T, QB_T, USIZE_T};
+    /// # use hugr::type_row;
+    /// # use hugr::types::FunctionType;
+    /// let sig = FunctionType::new(type_row![BOOL_T, QB_T], type_row![BOOL_T]);
+    /// let sig = sig.map_types(|t| if t == &BOOL_T { USIZE_T } else { t.clone() });
+    /// assert_eq!(sig, FunctionType::new(type_row![USIZE_T, QB_T], type_row![USIZE_T]));
+    /// ```
+    pub fn map_types(&self, mut f: impl FnMut(&Type) -> Type) -> Self {
+        Self {
+            input: self.input.map(&mut f),
+            output: self.output.map(&mut f),
+            extension_reqs: self.extension_reqs.clone(),
+        }
+    }
+
     /// The number of wires in the signature.
     #[inline(always)]
     pub fn is_empty(&self) -> bool {
@@ -17,7 +17,7 @@ use super::{check_typevar_decl, CustomType, Substitution, Type, TypeBound};
 /// The upper non-inclusive bound of a [`TypeParam::BoundedNat`]
 // A None inner value implies the maximum bound: u64::MAX + 1 (all u64 values valid)
 #[derive(
-    Clone, Debug, PartialEq, Eq, derive_more::Display, serde::Deserialize, serde::Serialize,
+    Clone, Debug, PartialEq, Eq, Hash, derive_more::Display, serde::Deserialize, serde::Serialize,
 )]
 #[display(fmt = "{}", "_0.map(|i|i.to_string()).unwrap_or(\"-\".to_string())")]
 pub struct UpperBound(Option<NonZeroU64>);
@@ -49,7 +49,7 @@ impl UpperBound {
 /// [PolyFuncType]: super::PolyFuncType
 /// [OpDef]: crate::extension::OpDef
 #[derive(
-    Clone, Debug, PartialEq, Eq, derive_more::Display, serde::Deserialize, serde::Serialize,
+    Clone, Debug, PartialEq, Eq, Hash, derive_more::Display, serde::Deserialize, serde::Serialize,
 )]
 #[non_exhaustive]
 #[serde(tag = "tp")]
@@ -59,10 +59,22 @@ pub enum TypeParam {
         /// Bound for the type parameter.
         b: TypeBound,
     },
-    /// Argument is a [TypeArg::BoundedNat] that is less than the upper bound.
+    /// Argument is a [TypeArg::BoundedNat] that is at least the lower bound
+    /// and less than the upper bound.
+    #[display(
+        fmt = "{}",
+        "match (*min, bound.0) {
+            (0, _) => bound.to_string(),
+            (min, None) => format!(\"{}..\", min),
+            (min, Some(b)) => format!(\"{}..{}\", min, b),
+        }"
+    )]
     BoundedNat {
         /// Upper bound for the Nat parameter.
         bound: UpperBound,
+        /// Lower bound (inclusive) for the Nat parameter.
+        #[serde(default, skip_serializing_if = "crate::utils::is_default")]
+        min: u64,
     },
     /// Argument is a [TypeArg::Opaque], defined by a [CustomType].
     Opaque {
@@ -91,6 +103,7 @@ impl TypeParam {
     pub const fn max_nat() -> Self {
         Self::BoundedNat {
             bound: UpperBound(None),
+            min: 0,
         }
     }
 
@@ -98,15 +111,53 @@ impl TypeParam {
     pub const fn bounded_nat(upper_bound: NonZeroU64) -> Self {
         Self::BoundedNat {
             bound: UpperBound(Some(upper_bound)),
+            min: 0,
+        }
+    }
+
+    /// [`TypeParam::BoundedNat`] with the stated lower bound (inclusive) and
+    /// upper bound (exclusive). An upper bound of `None` implies the maximum
+    /// bound (`u64::MAX` + 1).
+    pub const fn bounded_nat_range(min: u64, upper_bound: Option<NonZeroU64>) -> Self {
+        Self::BoundedNat {
+            bound: UpperBound(upper_bound),
+            min,
+        }
+    }
+
+    /// Checks that the parameter admits at least one value, and that the type
+    /// of any [TypeParam::Opaque] is valid and closed.
+    pub(crate) fn validate(
+        &self,
+        extension_registry: &ExtensionRegistry,
+    ) -> Result<(), SignatureError> {
+        match self {
+            TypeParam::Type { .. } | TypeParam::Extensions => Ok(()),
+            TypeParam::BoundedNat { bound, min } => match bound.valid_value(*min) {
+                true => Ok(()),
+                false => Err(SignatureError::EmptyTypeParam(self.clone())),
+            },
+            TypeParam::Opaque { ty } => ty.validate(extension_registry, &[]),
+            TypeParam::List { param } => param.validate(extension_registry),
+            TypeParam::Tuple { params } => params
+                .iter()
+                .try_for_each(|p| p.validate(extension_registry)),
         }
     }
 
     fn contains(&self, other: &TypeParam) -> bool {
         match (self, other) {
             (TypeParam::Type { b: b1 }, TypeParam::Type { b: b2 }) => b1.contains(*b2),
-            (TypeParam::BoundedNat { bound: b1 }, TypeParam::BoundedNat { bound: b2 }) => {
-                b1.contains(b2)
-            }
+            (
+                TypeParam::BoundedNat {
+                    bound: b1,
+                    min: min1,
+                },
+                TypeParam::BoundedNat {
+                    bound: b2,
+                    min: min2,
+                },
+            ) => b1.contains(b2) && min1 <= min2,
             (TypeParam::Opaque { ty: c1 }, TypeParam::Opaque { ty: c2 }) => c1 == c2,
             (TypeParam::List { param: e1 }, TypeParam::List { param: e2 }) => e1.contains(e2),
             (TypeParam::Tuple { params: es1 }, TypeParam::Tuple { params: es2 }) => {
@@ -126,7 +177,7 @@ impl From<TypeBound> for TypeParam {
 
 impl From<UpperBound> for TypeParam {
     fn from(bound: UpperBound) -> Self {
-        Self::BoundedNat { bound }
+        Self::BoundedNat { bound, min: 0 }
     }
 }
 
@@ -137,7 +188,7 @@ impl From<Type> for TypeArg {
 }
 
 /// A statically-known argument value to an operation.
-#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
 #[non_exhaustive]
 #[serde(tag = "tya")]
 pub enum TypeArg {
@@ -178,7 +229,7 @@ pub enum TypeArg {
 }
 
 /// Variable in a TypeArg, that is not a [TypeArg::Type] or [TypeArg::Extensions],
-#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
 pub struct TypeArgVariable {
     idx: usize,
     cached_decl: TypeParam,
@@ -268,7 +319,7 @@ impl TypeArgVariable {
 
 /// A serialized representation of a value of a [CustomType]
 /// restricted to equatable types.
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct CustomTypeArg {
     /// The type of the constant.
     /// (Exact matches only - the constant is exactly this type.)
@@ -316,10 +367,16 @@ pub fn check_type_arg(arg: &TypeArg, param: &TypeParam) -> Result<(), TypeArgErr
                     .try_for_each(|(arg, param)| check_type_arg(arg, param))
             }
         }
-        (TypeArg::BoundedNat { n: val }, TypeParam::BoundedNat { bound })
-            if bound.valid_value(*val) =>
-        {
-            Ok(())
+        (TypeArg::BoundedNat { n: val }, TypeParam::BoundedNat { bound, min }) => {
+            if *val >= *min && bound.valid_value(*val) {
+                Ok(())
+            } else {
+                Err(TypeArgError::NatOutOfRange {
+                    n: *val,
+                    min: *min,
+                    bound: bound.clone(),
+                })
+            }
         }
 
         (TypeArg::Opaque { arg }, TypeParam::Opaque { ty: param })
@@ -371,4 +428,54 @@ pub enum TypeArgError {
     /// Invalid value
     #[error("Invalid value of type argument")]
     InvalidValue(TypeArg),
+    /// A natural number argument outside the range of its parameter.
+    #[error("Type argument {n} is outside the range of the declared parameter: minimum {min}, upper bound {bound}")]
+    NatOutOfRange {
+        /// The argument value.
+        n: u64,
+        /// The lower bound (inclusive) of the parameter.
+        min: u64,
+        /// The upper bound (exclusive) of the parameter.
+        bound: UpperBound,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU64;
+
+    use super::{check_type_arg, TypeArg, TypeArgError, TypeParam, UpperBound};
+
+    #[test]
+    fn bounded_nat_range() {
+        let param = TypeParam::bounded_nat_range(2, NonZeroU64::new(9));
+        for n in 2..9 {
+            assert_eq!(check_type_arg(&TypeArg::BoundedNat { n }, &param), Ok(()));
+        }
+        for n in [0, 1, 9, 100] {
+            assert_eq!(
+                check_type_arg(&TypeArg::BoundedNat { n }, &param),
+                Err(TypeArgError::NatOutOfRange {
+                    n,
+                    min: 2,
+                    bound: UpperBound(NonZeroU64::new(9))
+                })
+            );
+        }
+
+        let unbounded = TypeParam::bounded_nat_range(1, None);
+        assert_eq!(
+            check_type_arg(&TypeArg::BoundedNat { n: u64::MAX }, &unbounded),
+            Ok(())
+        );
+        assert!(check_type_arg(&TypeArg::BoundedNat { n: 0 }, &unbounded).is_err());
+
+        assert!(unbounded.contains(&param));
+        assert!(!param.contains(&unbounded));
+        assert!(TypeParam::max_nat().contains(&unbounded));
+        assert!(!unbounded.contains(&TypeParam::max_nat()));
+        assert_eq!(param.to_string(), "2..9");
+        assert_eq!(unbounded.to_string(), "1..");
+        assert_eq!(TypeParam::max_nat().to_string(), "-");
+    }
 }
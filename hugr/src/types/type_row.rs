@@ -14,7 +14,7 @@ use delegate::delegate;
 use itertools::Itertools;
 
 /// List of types, used for function signatures.
-#[derive(Clone, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Serialize, serde::Deserialize)]
 #[non_exhaustive]
 #[serde(transparent)]
 pub struct TypeRow {
@@ -52,7 +52,20 @@ impl TypeRow {
 
     /// Returns a new `TypeRow` with `xs` concatenated onto `self`.
     pub fn extend<'a>(&'a self, rest: impl IntoIterator<Item = &'a Type>) -> Self {
-        self.iter().chain(rest).cloned().collect_vec().into()
+        Self::from_iter_ref(self.iter().chain(rest))
+    }
+
+    /// Create a new row by cloning the types yielded by an iterator of references.
+    pub fn from_iter_ref<'a>(types: impl IntoIterator<Item = &'a Type>) -> Self {
+        types.into_iter().cloned().collect()
+    }
+
+    /// Returns a new row with `f` applied to each type.
+    ///
+    /// `f` is applied to the types of the row only, not to the types nested
+    /// inside them.
+    pub fn map(&self, f: impl FnMut(&Type) -> Type) -> Self {
+        self.iter().map(f).collect()
     }
 
     /// Returns a reference to the types in the row.
@@ -105,6 +118,14 @@ impl From<Type> for TypeRow {
     }
 }
 
+impl FromIterator<Type> for TypeRow {
+    fn from_iter<I: IntoIterator<Item = Type>>(iter: I) -> Self {
+        Self {
+            types: iter.into_iter().collect_vec().into(),
+        }
+    }
+}
+
 impl Deref for TypeRow {
     type Target = [Type];
 
@@ -102,7 +102,7 @@ pub(crate) mod test_quantum_extension {
     use crate::{
         extension::{
             prelude::{BOOL_T, QB_T},
-            ExtensionId, ExtensionRegistry, PRELUDE,
+            ExtensionId, ExtensionRegistry, OpCapabilities, PRELUDE,
         },
         ops::CustomOp,
         std_extensions::arithmetic::float_types,
@@ -146,7 +146,8 @@ pub(crate) mod test_quantum_extension {
                 "Measure a qubit, returning the qubit and the measurement result.".into(),
                 FunctionType::new(type_row![QB_T], type_row![QB_T, BOOL_T]),
             )
-            .unwrap();
+            .unwrap()
+            .add_capabilities(OpCapabilities::MEASUREMENT);
 
         extension
             .add_op(
@@ -170,7 +171,8 @@ pub(crate) mod test_quantum_extension {
     lazy_static! {
         /// Quantum extension definition.
         pub static ref EXTENSION: Extension = extension();
-        static ref REG: ExtensionRegistry = ExtensionRegistry::try_new([EXTENSION.to_owned(), PRELUDE.to_owned(), float_types::EXTENSION.to_owned()]).unwrap();
+        /// Registry containing the quantum extension and its dependencies.
+        pub(crate) static ref REG: ExtensionRegistry = ExtensionRegistry::try_new([EXTENSION.to_owned(), PRELUDE.to_owned(), float_types::EXTENSION.to_owned()]).unwrap();
 
     }
 